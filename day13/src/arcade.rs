@@ -42,7 +42,6 @@ impl JoystickStates {
 pub struct Arcade {
     pub computer: IntcodeComputer,
     pub screen: HashMap<(i64,i64), Tile>,
-    joystickState: JoystickStates,
     pub ball_pos: (i64, i64),
     pub horizontal_pos: (i64, i64),
     pub score: i64
@@ -50,12 +49,10 @@ pub struct Arcade {
 
 impl Arcade {
     pub fn new(intcodes: Vec<i64>) -> Arcade {
-        let mut computer = IntcodeComputer::new(intcodes);
-        computer.show_stdinout = false;
+        let computer = IntcodeComputer::new(intcodes);
         Arcade {
             computer,
             screen: HashMap::new(),
-            joystickState: JoystickStates::Neutral,
             ball_pos: (0, 0),
             horizontal_pos: (0, 0),
             score: 0
@@ -76,14 +73,13 @@ impl Arcade {
             if (x == -1) && (y == 0) {
                 self.score = value;
             } else {
-                let tile = Tile::get_tile(value as usize);
-                if tile.is_some() {
-                    if let Some(Tile::Ball) = tile {
+                if let Some(tile) = Tile::get_tile(value as usize) {
+                    if let Tile::Ball = tile {
                         self.ball_pos = (x,y);
-                    } else if let Some(Tile::Horizontal) = tile {
+                    } else if let Tile::Horizontal = tile {
                         self.horizontal_pos = (x,y);
                     }
-                    self.screen.insert((x,y), tile.unwrap());
+                    self.screen.insert((x,y), tile);
                 }
             }
             if self.computer.terminated {
@@ -96,6 +92,17 @@ impl Arcade {
         self.computer.set_memory_input(direction.state_to_instruction());
     }
 
+    /// Tilt the joystick towards the ball
+    pub fn follow_ball(&mut self) {
+        if self.ball_pos.0 < self.horizontal_pos.0 {
+            self.joystick_input(JoystickStates::LeftTilt);
+        } else if self.ball_pos.0 > self.horizontal_pos.0 {
+            self.joystick_input(JoystickStates::RightTilt);
+        } else {
+            self.joystick_input(JoystickStates::Neutral);
+        }
+    }
+
     pub fn get_screen(&self) -> String {
         // first find max dimension of field
         let mut screen = String::new();
@@ -116,7 +123,7 @@ impl Arcade {
                 y_range.1 = field.1
             }
         }
-        for y in (y_range.0..=y_range.1) {
+        for y in y_range.0..=y_range.1 {
             for x in x_range.0..=x_range.1 {
                 let ch = match self.screen.get(&(x, y)) {
                     Some(Tile::Empty) => ".",
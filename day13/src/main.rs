@@ -4,8 +4,10 @@ use std::{thread, time};
 use ncurses;
 
 mod computer;
+mod memory;
 mod arcade;
-use arcade::{Arcade, Tile, JoystickStates};
+mod scanner;
+use arcade::{Arcade, Tile};
 
 fn read_input() -> Result<Vec<i64>, Error> {
     let content = fs::read_to_string("./input")?;
@@ -48,13 +50,7 @@ fn part2(mut intcodes: Vec<i64>) {
     ncurses::refresh();
     ncurses::halfdelay(2);
     loop {
-        if arcade.ball_pos.0 < arcade.horizontal_pos.0 {
-            arcade.joystick_input(JoystickStates::LeftTilt);
-        } else if arcade.ball_pos.0 > arcade.horizontal_pos.0 {
-            arcade.joystick_input(JoystickStates::RightTilt);
-        } else {
-            arcade.joystick_input(JoystickStates::Neutral);
-        }
+        arcade.follow_ball();
 
         // continue and update screen
         arcade.run();
@@ -74,8 +70,40 @@ fn part2(mut intcodes: Vec<i64>) {
     ncurses::endwin();
 }
 
+/// Find where the game keeps its state, then cheat by patching the score
+fn part3(intcodes: Vec<i64>) {
+    println!("PART 3");
+    let located = scanner::locate(&intcodes, &[
+        |arcade| arcade.ball_pos.0,
+        |arcade| arcade.horizontal_pos.0,
+        |arcade| arcade.score
+    ]);
+    for (name, scanner) in ["Ball x", "Paddle x", "Score"].iter().zip(located.iter()) {
+        match scanner {
+            Some(scanner) => println!("{} is stored at {:?}", name, scanner.candidates()),
+            None => println!("{} was not found in memory", name)
+        }
+    }
+    let score = match &located[2] {
+        Some(score) => score,
+        None => return
+    };
+
+    let mut intcodes = intcodes;
+    intcodes[0] = 2;
+    let mut arcade = Arcade::new(intcodes);
+    arcade.run();
+    score.patch(&mut arcade.computer, 1_000_000).unwrap();
+    while !arcade.computer.terminated {
+        arcade.follow_ball();
+        arcade.run();
+    }
+    println!("Final score with patched score: {}", arcade.score);
+}
+
 fn main() {
     let intcodes = read_input().unwrap();
     part1(intcodes.clone());
+    part3(intcodes.clone());
     part2(intcodes);
 }
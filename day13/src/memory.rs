@@ -0,0 +1,21 @@
+// Direct access to the memory of the computer for the memory scanner, kept
+// out of computer.rs so that it stays the same in every day.
+
+use crate::computer::IntcodeComputer;
+
+impl IntcodeComputer {
+    /// Get the value stored in memory at address
+    pub fn read_memory(&self, address: usize) -> i64 {
+        self.intcodes[address]
+    }
+
+    /// Overwrite the value stored in memory at address
+    pub fn write_memory(&mut self, address: usize, value: i64) {
+        self.intcodes[address] = value;
+    }
+
+    /// Number of addressable memory cells
+    pub fn memory_size(&self) -> usize {
+        self.intcodes.len()
+    }
+}
@@ -0,0 +1,127 @@
+use crate::arcade::Arcade;
+use crate::computer::IntcodeComputer;
+use std::ops::Range;
+
+/// Condition a memory cell has to fulfill, compared to the last snapshot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    Unchanged,
+    Changed,
+    Increased,
+    Decreased,
+    Equals(i64),
+}
+
+impl Filter {
+    fn matches(&self, before: i64, now: i64) -> bool {
+        match self {
+            Filter::Unchanged => now == before,
+            Filter::Changed => now != before,
+            Filter::Increased => now > before,
+            Filter::Decreased => now < before,
+            Filter::Equals(value) => now == *value,
+        }
+    }
+}
+
+/// Searches the memory of a running program for the cells holding some
+/// game state, in the style of a cheat engine: take a snapshot, let the
+/// program run and keep only the cells that changed as expected. Repeat
+/// until few candidates remain.
+pub struct MemoryScanner {
+    /// candidate addresses with their value in the last snapshot
+    candidates: Vec<(usize, i64)>,
+}
+
+impl MemoryScanner {
+    /// Snapshot the complete memory, every cell is a candidate
+    pub fn new(computer: &IntcodeComputer) -> MemoryScanner {
+        MemoryScanner::in_range(computer, 0..computer.memory_size())
+    }
+
+    /// Snapshot only the memory in the range of addresses
+    pub fn in_range(computer: &IntcodeComputer, range: Range<usize>) -> MemoryScanner {
+        let candidates = range
+            .map(|address| (address, computer.read_memory(address)))
+            .collect();
+        MemoryScanner { candidates }
+    }
+
+    /// Keep the candidates matching the filter and snapshot them again.
+    /// Returns the number of remaining candidates.
+    pub fn filter(&mut self, computer: &IntcodeComputer, filter: Filter) -> usize {
+        self.candidates
+            .retain(|(address, before)| filter.matches(*before, computer.read_memory(*address)));
+        for (address, value) in self.candidates.iter_mut() {
+            *value = computer.read_memory(*address);
+        }
+        self.candidates.len()
+    }
+
+    /// Addresses that matched every filter so far
+    pub fn candidates(&self) -> Vec<usize> {
+        self.candidates
+            .iter()
+            .map(|(address, _)| *address)
+            .collect()
+    }
+
+    /// True once at most one candidate is left
+    pub fn converged(&self) -> bool {
+        self.candidates.len() <= 1
+    }
+
+    /// Write the value into every remaining candidate, fails if there is
+    /// none left
+    pub fn patch(&self, computer: &mut IntcodeComputer, value: i64) -> Result<(), String> {
+        if self.candidates.is_empty() {
+            return Err(String::from("No candidate left to patch."));
+        }
+        for (address, _) in self.candidates.iter() {
+            computer.write_memory(*address, value);
+        }
+        Ok(())
+    }
+}
+
+/// Locate the memory cells of game values by scanning the memory after every
+/// frame of a single game, the values are known from the drawn screen.
+/// Returns a scanner for every value, None if no cell kept matching it.
+pub fn locate(intcodes: &[i64], values: &[fn(&Arcade) -> i64]) -> Vec<Option<MemoryScanner>> {
+    let mut intcodes = intcodes.to_vec();
+    intcodes[0] = 2;
+    let mut arcade = Arcade::new(intcodes);
+    arcade.run();
+    let mut scanners: Vec<MemoryScanner> = values
+        .iter()
+        .map(|value| {
+            let mut scanner = MemoryScanner::new(&arcade.computer);
+            scanner.filter(&arcade.computer, Filter::Equals(value(&arcade)));
+            scanner
+        })
+        .collect();
+    while !scanners.iter().all(MemoryScanner::converged) && !arcade.computer.terminated {
+        let before: Vec<i64> = values.iter().map(|value| value(&arcade)).collect();
+        // play as in part 2, so every value keeps changing
+        arcade.follow_ball();
+        arcade.run();
+        for ((scanner, value), before) in scanners.iter_mut().zip(values.iter()).zip(before) {
+            if scanner.converged() {
+                continue;
+            }
+            let now = value(&arcade);
+            if now > before {
+                scanner.filter(&arcade.computer, Filter::Increased);
+            } else if now < before {
+                scanner.filter(&arcade.computer, Filter::Decreased);
+            } else {
+                scanner.filter(&arcade.computer, Filter::Unchanged);
+            }
+            scanner.filter(&arcade.computer, Filter::Equals(now));
+        }
+    }
+    scanners
+        .into_iter()
+        .map(|scanner| if scanner.candidates.is_empty() { None } else { Some(scanner) })
+        .collect()
+}
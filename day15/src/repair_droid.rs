@@ -79,7 +79,6 @@ pub struct RepairDroid {
 impl RepairDroid {
     pub fn new(intcodes: Vec<i64>) -> RepairDroid {
         let mut computer = IntcodeComputer::new(intcodes);
-        computer.show_stdinout = false;
         // init computer
         computer.run();
         RepairDroid {
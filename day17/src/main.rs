@@ -1,4 +1,3 @@
-use std::char;
 use std::fs;
 use std::io::Error;
 use std::cmp;
@@ -17,15 +16,6 @@ fn read_input() -> Result<Vec<i64>, Error> {
     Ok(digits)
 }
 
-fn print_map(map: &Vec<Vec<char>>) {
-    for i in 0..map.len() {
-        for j in 0..map[0].len() {
-            print!("{}", map[i][j]);
-        }
-        println!();
-    }
-}
-
 fn part1(intcodes: Vec<i64>) {
     println!("PART 1");
     let mut robot = VacuumRobot::new(intcodes);
@@ -198,7 +188,7 @@ fn part2(mut intcodes: Vec<i64>) {
     }
 
     // chain of commands determined, now feed into robot
-    // robot.computer.show_stdinout = true;
+    // robot.computer.add_observer(Box::new(StdInOutTrace::new(std::io::stdout())));
     robot.run();
     robot.run_chain_of_inputs(unit_pattern);
     robot.run();
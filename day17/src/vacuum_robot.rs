@@ -41,7 +41,6 @@ impl VacuumRobot {
     pub fn new(intcodes: Vec<i64>) -> VacuumRobot {
         let mut computer = IntcodeComputer::new(intcodes.clone());
         // initialize camera
-        computer.show_stdinout = false;
         let mut map: Vec<Vec<char>> = Vec::new();
         map.push(Vec::new());
         let mut n_rows = 0;
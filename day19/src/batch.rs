@@ -0,0 +1,61 @@
+use crate::computer::IntcodeComputer;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Run the computer with the given inputs. Collects the outputs until the
+/// program halts or requests more input than given.
+fn run_on(computer: &mut IntcodeComputer, inputs: &[i64]) -> Vec<i64> {
+    let mut inputs = inputs.iter();
+    let mut outputs = Vec::new();
+    loop {
+        computer.run();
+        if computer.terminated {
+            break;
+        }
+        if computer.requesting_input {
+            match inputs.next() {
+                Some(input) => computer.set_memory_input(*input),
+                None => break,
+            }
+        } else {
+            outputs.push(computer.memory_output);
+        }
+    }
+    outputs
+}
+
+/// Run the program once for every list of inputs as in run_on,
+/// spread over all available cores. The outputs are in the order of the
+/// inputs. The program is loaded once, every worker resets its computer to
+/// that state for each job.
+pub fn run_batch(intcodes: &[i64], inputs: &[Vec<i64>]) -> Vec<Vec<i64>> {
+    let n_threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let prepared = IntcodeComputer::new(intcodes.to_vec()).snapshot();
+    let next_job = AtomicUsize::new(0);
+    let mut results = vec![Vec::new(); inputs.len()];
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..n_threads.min(inputs.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut computer = IntcodeComputer::new(Vec::new());
+                    let mut finished = Vec::new();
+                    loop {
+                        let job = next_job.fetch_add(1, Ordering::Relaxed);
+                        if job >= inputs.len() {
+                            break;
+                        }
+                        computer.restore(&prepared);
+                        finished.push((job, run_on(&mut computer, &inputs[job])));
+                    }
+                    finished
+                })
+            })
+            .collect();
+        for worker in workers {
+            for (job, outputs) in worker.join().expect("Batch worker panicked.") {
+                results[job] = outputs;
+            }
+        }
+    });
+    results
+}
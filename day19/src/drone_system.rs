@@ -1,3 +1,4 @@
+use crate::batch::run_batch;
 use crate::computer::IntcodeComputer;
 use std::fmt;
 
@@ -32,14 +33,12 @@ pub struct DroneSystem {
 
 impl DroneSystem {
     pub fn new(intcodes: Vec<i64>) -> DroneSystem {
-        let mut computer = IntcodeComputer::new(intcodes.clone());
-        computer.show_stdinout = false;
+        let computer = IntcodeComputer::new(intcodes.clone());
         DroneSystem { computer, intcodes }
     }
 
     fn restart_computer(&mut self) {
         self.computer = IntcodeComputer::new(self.intcodes.clone());
-        self.computer.show_stdinout = false;
     }
 
     pub fn deploy_drone(&mut self, x: usize, y: usize) -> DroneState {
@@ -55,4 +54,22 @@ impl DroneSystem {
             panic!("Computer output invalid drone state upon drone deployment.")
         }
     }
+
+    /// Deploy a drone to every point, the drones run in parallel.
+    /// Returns the states in the order of the points.
+    pub fn scan(&self, points: &[(usize, usize)]) -> Vec<DroneState> {
+        let inputs: Vec<Vec<i64>> = points
+            .iter()
+            .map(|&(x, y)| vec![x as i64, y as i64])
+            .collect();
+        run_batch(&self.intcodes, &inputs)
+            .iter()
+            .map(|outputs| {
+                outputs
+                    .first()
+                    .and_then(|&output| DroneState::output_to_state(output))
+                    .expect("Computer output invalid drone state upon drone deployment.")
+            })
+            .collect()
+    }
 }
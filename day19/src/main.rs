@@ -1,8 +1,10 @@
 use std::fs;
 use std::io::Error;
 
+mod batch;
 mod computer;
 mod drone_system;
+mod snapshot;
 use drone_system::{DroneState, DroneSystem};
 
 const SCAN_RANGE: usize = 50;
@@ -26,16 +28,17 @@ fn print_map(map: &[[char; SCAN_RANGE]; SCAN_RANGE]) {
 }
 
 fn part1(intcodes: Vec<i64>) {
-    let mut drone_system = DroneSystem::new(intcodes);
+    let drone_system = DroneSystem::new(intcodes);
     let mut tractor_beam_counter = 0;
     let mut map: [[char; SCAN_RANGE]; SCAN_RANGE] = [['.'; SCAN_RANGE]; SCAN_RANGE];
-    for i in 0..SCAN_RANGE {
-        for j in 0..SCAN_RANGE {
-            let state = drone_system.deploy_drone(i, j);
-            if let DroneState::Pulled = state {
-                map[i][j] = '#';
-                tractor_beam_counter += 1;
-            }
+    let points: Vec<(usize, usize)> = (0..SCAN_RANGE)
+        .flat_map(|i| (0..SCAN_RANGE).map(move |j| (i, j)))
+        .collect();
+    let states = drone_system.scan(&points);
+    for (&(i, j), state) in points.iter().zip(states.iter()) {
+        if let DroneState::Pulled = state {
+            map[i][j] = '#';
+            tractor_beam_counter += 1;
         }
     }
     print_map(&map);
@@ -0,0 +1,42 @@
+// Saving and restoring the state of the computer, kept out of computer.rs
+// so that it stays the same in every day.
+
+use crate::computer::IntcodeComputer;
+
+/// State of an IntcodeComputer at some point of the execution
+#[derive(Clone)]
+pub struct Snapshot {
+    intcodes: Vec<i64>,
+    ip: usize,
+    memory_input: Option<i64>,
+    memory_output: i64,
+    relative_base: i64,
+    requesting_input: bool,
+    terminated: bool,
+}
+
+impl IntcodeComputer {
+    /// Save the complete state of the computer, except for the observers
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            intcodes: self.intcodes.clone(),
+            ip: self.ip,
+            memory_input: self.memory_input,
+            memory_output: self.memory_output,
+            relative_base: self.relative_base,
+            requesting_input: self.requesting_input,
+            terminated: self.terminated,
+        }
+    }
+
+    /// Continue from a saved state
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.intcodes.copy_from_slice(&snapshot.intcodes);
+        self.ip = snapshot.ip;
+        self.memory_input = snapshot.memory_input;
+        self.memory_output = snapshot.memory_output;
+        self.relative_base = snapshot.relative_base;
+        self.requesting_input = snapshot.requesting_input;
+        self.terminated = snapshot.terminated;
+    }
+}
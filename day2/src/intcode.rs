@@ -0,0 +1,40 @@
+/// Run the intcodes until opcode 99 is reached, the result is left in the
+/// intcodes themselves. Knows opcodes
+/// 1 (add): add values at addresses of first and second parameter, store at third
+/// 2 (multiply): multiply values at addresses of first and second parameter, store at third
+/// 99 (terminate)
+pub fn run_intcodes(intcodes: &mut [usize]) {
+    run_intcodes_within(intcodes, usize::MAX);
+}
+
+/// Same as run_intcodes, but gives up after `max_steps` instructions.
+/// Returns whether opcode 99 was reached
+pub fn run_intcodes_within(intcodes: &mut [usize], max_steps: usize) -> bool {
+    let mut ip = 0; // instruction pointer
+    for _ in 0..max_steps {
+        match intcodes[ip] {
+            1 => {
+                let i = intcodes[ip+1];
+                let j = intcodes[ip+2];
+                let k = intcodes[ip+3];
+                intcodes[k] = intcodes[i] + intcodes[j];
+            },
+            2 => {
+                let i = intcodes[ip+1];
+                let j = intcodes[ip+2];
+                let k = intcodes[ip+3];
+                intcodes[k] = intcodes[i] * intcodes[j];
+            },
+            99 => {
+                return true;
+            },
+            _ => {
+                panic!(
+                    "Error while reading intcodes. Unknown opcode {} at \
+                    position: {}", intcodes[ip], ip);
+            }
+        }
+        ip += 4;
+    }
+    false
+}
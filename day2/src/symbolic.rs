@@ -0,0 +1,356 @@
+use crate::intcode::run_intcodes;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Value of a memory cell during symbolic execution
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Const(i64),
+    /// initial, unknown content of the memory cell at this address
+    Symbol(usize),
+    Add(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    /// content of memory at an address only known symbolically
+    Load(Box<Expr>),
+}
+
+impl Expr {
+    fn add(a: &Expr, b: &Expr) -> Expr {
+        match (a, b) {
+            (Expr::Const(a), Expr::Const(b)) => Expr::Const(a + b),
+            _ => Expr::Add(Box::new(a.clone()), Box::new(b.clone())),
+        }
+    }
+
+    fn mul(a: &Expr, b: &Expr) -> Expr {
+        match (a, b) {
+            (Expr::Const(a), Expr::Const(b)) => Expr::Const(a * b),
+            _ => Expr::Mul(Box::new(a.clone()), Box::new(b.clone())),
+        }
+    }
+
+    /// Expand the expression into a sum of monomials. Not possible if the
+    /// expression loads from a symbolic address.
+    pub fn polynomial(&self) -> Option<Polynomial> {
+        match self {
+            Expr::Const(value) => Some(Polynomial::constant(*value)),
+            Expr::Symbol(address) => Some(Polynomial::symbol(*address)),
+            Expr::Add(a, b) => Some(a.polynomial()?.add(&b.polynomial()?)),
+            Expr::Mul(a, b) => Some(a.polynomial()?.mul(&b.polynomial()?)),
+            Expr::Load(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Const(value) => write!(f, "{}", value),
+            Expr::Symbol(address) => write!(f, "{}", symbol_name(*address)),
+            Expr::Add(a, b) => write!(f, "({} + {})", a, b),
+            Expr::Mul(a, b) => write!(f, "{} * {}", a, b),
+            Expr::Load(address) => write!(f, "mem[{}]", address),
+        }
+    }
+}
+
+/// Name of a symbolic memory cell, addresses 1 and 2 are noun and verb
+fn symbol_name(address: usize) -> String {
+    match address {
+        1 => String::from("noun"),
+        2 => String::from("verb"),
+        _ => format!("m{}", address),
+    }
+}
+
+/// Sum of monomials, each monomial is the sorted list of symbols multiplied
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial {
+    pub terms: BTreeMap<Vec<usize>, i64>,
+}
+
+impl Polynomial {
+    fn constant(value: i64) -> Polynomial {
+        let mut terms = BTreeMap::new();
+        terms.insert(Vec::new(), value);
+        Polynomial { terms }
+    }
+
+    fn symbol(address: usize) -> Polynomial {
+        let mut terms = BTreeMap::new();
+        terms.insert(vec![address], 1);
+        Polynomial { terms }
+    }
+
+    fn add(&self, other: &Polynomial) -> Polynomial {
+        let mut terms = self.terms.clone();
+        for (monomial, coefficient) in other.terms.iter() {
+            *terms.entry(monomial.clone()).or_insert(0) += coefficient;
+        }
+        terms.retain(|_, coefficient| *coefficient != 0);
+        Polynomial { terms }
+    }
+
+    fn mul(&self, other: &Polynomial) -> Polynomial {
+        let mut terms = BTreeMap::new();
+        for (monomial_a, coefficient_a) in self.terms.iter() {
+            for (monomial_b, coefficient_b) in other.terms.iter() {
+                let mut monomial = monomial_a.clone();
+                monomial.extend(monomial_b.iter());
+                monomial.sort_unstable();
+                *terms.entry(monomial).or_insert(0) += coefficient_a * coefficient_b;
+            }
+        }
+        terms.retain(|_, coefficient: &mut i64| *coefficient != 0);
+        Polynomial { terms }
+    }
+
+    /// Coefficient of a monomial, 0 if not present
+    pub fn coefficient(&self, monomial: &[usize]) -> i64 {
+        *self.terms.get(monomial).unwrap_or(&0)
+    }
+
+    pub fn is_linear(&self) -> bool {
+        self.terms.keys().all(|monomial| monomial.len() <= 1)
+    }
+
+    /// Evaluate with the given (address, value) assignment of the symbols
+    pub fn evaluate(&self, assignment: &[(usize, i64)]) -> i64 {
+        let value_of = |address: &usize| -> i64 {
+            assignment
+                .iter()
+                .find(|(symbol, _)| symbol == address)
+                .map(|(_, value)| *value)
+                .unwrap_or(0)
+        };
+        self.terms
+            .iter()
+            .map(|(monomial, coefficient)| {
+                coefficient * monomial.iter().map(value_of).product::<i64>()
+            })
+            .sum()
+    }
+}
+
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut terms = Vec::new();
+        // highest degree first, constant last
+        for (monomial, coefficient) in self.terms.iter().rev() {
+            let symbols: Vec<String> = monomial
+                .iter()
+                .map(|&address| symbol_name(address))
+                .collect();
+            terms.push(match (coefficient, symbols.is_empty()) {
+                (_, true) => coefficient.to_string(),
+                (1, false) => symbols.join("*"),
+                _ => format!("{}*{}", coefficient, symbols.join("*")),
+            });
+        }
+        if terms.is_empty() {
+            terms.push(String::from("0"));
+        }
+        write!(f, "{}", terms.join(" + "))
+    }
+}
+
+/// How a symbolic value was used, such that the path taken depends on it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Usage {
+    Opcode,
+    ReadAddress,
+    WriteAddress,
+}
+
+/// Symbolic value the execution path depended on at instruction pointer ip
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathConstraint {
+    pub ip: usize,
+    pub usage: Usage,
+    pub expr: Expr,
+}
+
+impl fmt::Display for PathConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let usage = match self.usage {
+            Usage::Opcode => "opcode",
+            Usage::ReadAddress => "read address",
+            Usage::WriteAddress => "write address",
+        };
+        write!(f, "ip {}: {} is {}", self.ip, usage, self.expr)
+    }
+}
+
+/// Result of running the intcodes with symbolic memory cells
+pub struct Execution {
+    /// final memory, None if the execution could not continue symbolically
+    pub memory: Option<Vec<Expr>>,
+    pub constraints: Vec<PathConstraint>,
+}
+
+/// Run the intcodes like run_intcodes, but with the cells at the symbolic
+/// addresses unknown. Reading from a symbolic address yields a Load; an
+/// opcode or a write address that is not concrete stops the execution, as
+/// the rest of the path depends on the symbols.
+pub fn execute_symbolic(intcodes: &[usize], symbolic: &[usize]) -> Execution {
+    let mut memory: Vec<Expr> = intcodes
+        .iter()
+        .enumerate()
+        .map(|(address, &value)| {
+            if symbolic.contains(&address) {
+                Expr::Symbol(address)
+            } else {
+                Expr::Const(value as i64)
+            }
+        })
+        .collect();
+    let mut constraints = Vec::new();
+    let mut ip = 0;
+    loop {
+        let opcode = match &memory[ip] {
+            Expr::Const(opcode) => *opcode,
+            expr => {
+                constraints.push(PathConstraint {
+                    ip,
+                    usage: Usage::Opcode,
+                    expr: expr.clone(),
+                });
+                return Execution {
+                    memory: None,
+                    constraints,
+                };
+            }
+        };
+        match opcode {
+            1 | 2 => {
+                let mut operands = Vec::new();
+                for offset in 1..3 {
+                    operands.push(match &memory[ip + offset] {
+                        Expr::Const(address) => memory[*address as usize].clone(),
+                        expr => {
+                            constraints.push(PathConstraint {
+                                ip,
+                                usage: Usage::ReadAddress,
+                                expr: expr.clone(),
+                            });
+                            Expr::Load(Box::new(expr.clone()))
+                        }
+                    });
+                }
+                let target = match &memory[ip + 3] {
+                    Expr::Const(address) => *address as usize,
+                    expr => {
+                        constraints.push(PathConstraint {
+                            ip,
+                            usage: Usage::WriteAddress,
+                            expr: expr.clone(),
+                        });
+                        return Execution {
+                            memory: None,
+                            constraints,
+                        };
+                    }
+                };
+                memory[target] = if opcode == 1 {
+                    Expr::add(&operands[0], &operands[1])
+                } else {
+                    Expr::mul(&operands[0], &operands[1])
+                };
+            }
+            99 => {
+                break;
+            }
+            _ => {
+                panic!(
+                    "Error while reading intcodes. Unknown opcode {} at \
+                     position: {}",
+                    opcode, ip
+                );
+            }
+        }
+        ip += 4;
+    }
+    Execution {
+        memory: Some(memory),
+        constraints,
+    }
+}
+
+/// How the noun and verb were found
+#[derive(Debug, Clone, PartialEq)]
+pub enum Method {
+    /// output is linear in noun and verb and was solved for them
+    Linear,
+    /// output is a polynomial in noun and verb, evaluated for every pair
+    Polynomial,
+    /// path depends on noun or verb, the intcodes were rerun for every pair
+    Enumeration,
+}
+
+pub struct Solution {
+    pub noun: usize,
+    pub verb: usize,
+    pub method: Method,
+    /// the expression at address 0, if it could be determined
+    pub output: Option<Expr>,
+    pub constraints: Vec<PathConstraint>,
+}
+
+/// Find noun and verb in 0..=99 such that address 0 ends up being target
+pub fn solve_noun_verb(intcodes: &[usize], target: usize) -> Option<Solution> {
+    let execution = execute_symbolic(intcodes, &[1, 2]);
+    let output = execution.memory.map(|memory| memory[0].clone());
+    let polynomial = output.as_ref().and_then(|output| output.polynomial());
+    let target = target as i64;
+
+    let (noun, verb, method) = match &polynomial {
+        Some(polynomial) if polynomial.is_linear() => {
+            // target = c + a * noun + b * verb, the verb follows from the noun
+            let a = polynomial.coefficient(&[1]);
+            let b = polynomial.coefficient(&[2]);
+            let c = polynomial.coefficient(&[]);
+            let (noun, verb) = (0..100).find_map(|noun| {
+                let remainder = target - c - a * noun;
+                let verb = match b {
+                    0 if remainder == 0 => 0,
+                    0 => return None,
+                    _ if remainder % b != 0 => return None,
+                    _ => remainder / b,
+                };
+                if (0..100).contains(&verb) {
+                    Some((noun, verb))
+                } else {
+                    None
+                }
+            })?;
+            (noun, verb, Method::Linear)
+        }
+        Some(polynomial) => {
+            let (noun, verb) = noun_verb_pairs().find(|&(noun, verb)| {
+                polynomial.evaluate(&[(1, noun as i64), (2, verb as i64)]) == target
+            })?;
+            (noun as i64, verb as i64, Method::Polynomial)
+        }
+        None => {
+            let (noun, verb) = noun_verb_pairs().find(|&(noun, verb)| {
+                let mut memory = intcodes.to_vec();
+                memory[1] = noun;
+                memory[2] = verb;
+                run_intcodes(&mut memory);
+                memory[0] as i64 == target
+            })?;
+            (noun as i64, verb as i64, Method::Enumeration)
+        }
+    };
+    Some(Solution {
+        noun: noun as usize,
+        verb: verb as usize,
+        method,
+        output,
+        constraints: execution.constraints,
+    })
+}
+
+fn noun_verb_pairs() -> impl Iterator<Item = (usize, usize)> {
+    (0..100).flat_map(|noun| (0..100).map(move |verb| (noun, verb)))
+}
@@ -0,0 +1,77 @@
+use crate::hull::HullPattern;
+use crate::springscript::Mode;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// Hull patterns the droid fell on, kept in a file with one pattern per
+/// line, preceded by the mode it was seen in:
+///
+/// ```text
+/// WALK #####.#..########
+/// RUN #####.#.##..#####
+/// ```
+pub struct Corpus {
+    path: PathBuf,
+    patterns: BTreeSet<(Mode, HullPattern)>
+}
+
+impl Corpus {
+    /// Read the corpus from the file, starting empty if it does not exist yet
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Corpus, Error> {
+        let path = path.as_ref().to_path_buf();
+        let mut patterns = BTreeSet::new();
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(error)
+        };
+        for (n, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid = |message: String| {
+                Error::new(ErrorKind::InvalidData, format!("{}:{}: {}", path.display(), n + 1, message))
+            };
+            let mut words = line.split_whitespace();
+            let mode: Mode = words.next().unwrap().parse().map_err(|error| invalid(format!("{}", error)))?;
+            let hull: HullPattern = words
+                .next()
+                .ok_or_else(|| invalid(String::from("Missing hull pattern.")))?
+                .parse()
+                .map_err(invalid)?;
+            patterns.insert((mode, hull));
+        }
+        Ok(Corpus { path, patterns })
+    }
+
+    /// Add a pattern, returns false if it is known already
+    pub fn add(&mut self, mode: Mode, hull: HullPattern) -> bool {
+        self.patterns.insert((mode, hull))
+    }
+
+    /// All patterns seen in the mode
+    pub fn patterns(&self, mode: Mode) -> impl Iterator<Item = &HullPattern> {
+        self.patterns
+            .iter()
+            .filter(move |(pattern_mode, _)| *pattern_mode == mode)
+            .map(|(_, hull)| hull)
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let content: String = self.patterns
+            .iter()
+            .map(|(mode, hull)| format!("{} {}\n", mode, hull))
+            .collect();
+        fs::write(&self.path, content)
+    }
+}
@@ -0,0 +1,459 @@
+use crate::logic::{self, Compiler, Cube, TruthTable};
+use crate::springscript::{Mode, Op, Reg, ScriptError, SpringScript, MAX_INSTRUCTIONS};
+use std::fmt;
+use std::ops;
+use std::str::FromStr;
+
+/// Boolean expression over the sensors, true for ground. Written like
+/// `(!C & D & (E | H)) | !A`, with ! binding closer than & and & closer
+/// than |.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Const(bool),
+    Sensor(Reg),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompileError {
+    /// the shortest script found needs that many instructions
+    TooLong(usize),
+    Script(ScriptError)
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompileError::TooLong(length) => write!(
+                f,
+                "The expression needs {} instructions, only {} fit.",
+                length, MAX_INSTRUCTIONS
+            ),
+            CompileError::Script(error) => write!(f, "{}", error)
+        }
+    }
+}
+
+impl ops::Not for Expr {
+    type Output = Expr;
+
+    fn not(self) -> Expr {
+        Expr::Not(Box::new(self))
+    }
+}
+
+impl Expr {
+    /// Value for the sensor readings, bit i set for ground at sensor i
+    pub fn evaluate(&self, reading: logic::Reading) -> bool {
+        match self {
+            Expr::Const(value) => *value,
+            Expr::Sensor(sensor) => reading & 1 << *sensor as usize != 0,
+            Expr::Not(expr) => !expr.evaluate(reading),
+            Expr::And(operands) => operands.iter().all(|operand| operand.evaluate(reading)),
+            Expr::Or(operands) => operands.iter().any(|operand| operand.evaluate(reading))
+        }
+    }
+
+    /// Number of sensors up to the last one the expression reads
+    pub fn sensors(&self) -> usize {
+        match self {
+            Expr::Const(_) => 0,
+            Expr::Sensor(sensor) => *sensor as usize + 1,
+            Expr::Not(expr) => expr.sensors(),
+            Expr::And(operands) | Expr::Or(operands) => {
+                operands.iter().map(|operand| operand.sensors()).max().unwrap_or(0)
+            }
+        }
+    }
+
+    /// Number of constants and sensors in the expression
+    pub fn size(&self) -> usize {
+        match self {
+            Expr::Const(_) | Expr::Sensor(_) => 1,
+            Expr::Not(expr) => expr.size(),
+            Expr::And(operands) | Expr::Or(operands) => operands.iter().map(|operand| operand.size()).sum()
+        }
+    }
+
+    /// Value for every reading of the sensors the expression reads
+    pub fn truth_table(&self) -> TruthTable {
+        let mut table = TruthTable::new(self.sensors());
+        for reading in 0..1 << table.sensors {
+            table.rows.insert(reading, self.evaluate(reading));
+        }
+        table
+    }
+
+    /// Sum of products
+    pub fn from_cubes(cubes: &[Cube]) -> Expr {
+        let products: Vec<Expr> = cubes
+            .iter()
+            .map(|cube| {
+                let literals: Vec<Expr> = cube.literals()
+                    .iter()
+                    .map(|&(sensor, ground)| {
+                        if ground { Expr::Sensor(sensor) } else { !Expr::Sensor(sensor) }
+                    })
+                    .collect();
+                combine(literals, true)
+            })
+            .collect();
+        combine(products, false)
+    }
+
+    /// Equivalent expression of the smallest size found, from rewriting the
+    /// expression or from its minimised truth table, preferring the sum of
+    /// products
+    pub fn simplify(&self) -> Expr {
+        let rewritten = self.rewrite();
+        let minimised = Expr::from_cubes(&logic::minimise(&self.truth_table()));
+        if minimised.size() <= rewritten.size() { minimised } else { rewritten }
+    }
+
+    /// Fold constants, double negations, negations of negated operands,
+    /// nested and duplicate operands, complements (x & !x) and absorbed
+    /// operands (x | x & y)
+    fn rewrite(&self) -> Expr {
+        match self {
+            Expr::Const(_) | Expr::Sensor(_) => self.clone(),
+            Expr::Not(expr) => {
+                let negated = |operands: &[Expr]| operands.iter().all(|operand| matches!(operand, Expr::Not(_)));
+                match expr.rewrite() {
+                    Expr::Const(value) => Expr::Const(!value),
+                    Expr::Not(inner) => *inner,
+                    // !(!x & !y) = x | y, !(!x | !y) = x & y
+                    Expr::And(operands) if negated(&operands) => {
+                        combine(operands.into_iter().map(|operand| !operand).collect(), false).rewrite()
+                    }
+                    Expr::Or(operands) if negated(&operands) => {
+                        combine(operands.into_iter().map(|operand| !operand).collect(), true).rewrite()
+                    }
+                    other => !other
+                }
+            }
+            Expr::And(operands) | Expr::Or(operands) => {
+                let is_and = matches!(self, Expr::And(_));
+                let mut flat = Vec::new();
+                for operand in operands.iter() {
+                    match (operand.rewrite(), is_and) {
+                        // the neutral element
+                        (Expr::Const(value), _) if value == is_and => (),
+                        (Expr::Const(value), _) => return Expr::Const(value),
+                        (Expr::And(inner), true) | (Expr::Or(inner), false) => flat.extend(inner),
+                        (other, _) => flat.push(other)
+                    }
+                }
+                let mut kept: Vec<Expr> = Vec::new();
+                for operand in flat {
+                    if !kept.contains(&operand) {
+                        kept.push(operand);
+                    }
+                }
+                if kept.iter().any(|operand| kept.contains(&!operand.clone())) {
+                    return Expr::Const(!is_and);
+                }
+                let absorbed = |operand: &Expr| match (operand, is_and) {
+                    (Expr::Or(inner), true) | (Expr::And(inner), false) => {
+                        inner.iter().any(|part| kept.contains(part))
+                    }
+                    _ => false
+                };
+                let kept: Vec<Expr> = kept.iter().filter(|operand| !absorbed(operand)).cloned().collect();
+                combine(kept, is_and)
+            }
+        }
+    }
+
+    /// Shortest script found that sets J to the expression: compiled from
+    /// the rewritten expression, from the minimised truth table or from
+    /// the minimised complement
+    pub fn compile(&self, mode: Mode) -> Result<SpringScript, CompileError> {
+        let table = self.truth_table();
+        let mut candidates = vec![
+            logic::compile(&logic::minimise(&table), false),
+            logic::compile(&logic::minimise(&table.complement()), true),
+        ];
+        if let Some(compiler) = compile_into(&self.rewrite(), Reg::J, true, Compiler::new()) {
+            candidates.push(compiler.instructions);
+        }
+        let instructions = candidates.into_iter().min_by_key(|instructions| instructions.len()).unwrap();
+        if instructions.len() > MAX_INSTRUCTIONS {
+            return Err(CompileError::TooLong(instructions.len()));
+        }
+        let mut script = SpringScript::new(mode);
+        for instruction in instructions {
+            script.push(instruction).map_err(CompileError::Script)?;
+        }
+        Ok(script)
+    }
+}
+
+/// Conjunction or disjunction of the operands, without nesting single ones
+fn combine(mut operands: Vec<Expr>, is_and: bool) -> Expr {
+    match operands.len() {
+        0 => Expr::Const(is_and),
+        1 => operands.pop().unwrap(),
+        _ if is_and => Expr::And(operands),
+        _ => Expr::Or(operands)
+    }
+}
+
+fn other(reg: Reg) -> Reg {
+    if reg == Reg::T { Reg::J } else { Reg::T }
+}
+
+/// Compile the expression into reg, with the other register free to use
+/// if `scratch`. An operand of & and | that is not a sensor needs a free
+/// register, so the expression can only be compiled if no such operand is
+/// nested in another one. Tries every operand to compute first and keeps
+/// the shortest instructions.
+fn compile_into(expr: &Expr, reg: Reg, scratch: bool, mut compiler: Compiler) -> Option<Compiler> {
+    match expr {
+        Expr::Const(true) => compiler.cube(&Cube { mask: 0, value: 0 }, reg),
+        Expr::Const(false) => {
+            if !compiler.is_fresh(reg) {
+                compiler.emit(Op::Not, Reg::A, reg);
+                compiler.emit(Op::And, Reg::A, reg);
+            }
+        }
+        Expr::Sensor(sensor) => compiler.load(*sensor, true, reg),
+        Expr::Not(inner) => match inner.as_ref() {
+            Expr::Sensor(sensor) => compiler.load(*sensor, false, reg),
+            _ => {
+                compiler = compile_into(inner, reg, scratch, compiler)?;
+                compiler.emit(Op::Not, reg, reg);
+            }
+        },
+        Expr::And(operands) | Expr::Or(operands) => {
+            let op = if matches!(expr, Expr::And(_)) { Op::And } else { Op::Or };
+            return (0..operands.len())
+                .filter_map(|first| {
+                    let rest: Vec<&Expr> = operands
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| *i != first)
+                        .map(|(_, operand)| operand)
+                        .collect();
+                    let compiler = compile_into(&operands[first], reg, scratch, compiler.clone())?;
+                    combine_into(op, &rest, reg, scratch, compiler)
+                })
+                .min_by_key(|compiler| compiler.instructions.len());
+        }
+    }
+    Some(compiler)
+}
+
+/// reg = reg op operand op operand ...
+fn combine_into(op: Op, operands: &[&Expr], reg: Reg, scratch: bool, mut compiler: Compiler) -> Option<Compiler> {
+    let mut negated = Vec::new();
+    let mut nested = Vec::new();
+    for operand in operands.iter() {
+        match operand {
+            Expr::Sensor(sensor) => compiler.emit(op, *sensor, reg),
+            Expr::Not(inner) => match inner.as_ref() {
+                Expr::Sensor(sensor) => negated.push(*sensor),
+                _ => nested.push(*operand)
+            },
+            _ => nested.push(*operand)
+        }
+    }
+    match negated.as_slice() {
+        [] => (),
+        [sensor] if scratch => {
+            compiler.emit(Op::Not, *sensor, other(reg));
+            compiler.emit(op, other(reg), reg);
+        }
+        sensors => {
+            // reg & !x & !y = !(!reg | x | y), reg | !x | !y = !(!reg & x & y)
+            let dual = if op == Op::And { Op::Or } else { Op::And };
+            compiler.emit(Op::Not, reg, reg);
+            for sensor in sensors.iter() {
+                compiler.emit(dual, *sensor, reg);
+            }
+            compiler.emit(Op::Not, reg, reg);
+        }
+    }
+    if nested.is_empty() {
+        return Some(compiler);
+    }
+    if !scratch {
+        return None;
+    }
+    nested_into(op, &nested, reg, compiler)
+}
+
+/// reg = reg op operand op operand ..., computing the operands in the other
+/// register. Tries every order, only the first one finds it still false.
+fn nested_into(op: Op, operands: &[&Expr], reg: Reg, compiler: Compiler) -> Option<Compiler> {
+    if operands.is_empty() {
+        return Some(compiler);
+    }
+    (0..operands.len())
+        .filter_map(|next| {
+            let mut compiler = compile_into(operands[next], other(reg), false, compiler.clone())?;
+            compiler.emit(op, other(reg), reg);
+            let rest: Vec<&Expr> = operands
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != next)
+                .map(|(_, operand)| *operand)
+                .collect();
+            nested_into(op, &rest, reg, compiler)
+        })
+        .min_by_key(|compiler| compiler.instructions.len())
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // operands of & need parentheses if they are |
+        let operand = |expr: &Expr, parent_is_and: bool| match expr {
+            Expr::Or(_) if parent_is_and => format!("({})", expr),
+            _ => format!("{}", expr)
+        };
+        match self {
+            Expr::Const(value) => write!(f, "{}", value),
+            Expr::Sensor(sensor) => write!(f, "{}", sensor),
+            Expr::Not(expr) => match expr.as_ref() {
+                Expr::And(_) | Expr::Or(_) => write!(f, "!({})", expr),
+                _ => write!(f, "!{}", expr)
+            },
+            Expr::And(operands) => {
+                let parts: Vec<String> = operands.iter().map(|expr| operand(expr, true)).collect();
+                write!(f, "{}", parts.join(" & "))
+            }
+            Expr::Or(operands) => {
+                let parts: Vec<String> = operands.iter().map(|expr| operand(expr, false)).collect();
+                write!(f, "{}", parts.join(" | "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Const(bool),
+    Sensor(Reg),
+    Not,
+    And,
+    Or,
+    Open,
+    Close
+}
+
+/// Split the text into tokens with the column they start at. && and || are
+/// read as & and |.
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let column = i + 1;
+        let token = match chars[i] {
+            c if c.is_whitespace() => None,
+            '!' => Some(Token::Not),
+            '&' => Some(Token::And),
+            '|' => Some(Token::Or),
+            '(' => Some(Token::Open),
+            ')' => Some(Token::Close),
+            c if c.is_ascii_alphanumeric() => {
+                let word: String = chars[i..].iter().take_while(|c| c.is_ascii_alphanumeric()).collect();
+                i += word.len() - 1;
+                match word.as_str() {
+                    "true" => Some(Token::Const(true)),
+                    "false" => Some(Token::Const(false)),
+                    _ => match word.parse::<Reg>() {
+                        Ok(sensor) if !sensor.is_writable() => Some(Token::Sensor(sensor)),
+                        _ => return Err(format!("column {}: {} is not a sensor.", column, word))
+                    }
+                }
+            }
+            c => return Err(format!("column {}: Unexpected '{}'.", column, c))
+        };
+        if let Some(token) = token {
+            let doubled = matches!(token, Token::And | Token::Or) && tokens.last() == Some(&(column - 1, token));
+            if !doubled {
+                tokens.push((column, token));
+            }
+        }
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the tokens
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    /// column after the text, for errors at its end
+    end: usize
+}
+
+impl Parser {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.position).map(|&(_, token)| token)
+    }
+
+    fn error(&self, message: &str) -> String {
+        let column = self.tokens.get(self.position).map(|&(column, _)| column).unwrap_or(self.end);
+        format!("column {}: {}", column, message)
+    }
+
+    /// Operands separated by the operator, each parsed by `operand`
+    fn list(&mut self, separator: Token, operand: fn(&mut Parser) -> Result<Expr, String>) -> Result<Expr, String> {
+        let mut operands = vec![operand(self)?];
+        while self.peek() == Some(separator) {
+            self.position += 1;
+            operands.push(operand(self)?);
+        }
+        Ok(combine(operands, separator == Token::And))
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        self.list(Token::Or, Parser::and)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        self.list(Token::And, Parser::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        let token = self.peek().ok_or_else(|| self.error("Expression ends too early."))?;
+        self.position += 1;
+        match token {
+            Token::Not => Ok(!self.unary()?),
+            Token::Const(value) => Ok(Expr::Const(value)),
+            Token::Sensor(sensor) => Ok(Expr::Sensor(sensor)),
+            Token::Open => {
+                let expr = self.or()?;
+                if self.peek() != Some(Token::Close) {
+                    return Err(self.error("Expected ')'."));
+                }
+                self.position += 1;
+                Ok(expr)
+            }
+            _ => {
+                self.position -= 1;
+                Err(self.error("Expected a sensor, a constant, '!' or '('."))
+            }
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Expr, String> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+            end: s.chars().count() + 1
+        };
+        let expr = parser.or()?;
+        if parser.position < parser.tokens.len() {
+            return Err(parser.error("Unexpected input after the expression."));
+        }
+        Ok(expr)
+    }
+}
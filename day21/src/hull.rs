@@ -0,0 +1,136 @@
+use crate::springscript::{Mode, ScriptError, SpringScript};
+use std::fmt;
+use std::str::FromStr;
+
+/// Tiles the droid moves forward with a jump
+pub const JUMP_DISTANCE: usize = 4;
+
+/// A stretch of hull as the droid program draws it, '#' for ground and '.'
+/// for a hole. The droid starts on the first tile.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HullPattern {
+    /// true for ground
+    pub tiles: Vec<bool>
+}
+
+impl HullPattern {
+    /// Whether there is ground at the position, everything behind the
+    /// pattern is ground
+    pub fn is_ground(&self, position: usize) -> bool {
+        self.tiles.get(position).cloned().unwrap_or(true)
+    }
+
+    /// Hull of the animation the droid program prints after
+    /// "Didn't make it across:". Every frame shows the droid above the hull,
+    /// the first one with the droid standing on the first tile.
+    pub fn from_crash(text: &str) -> Option<HullPattern> {
+        text.lines()
+            .skip_while(|line| !line.starts_with("Didn't make it across"))
+            .find(|line| line.contains('#'))
+            .and_then(|line| line.parse().ok())
+    }
+}
+
+impl FromStr for HullPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<HullPattern, String> {
+        let tiles = s
+            .trim()
+            .chars()
+            .map(|tile| match tile {
+                '#' | '@' => Ok(true),
+                '.' => Ok(false),
+                _ => Err(format!("Unknown hull tile {}.", tile))
+            })
+            .collect::<Result<Vec<bool>, String>>()?;
+        if tiles.is_empty() {
+            return Err(String::from("Empty hull pattern."));
+        }
+        Ok(HullPattern { tiles })
+    }
+}
+
+impl fmt::Display for HullPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &tile in self.tiles.iter() {
+            write!(f, "{}", if tile { '#' } else { '.' })?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// the droid made it past the end of the pattern
+    Survived,
+    /// the droid stepped or jumped into the hole at the position
+    Fell(usize)
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Survived => write!(f, "survived"),
+            Outcome::Fell(position) => write!(f, "fell into the hole at {}", position)
+        }
+    }
+}
+
+/// Model of the droid walking over the hull
+#[derive(Debug, Clone, Copy)]
+pub struct Simulator {
+    /// tiles in front of the droid the sensors see
+    pub sensors: usize,
+    pub jump: usize
+}
+
+impl Simulator {
+    pub fn new(mode: Mode) -> Simulator {
+        Simulator {
+            sensors: mode.sensors().len(),
+            jump: JUMP_DISTANCE
+        }
+    }
+
+    /// Readings of the sensors for the droid standing at the position
+    pub fn sensors(&self, hull: &HullPattern, position: usize) -> Vec<bool> {
+        (1..=self.sensors).map(|distance| hull.is_ground(position + distance)).collect()
+    }
+
+    /// Let the droid run the script at every tile it stands on until it is
+    /// past the pattern or falls. Fails if the simulator has fewer sensors
+    /// than the script reads.
+    pub fn run(&self, script: &SpringScript, hull: &HullPattern) -> Result<Outcome, ScriptError> {
+        let mut error = None;
+        let outcome = self.run_with(hull, |sensors| match script.jumps(sensors) {
+            Ok(jumps) => Some(jumps),
+            Err(script_error) => {
+                error = Some(script_error);
+                None
+            }
+        });
+        match error {
+            Some(error) => Err(error),
+            None => Ok(outcome.unwrap())
+        }
+    }
+
+    /// Let the droid decide with a function of the sensor readings whether
+    /// to jump. Stops with None as soon as the function has no answer.
+    pub fn run_with<F>(&self, hull: &HullPattern, mut jumps: F) -> Option<Outcome>
+    where F: FnMut(&[bool]) -> Option<bool> {
+        let mut position = 0;
+        while position + 1 < hull.tiles.len() {
+            position += if jumps(&self.sensors(hull, position))? {
+                self.jump
+            } else {
+                1
+            };
+            if !hull.is_ground(position) {
+                return Some(Outcome::Fell(position));
+            }
+        }
+        Some(Outcome::Survived)
+    }
+}
@@ -0,0 +1,306 @@
+use crate::springscript::{Mode, Op, Reg, ScriptError, SpringInstr, SpringScript};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Sensor readings as bits, bit i is set if sensor i (A for 0) sees ground
+pub type Reading = u16;
+
+pub fn reading(sensors: &[bool]) -> Reading {
+    sensors
+        .iter()
+        .enumerate()
+        .filter(|(_, &ground)| ground)
+        .fold(0, |bits, (i, _)| bits | 1 << i)
+}
+
+/// Conjunction of sensor literals: the sensors in `mask` read `value`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Cube {
+    pub mask: Reading,
+    pub value: Reading
+}
+
+impl Cube {
+    pub fn contains(&self, reading: Reading) -> bool {
+        reading & self.mask == self.value
+    }
+
+    /// Sensors of the cube with whether they have to see ground
+    pub fn literals(&self) -> Vec<(Reg, bool)> {
+        Reg::SENSORS
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.mask & 1 << i != 0)
+            .map(|(i, &sensor)| (sensor, self.value & 1 << i != 0))
+            .collect()
+    }
+
+    /// Estimated instructions to compute the cube and add it to J
+    fn cost(&self) -> u32 {
+        self.mask.count_ones() + 1
+    }
+}
+
+impl fmt::Display for Cube {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let literals: Vec<String> = self.literals()
+            .iter()
+            .map(|(sensor, ground)| format!("{}{}", if *ground { "" } else { "!" }, sensor))
+            .collect();
+        if literals.is_empty() {
+            write!(f, "true")
+        } else {
+            write!(f, "{}", literals.join(" & "))
+        }
+    }
+}
+
+/// Boolean function of the first `sensors` sensors. Only the readings in
+/// `rows` matter, the function may be anything for the others.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TruthTable {
+    pub sensors: usize,
+    pub rows: BTreeMap<Reading, bool>
+}
+
+impl TruthTable {
+    pub fn new(sensors: usize) -> TruthTable {
+        TruthTable {
+            sensors,
+            rows: BTreeMap::new()
+        }
+    }
+
+    pub fn complement(&self) -> TruthTable {
+        TruthTable {
+            sensors: self.sensors,
+            rows: self.rows.iter().map(|(&reading, &value)| (reading, !value)).collect()
+        }
+    }
+
+    /// Readings the function has to be true or false for
+    fn readings(&self, value: bool) -> Vec<Reading> {
+        self.rows.iter().filter(|(_, &row)| row == value).map(|(&reading, _)| reading).collect()
+    }
+}
+
+/// Cubes that contain none of the readings where the function is false and
+/// are not part of a larger such cube
+fn prime_implicants(sensors: usize, off: &[Reading]) -> Vec<Cube> {
+    let is_implicant = |cube: Cube| !off.iter().any(|&reading| cube.contains(reading));
+    let mut primes = Vec::new();
+    for mask in 0..(1 << sensors) as Reading {
+        // walk through all subsets of the mask as values
+        let mut value = mask;
+        loop {
+            let cube = Cube { mask, value };
+            let is_prime = is_implicant(cube)
+                && (0..sensors).filter(|i| mask & 1 << i != 0).all(|i| {
+                    let larger = Cube { mask: mask & !(1 << i), value: value & !(1 << i) };
+                    !is_implicant(larger)
+                });
+            if is_prime {
+                primes.push(cube);
+            }
+            if value == 0 {
+                break;
+            }
+            value = (value - 1) & mask;
+        }
+    }
+    primes
+}
+
+/// Branch and bound for the cheapest set of primes covering the readings
+fn cover(uncovered: &[Reading], primes: &[Cube], chosen: &mut Vec<Cube>, best: &mut Option<(u32, Vec<Cube>)>) {
+    let cost: u32 = chosen.iter().map(|cube| cube.cost()).sum();
+    if let Some((best_cost, _)) = best {
+        if cost >= *best_cost {
+            return;
+        }
+    }
+    // the reading with the fewest primes to choose from
+    let reading = match uncovered
+        .iter()
+        .min_by_key(|&&reading| primes.iter().filter(|cube| cube.contains(reading)).count()) {
+        Some(&reading) => reading,
+        None => {
+            *best = Some((cost, chosen.clone()));
+            return;
+        }
+    };
+    let mut candidates: Vec<(usize, Cube)> = primes
+        .iter()
+        .filter(|cube| cube.contains(reading))
+        .map(|&cube| (uncovered.iter().filter(|&&other| cube.contains(other)).count(), cube))
+        .collect();
+    candidates.sort_by_key(|&(covered, cube)| (std::cmp::Reverse(covered), cube.cost()));
+    for (_, cube) in candidates {
+        let rest: Vec<Reading> = uncovered.iter().cloned().filter(|&other| !cube.contains(other)).collect();
+        chosen.push(cube);
+        cover(&rest, primes, chosen, best);
+        chosen.pop();
+    }
+}
+
+/// Cheapest sum of products that agrees with every row of the table
+pub fn minimise(table: &TruthTable) -> Vec<Cube> {
+    let on = table.readings(true);
+    let off = table.readings(false);
+    let primes: Vec<Cube> = prime_implicants(table.sensors, &off)
+        .into_iter()
+        .filter(|cube| on.iter().any(|&reading| cube.contains(reading)))
+        .collect();
+    let mut best = None;
+    cover(&on, &primes, &mut Vec::new(), &mut best);
+    let mut cubes = best.map(|(_, cubes)| cubes).unwrap_or_default();
+    cubes.sort();
+    cubes
+}
+
+/// Emits instructions and keeps track of which of T and J still hold
+/// their initial false
+#[derive(Clone)]
+pub(crate) struct Compiler {
+    pub(crate) instructions: Vec<SpringInstr>,
+    fresh_t: bool,
+    fresh_j: bool
+}
+
+impl Compiler {
+    pub(crate) fn new() -> Compiler {
+        Compiler {
+            instructions: Vec::new(),
+            fresh_t: true,
+            fresh_j: true
+        }
+    }
+
+    pub(crate) fn emit(&mut self, op: Op, src: Reg, dst: Reg) {
+        self.instructions.push(SpringInstr { op, src, dst });
+        if dst == Reg::T {
+            self.fresh_t = false;
+        } else {
+            self.fresh_j = false;
+        }
+    }
+
+    pub(crate) fn is_fresh(&self, reg: Reg) -> bool {
+        if reg == Reg::T { self.fresh_t } else { self.fresh_j }
+    }
+
+    /// reg = sensor, or reg = !sensor
+    pub(crate) fn load(&mut self, sensor: Reg, ground: bool, reg: Reg) {
+        if !ground {
+            self.emit(Op::Not, sensor, reg);
+        } else if self.is_fresh(reg) {
+            self.emit(Op::Or, sensor, reg);
+        } else {
+            self.emit(Op::Not, sensor, reg);
+            self.emit(Op::Not, reg, reg);
+        }
+    }
+
+    /// reg = cube. Negated literals are combined with De Morgan, as there
+    /// is no register left to hold them: !x & !y = !(x | y)
+    pub(crate) fn cube(&mut self, cube: &Cube, reg: Reg) {
+        let literals = cube.literals();
+        let holes: Vec<Reg> = literals.iter().filter(|(_, ground)| !ground).map(|(sensor, _)| *sensor).collect();
+        let grounds: Vec<Reg> = literals.iter().filter(|(_, ground)| *ground).map(|(sensor, _)| *sensor).collect();
+        let rest = match (holes.split_first(), grounds.split_first()) {
+            (None, None) => {
+                // constant true
+                if self.is_fresh(reg) {
+                    self.emit(Op::Not, reg, reg);
+                } else {
+                    self.emit(Op::Not, Reg::A, reg);
+                    self.emit(Op::Or, Reg::A, reg);
+                }
+                &grounds[..]
+            }
+            (None, Some((first, rest))) => {
+                self.load(*first, true, reg);
+                rest
+            }
+            (Some((hole, [])), _) => {
+                self.load(*hole, false, reg);
+                &grounds[..]
+            }
+            (Some((hole, others)), _) => {
+                self.load(*hole, true, reg);
+                for other in others.iter() {
+                    self.emit(Op::Or, *other, reg);
+                }
+                self.emit(Op::Not, reg, reg);
+                &grounds[..]
+            }
+        };
+        for sensor in rest.iter() {
+            self.emit(Op::And, *sensor, reg);
+        }
+    }
+}
+
+/// Compile J = cube | cube | ..., or its negation. The first cube is
+/// computed in J, all others in T and added to J, except for single
+/// sensors that can be added directly.
+fn compile_order(cubes: &[&Cube], sensors: &[Reg], negate: bool) -> Vec<SpringInstr> {
+    let mut compiler = Compiler::new();
+    for (i, cube) in cubes.iter().enumerate() {
+        if i == 0 {
+            compiler.cube(cube, Reg::J);
+        } else {
+            compiler.cube(cube, Reg::T);
+            compiler.emit(Op::Or, Reg::T, Reg::J);
+        }
+    }
+    for sensor in sensors.iter() {
+        compiler.emit(Op::Or, *sensor, Reg::J);
+    }
+    if negate {
+        compiler.emit(Op::Not, Reg::J, Reg::J);
+    }
+    compiler.instructions
+}
+
+/// Shortest instructions setting J to the sum of products, or to its
+/// negation. Tries every choice for the cubes computed while J and T are
+/// still false.
+pub fn compile(cubes: &[Cube], negate: bool) -> Vec<SpringInstr> {
+    let sensors: Vec<Reg> = cubes
+        .iter()
+        .filter_map(|cube| match cube.literals().as_slice() {
+            [(sensor, true)] => Some(*sensor),
+            _ => None
+        })
+        .collect();
+    let others: Vec<&Cube> = cubes.iter().filter(|cube| !matches!(cube.literals().as_slice(), [(_, true)])).collect();
+    let mut best = compile_order(&others, &sensors, negate);
+    for first in 0..others.len() {
+        for second in 0..others.len() {
+            if first == second {
+                continue;
+            }
+            let mut order = vec![others[first], others[second]];
+            order.extend(others.iter().enumerate().filter(|(i, _)| *i != first && *i != second).map(|(_, cube)| cube));
+            let instructions = compile_order(&order, &sensors, negate);
+            if instructions.len() < best.len() {
+                best = instructions;
+            }
+        }
+    }
+    best
+}
+
+/// Shortest script found for the table, either from the minimised function
+/// or from its minimised complement and a final negation
+pub fn script_for(table: &TruthTable, mode: Mode) -> Result<SpringScript, ScriptError> {
+    let direct = compile(&minimise(table), false);
+    let negated = compile(&minimise(&table.complement()), true);
+    let instructions = if negated.len() < direct.len() { negated } else { direct };
+    let mut script = SpringScript::new(mode);
+    for instruction in instructions {
+        script.push(instruction)?;
+    }
+    Ok(script)
+}
@@ -1,9 +1,21 @@
+use std::env;
 use std::fs;
 use std::io::Error;
 
 mod computer;
+mod corpus;
+mod expression;
+mod hull;
+mod logic;
 mod springdroid;
-use springdroid::SpringDroid;
+mod springscript;
+mod synthesis;
+use corpus::Corpus;
+use expression::Expr;
+use hull::{HullPattern, Simulator};
+use springdroid::{DroidError, SpringDroid};
+use springscript::{Mode, Reg, ScriptError, SpringScript};
+use synthesis::synthesise;
 
 fn read_input() -> Result<Vec<i64>, Error> {
     let content = fs::read_to_string("./input")?;
@@ -15,69 +27,188 @@ fn read_input() -> Result<Vec<i64>, Error> {
     Ok(digits)
 }
 
-fn part1(intcodes: Vec<i64>) {
-    println!("Part 1");
-    let mut springdroid = SpringDroid::new(intcodes);
-    // a jump takes you 4 fields further
-    springdroid.run();
+/// Hull patterns the droid fell on so far
+const CORPUS: &str = "./hull_patterns";
+
+/// Print the hull damage, or the hull the droid fell on and add it to the
+/// corpus
+fn report(result: Result<i64, DroidError>, mode: Mode) {
+    match result {
+        Ok(damage) => println!("{}", damage),
+        Err(DroidError::Fell(hull)) => {
+            println!("Didn't make it across {}", hull);
+            let mut corpus = Corpus::load(CORPUS).unwrap();
+            if corpus.add(mode, hull) {
+                corpus.save().unwrap();
+                println!("Added to {}, {} patterns known.", CORPUS, corpus.len());
+            }
+        }
+        Err(error) => println!("{}", error)
+    }
+}
+
+fn walk_script() -> Result<SpringScript, ScriptError> {
+    let mut script = SpringScript::new(Mode::Walk);
     //case 1: 1 field in front is a hole: J = !1
-    springdroid.input_ascii("NOT A J"); // jump if the next field is a hole 
+    script.not(Reg::A, Reg::J)?; // jump if the next field is a hole
 
     //case 2: 3 fields in front is a hole and the fourth field is free to jump to
     // T = !3 && 4
-    springdroid.input_ascii("NOT C T"); // jump if 3 fields in front is hole
-    springdroid.input_ascii("AND D T"); // and the fourth field is not a hole
+    script
+        .not(Reg::C, Reg::T)? // jump if 3 fields in front is hole
+        .and(Reg::D, Reg::T)?; // and the fourth field is not a hole
 
     // J = J || T
-    springdroid.input_ascii("OR T J"); // jump in case 1 and 2
-    springdroid.input_ascii("WALK");
+    script.or(Reg::T, Reg::J)?; // jump in case 1 and 2
+    Ok(script)
+}
+
+fn part1(intcodes: Vec<i64>) {
+    println!("Part 1");
+    let mut springdroid = SpringDroid::new(intcodes);
+    // a jump takes you 4 fields further
+    let script = walk_script().unwrap();
+    report(springdroid.execute(&script), script.mode());
+}
+
+fn run_script() -> Result<SpringScript, ScriptError> {
+    SpringScript::parse("
+        // J, T are initialised as false
+        // check if 3 is hole and 4 is free field: !3 && 4
+        NOT C J
+        AND D J
+
+        // check if both 5 and 8 are holes: !5 && !8, in this case set T to false, otherwise its true
+        // !(!5 && !8) is the same as (5||f) || 8
+        OR E T
+        OR H T
+
+        // if 3 is hole & 4 is free, J is true, however if 5 & 8 are holes J should become false
+        // J = (!3 && 4) && !(!5 && !8)
+        AND T J
+
+        // set T to same value as J
+        NOT J T
+        NOT T T
+
+        // if J is true, it should remain true (only apply OR to J) -> field in range of 3
+        // causes jump
+        // if jump is false, check if fields in range 2 can cause a jump
+        // in this case T is false (as T = J)
+        // jump if both 2 and 5 are holes, as otherwise next step 1 & 4 are holes -> dead
+        // T = !2 && !5 = !( (2||f) || 5)
+        OR B T
+        OR E T
+        NOT T T
+
+        // if J was true, it's still true. if it was false, it's true now if 2 and 5 are holes
+        OR T J
+
+        // last check: if 1 is a hole: jump
+        NOT A T
+        OR T J
+        RUN
+    ")
 }
 
 fn part2(intcodes: Vec<i64>) {
     println!("Part 2");
     let mut springdroid = SpringDroid::new(intcodes);
-    springdroid.run();
-    // J, T are initialised as false
-    // check if 3 is hole and 4 is free field: !3 && 4
-    springdroid.input_ascii("NOT C J");
-    springdroid.input_ascii("AND D J");
-
-    // check if both 5 and 8 are holes: !5 && !8, in this case set T to false, otherwise its true
-    // !(!5 && !8) is the same as (5||f) || 8
-    springdroid.input_ascii("OR E T"); //  
-    springdroid.input_ascii("OR H T"); 
- 
-    // if 3 is hole & 4 is free, J is true, however if 5 & 8 are holes J should become false
-    // J = (!3 && 4) && !(!5 && !8)
-    springdroid.input_ascii("AND T J");
-
-    // set T to same value as J
-    springdroid.input_ascii("NOT J T");
-    springdroid.input_ascii("NOT T T");
-
-    // if J is true, it should remain true (only apply OR to J) -> field in range of 3 
-    // causes jump
-    // if jump is false, check if fields in range 2 can cause a jump
-    // in this case T is false (as T = J)
-    // jump if both 2 and 5 are holes, as otherwise next step 1 & 4 are holes -> dead
-    // T = !2 && !5 = !( (2||f) || 5)
-    springdroid.input_ascii("OR B T");
-    springdroid.input_ascii("OR E T");
-    springdroid.input_ascii("NOT T T");
-
-    // if J was true, it's still true. if it was false, it's true now if 2 and 5 are holes
-    springdroid.input_ascii("OR T J"); 
-    
-    // last check: if 1 is a hole: jump
-    springdroid.input_ascii("NOT A T");
-    springdroid.input_ascii("OR T J");
-
-    springdroid.input_ascii("RUN");
+    let script = run_script().unwrap();
+    report(springdroid.execute(&script), script.mode());
+}
+
+/// Let the droid try a springscript from a file
+fn try_script(intcodes: Vec<i64>, path: &str) {
+    let script = match fs::read_to_string(path).map(|text| SpringScript::parse(&text)) {
+        Ok(Ok(script)) => script,
+        Ok(Err(error)) => return println!("{}: {}", path, error),
+        Err(error) => return println!("Could not read {}: {}", path, error)
+    };
+    let mut springdroid = SpringDroid::new(intcodes);
+    report(springdroid.execute(&script), script.mode());
+}
+
+/// Check the scripts on hull patterns without the intcode program. Given
+/// patterns are checked with both scripts, the patterns of the corpus with
+/// the script for their mode.
+fn simulate(patterns: &[String]) {
+    let scripts = [walk_script().unwrap(), run_script().unwrap()];
+    let mut checks = Vec::new();
+    for pattern in patterns.iter() {
+        match pattern.parse::<HullPattern>() {
+            Ok(hull) => checks.extend(scripts.iter().map(|script| (script, hull.clone()))),
+            Err(error) => println!("{}: {}", pattern, error)
+        }
+    }
+    if patterns.is_empty() {
+        let corpus = Corpus::load(CORPUS).unwrap();
+        if corpus.is_empty() {
+            println!("No hull patterns in {}.", CORPUS);
+        }
+        for script in scripts.iter() {
+            checks.extend(corpus.patterns(script.mode()).map(|hull| (script, hull.clone())));
+        }
+    }
+    for (script, hull) in checks.iter() {
+        match Simulator::new(script.mode()).run(script, hull) {
+            Ok(outcome) => println!("{} {}: {}", hull, script.mode(), outcome),
+            Err(error) => println!("{} {}: {}", hull, script.mode(), error)
+        }
+    }
+}
+
+/// Compile a boolean expression over the sensors and let the droid try it,
+/// in WALK mode if the expression only reads A to D
+fn compile_expression(intcodes: Vec<i64>, text: &str) {
+    let expr: Expr = match text.parse() {
+        Ok(expr) => expr,
+        Err(error) => return println!("{}", error)
+    };
+    println!("J = {}", expr.simplify());
+    let mode = if expr.sensors() <= Mode::Walk.sensors().len() { Mode::Walk } else { Mode::Run };
+    match expr.compile(mode) {
+        Ok(script) => {
+            print!("{}", script);
+            let mut springdroid = SpringDroid::new(intcodes);
+            report(springdroid.execute(&script), mode);
+        }
+        Err(error) => println!("{}", error)
+    }
+}
 
+/// Let the droid find its own scripts, learning from the hulls it falls on
+fn synthesise_scripts(intcodes: Vec<i64>) {
+    let mut corpus = Corpus::load(CORPUS).unwrap();
+    for &mode in [Mode::Walk, Mode::Run].iter() {
+        println!("{}", mode);
+        match synthesise(&intcodes, mode, &mut corpus) {
+            Ok(synthesis) => {
+                print!("{}", synthesis.script);
+                println!(
+                    "Found after {} tries, deciding for {} sensor readings. Hull damage {}",
+                    synthesis.rounds,
+                    synthesis.table.rows.len(),
+                    synthesis.damage
+                );
+            }
+            Err(error) => println!("{}", error)
+        }
+    }
+    corpus.save().unwrap();
 }
 
 fn main() {
-    let intcodes = read_input().unwrap();
-    part1(intcodes.clone());
-    part2(intcodes);
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(|mode| mode.as_str()) {
+        Some("simulate") => simulate(&args[1..]),
+        Some("synthesise") => synthesise_scripts(read_input().unwrap()),
+        Some("compile") if args.len() == 2 => compile_expression(read_input().unwrap(), &args[1]),
+        Some("try") if args.len() == 2 => try_script(read_input().unwrap(), &args[1]),
+        _ => {
+            let intcodes = read_input().unwrap();
+            part1(intcodes.clone());
+            part2(intcodes);
+        }
+    }
 }
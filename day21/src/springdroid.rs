@@ -1,14 +1,38 @@
 use crate::computer::IntcodeComputer;
+use crate::hull::HullPattern;
+use crate::springscript::SpringScript;
+use std::fmt;
+
+/// Why the droid did not report the hull damage
+#[derive(Debug, Clone, PartialEq)]
+pub enum DroidError {
+    /// the droid fell on the hull
+    Fell(HullPattern),
+    /// the droid printed neither the damage nor a hull, with its text
+    UnexpectedOutput(String)
+}
+
+impl fmt::Display for DroidError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DroidError::Fell(hull) => write!(f, "Didn't make it across {}", hull),
+            DroidError::UnexpectedOutput(text) => write!(f, "Unexpected output of the droid:\n{}", text)
+        }
+    }
+}
 
 pub struct SpringDroid {
-    computer: IntcodeComputer
+    computer: IntcodeComputer,
+    /// values printed since the last script was entered
+    output: Vec<i64>
 }
 
 impl SpringDroid {
     pub fn new(intcodes: Vec<i64>) -> SpringDroid {
         let computer = IntcodeComputer::new(intcodes);
         SpringDroid {
-            computer
+            computer,
+            output: Vec::new()
         }
     }
 
@@ -16,21 +40,46 @@ impl SpringDroid {
         loop {
             self.computer.run();
             if self.computer.terminated || self.computer.requesting_input {
-                if self.computer.terminated {
-                    println!("{}", self.computer.memory_output);
-                }
                 break;
             }
+            self.output.push(self.computer.memory_output);
         }
-
     }
 
     pub fn input_ascii(&mut self, command: &str) {
         for character in command.chars() {
             self.computer.set_memory_input((character as u8) as i64);
-            self.computer.run();
+            self.run();
         }
         self.computer.set_memory_input(10);
         self.run();
     }
-}
\ No newline at end of file
+
+    /// Text printed since the last script was entered
+    pub fn text(&self) -> String {
+        self.output
+            .iter()
+            .filter(|&&value| (0..128).contains(&value))
+            .map(|&value| value as u8 as char)
+            .collect()
+    }
+
+    /// Enter the script line by line, ending with the mode to start in.
+    /// Returns the hull damage reported once the droid made it across, or
+    /// the hull it fell on.
+    pub fn execute(&mut self, script: &SpringScript) -> Result<i64, DroidError> {
+        self.run();
+        self.output.clear();
+        for instruction in script.instructions() {
+            self.input_ascii(&instruction.to_string());
+        }
+        self.input_ascii(&script.mode().to_string());
+        match self.output.last() {
+            Some(&damage) if damage >= 128 => Ok(damage),
+            _ => match HullPattern::from_crash(&self.text()) {
+                Some(hull) => Err(DroidError::Fell(hull)),
+                None => Err(DroidError::UnexpectedOutput(self.text()))
+            }
+        }
+    }
+}
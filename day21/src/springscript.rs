@@ -0,0 +1,291 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Most instructions the springdroid accepts
+pub const MAX_INSTRUCTIONS: usize = 15;
+
+/// Registers: the hull sensors A to I for the tiles 1 to 9 in front of the
+/// droid, the temporary register T and the jump register J
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Reg {
+    A, B, C, D, E, F, G, H, I, T, J
+}
+
+impl Reg {
+    pub const SENSORS: [Reg; 9] = [
+        Reg::A, Reg::B, Reg::C, Reg::D, Reg::E, Reg::F, Reg::G, Reg::H, Reg::I
+    ];
+
+    pub fn is_writable(&self) -> bool {
+        *self == Reg::T || *self == Reg::J
+    }
+}
+
+impl fmt::Display for Reg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for Reg {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<Reg, ScriptError> {
+        match s {
+            "T" => Ok(Reg::T),
+            "J" => Ok(Reg::J),
+            _ => Reg::SENSORS
+                .iter()
+                .find(|sensor| sensor.to_string() == s)
+                .cloned()
+                .ok_or_else(|| ScriptError::Syntax(format!("Unknown register {}.", s)))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
+    And,
+    Or,
+    Not
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Op::And => "AND",
+            Op::Or => "OR",
+            Op::Not => "NOT"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Op {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<Op, ScriptError> {
+        match s {
+            "AND" => Ok(Op::And),
+            "OR" => Ok(Op::Or),
+            "NOT" => Ok(Op::Not),
+            _ => Err(ScriptError::Syntax(format!("Unknown instruction {}.", s)))
+        }
+    }
+}
+
+/// A springscript instruction: dst = dst op src, or dst = !src for NOT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpringInstr {
+    pub op: Op,
+    pub src: Reg,
+    pub dst: Reg
+}
+
+impl fmt::Display for SpringInstr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.op, self.src, self.dst)
+    }
+}
+
+impl FromStr for SpringInstr {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<SpringInstr, ScriptError> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        if words.len() != 3 {
+            return Err(ScriptError::Syntax(format!("Expected 'OP SRC DST' but found '{}'.", s)));
+        }
+        Ok(SpringInstr {
+            op: words[0].parse()?,
+            src: words[1].parse()?,
+            dst: words[2].parse()?
+        })
+    }
+}
+
+/// WALK sees the tiles 1 to 4, RUN the tiles 1 to 9
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Mode {
+    Walk,
+    Run
+}
+
+impl Mode {
+    /// Sensors that can be read in the mode
+    pub fn sensors(&self) -> &'static [Reg] {
+        match self {
+            Mode::Walk => &Reg::SENSORS[..4],
+            Mode::Run => &Reg::SENSORS
+        }
+    }
+}
+
+impl FromStr for Mode {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<Mode, ScriptError> {
+        match s {
+            "WALK" => Ok(Mode::Walk),
+            "RUN" => Ok(Mode::Run),
+            _ => Err(ScriptError::Syntax(format!("Unknown mode {}.", s)))
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Walk => write!(f, "WALK"),
+            Mode::Run => write!(f, "RUN")
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptError {
+    TooManyInstructions,
+    InvalidDestination(Reg),
+    SensorNotAvailable(Reg, Mode),
+    /// fewer sensor readings than the mode reads, with their number
+    MissingSensors(Mode, usize),
+    MissingMode,
+    Syntax(String),
+    /// error in a line of a parsed script
+    AtLine(usize, Box<ScriptError>)
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScriptError::TooManyInstructions => {
+                write!(f, "More than {} instructions.", MAX_INSTRUCTIONS)
+            }
+            ScriptError::InvalidDestination(reg) => {
+                write!(f, "Register {} can not be written, only T and J.", reg)
+            }
+            ScriptError::SensorNotAvailable(reg, mode) => {
+                write!(f, "Sensor {} can not be read in {} mode.", reg, mode)
+            }
+            ScriptError::MissingSensors(mode, count) => {
+                write!(f, "{} mode reads {} sensors, but only {} were given.", mode, mode.sensors().len(), count)
+            }
+            ScriptError::MissingMode => write!(f, "Script has to end with WALK or RUN."),
+            ScriptError::Syntax(message) => write!(f, "{}", message),
+            ScriptError::AtLine(line, error) => write!(f, "line {}: {}", line, error)
+        }
+    }
+}
+
+/// A valid springscript program
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpringScript {
+    mode: Mode,
+    instructions: Vec<SpringInstr>
+}
+
+impl SpringScript {
+    pub fn new(mode: Mode) -> SpringScript {
+        SpringScript {
+            mode,
+            instructions: Vec::new()
+        }
+    }
+
+    /// Append the instruction if the droid can execute it
+    pub fn push(&mut self, instruction: SpringInstr) -> Result<&mut SpringScript, ScriptError> {
+        if self.instructions.len() == MAX_INSTRUCTIONS {
+            return Err(ScriptError::TooManyInstructions);
+        }
+        if !instruction.dst.is_writable() {
+            return Err(ScriptError::InvalidDestination(instruction.dst));
+        }
+        if !instruction.src.is_writable() && !self.mode.sensors().contains(&instruction.src) {
+            return Err(ScriptError::SensorNotAvailable(instruction.src, self.mode));
+        }
+        self.instructions.push(instruction);
+        Ok(self)
+    }
+
+    pub fn and(&mut self, src: Reg, dst: Reg) -> Result<&mut SpringScript, ScriptError> {
+        self.push(SpringInstr { op: Op::And, src, dst })
+    }
+
+    pub fn or(&mut self, src: Reg, dst: Reg) -> Result<&mut SpringScript, ScriptError> {
+        self.push(SpringInstr { op: Op::Or, src, dst })
+    }
+
+    pub fn not(&mut self, src: Reg, dst: Reg) -> Result<&mut SpringScript, ScriptError> {
+        self.push(SpringInstr { op: Op::Not, src, dst })
+    }
+
+    /// Parse a script with one instruction per line, ending with WALK or RUN.
+    /// Blank lines and everything after // are ignored.
+    pub fn parse(text: &str) -> Result<SpringScript, ScriptError> {
+        let mut instructions = Vec::new();
+        let mut mode = None;
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split("//").next().unwrap().trim();
+            let at_line = |error| ScriptError::AtLine(n + 1, Box::new(error));
+            if line.is_empty() {
+                continue;
+            }
+            if mode.is_some() {
+                return Err(at_line(ScriptError::Syntax(format!("Unexpected '{}' after the mode.", line))));
+            }
+            match line.parse::<Mode>() {
+                Ok(line_mode) => mode = Some(line_mode),
+                Err(_) => instructions.push((n + 1, line.parse::<SpringInstr>().map_err(at_line)?))
+            }
+        }
+        let mut script = SpringScript::new(mode.ok_or(ScriptError::MissingMode)?);
+        for (line, instruction) in instructions {
+            script.push(instruction).map_err(|error| ScriptError::AtLine(line, Box::new(error)))?;
+        }
+        Ok(script)
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn instructions(&self) -> &[SpringInstr] {
+        &self.instructions
+    }
+
+    /// Run the script once like the droid does, with T and J starting as
+    /// false. `sensors` holds the readings of A, B, ..., true for ground, at
+    /// least up to the last sensor of the mode. Returns whether the droid
+    /// jumps.
+    pub fn jumps(&self, sensors: &[bool]) -> Result<bool, ScriptError> {
+        if sensors.len() < self.mode.sensors().len() {
+            return Err(ScriptError::MissingSensors(self.mode, sensors.len()));
+        }
+        let mut t = false;
+        let mut j = false;
+        for instruction in self.instructions.iter() {
+            let src = match instruction.src {
+                Reg::T => t,
+                Reg::J => j,
+                sensor => sensors[sensor as usize]
+            };
+            let dst = if instruction.dst == Reg::T { &mut t } else { &mut j };
+            *dst = match instruction.op {
+                Op::And => *dst && src,
+                Op::Or => *dst || src,
+                Op::Not => !src
+            };
+        }
+        Ok(j)
+    }
+}
+
+impl fmt::Display for SpringScript {
+    /// The script as the droid reads it
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for instruction in self.instructions.iter() {
+            writeln!(f, "{}", instruction)?;
+        }
+        writeln!(f, "{}", self.mode)
+    }
+}
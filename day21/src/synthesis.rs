@@ -0,0 +1,110 @@
+use crate::corpus::Corpus;
+use crate::hull::{HullPattern, Outcome, Simulator};
+use crate::logic::{self, Reading, TruthTable};
+use crate::springdroid::{DroidError, SpringDroid};
+use crate::springscript::{Mode, ScriptError, SpringScript};
+use std::fmt;
+
+/// Script that got the droid across
+#[derive(Debug)]
+pub struct Synthesis {
+    pub script: SpringScript,
+    pub table: TruthTable,
+    pub damage: i64,
+    /// scripts the droid executed, the last one made it
+    pub rounds: usize
+}
+
+#[derive(Debug)]
+pub enum SynthesisError {
+    /// no function of the sensors gets the droid over all the hulls
+    Unsolvable,
+    /// the function for the hulls needs too many instructions
+    Script(ScriptError),
+    /// the droid fell on a hull the script was made for, the simulation
+    /// does not match the droid
+    Mismatch(SpringScript, HullPattern),
+    /// the droid did not run the script as expected
+    Droid(DroidError)
+}
+
+impl fmt::Display for SynthesisError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SynthesisError::Unsolvable => write!(f, "No script can get the droid over all hulls."),
+            SynthesisError::Script(error) => write!(f, "{}", error),
+            SynthesisError::Mismatch(script, hull) => {
+                write!(f, "The droid fell on {} which the script survives in the simulation:\n{}", hull, script)
+            }
+            SynthesisError::Droid(error) => write!(f, "{}", error)
+        }
+    }
+}
+
+/// Decide for the readings the droid meets on its way over the hulls
+/// whether to jump, with a depth first search trying to walk first. Every
+/// hull is started again whenever a decision was added, as it changes the
+/// way over the hulls before.
+fn assign(table: &mut TruthTable, hulls: &[&HullPattern], simulator: &Simulator) -> bool {
+    for hull in hulls.iter() {
+        let mut unknown: Option<Reading> = None;
+        let outcome = simulator.run_with(hull, |sensors| {
+            let reading = logic::reading(sensors);
+            let jump = table.rows.get(&reading).cloned();
+            if jump.is_none() {
+                unknown = Some(reading);
+            }
+            jump
+        });
+        match outcome {
+            Some(Outcome::Survived) => continue,
+            Some(Outcome::Fell(_)) => return false,
+            None => {
+                let reading = unknown.unwrap();
+                for &jump in [false, true].iter() {
+                    table.rows.insert(reading, jump);
+                    if assign(table, hulls, simulator) {
+                        return true;
+                    }
+                }
+                table.rows.remove(&reading);
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Truth table of the jump register that gets the droid over all hulls,
+/// with a row for every reading the droid meets on the way
+pub fn derive_table(hulls: &[&HullPattern], simulator: &Simulator) -> Option<TruthTable> {
+    let mut table = TruthTable::new(simulator.sensors);
+    if assign(&mut table, hulls, simulator) {
+        Some(table)
+    } else {
+        None
+    }
+}
+
+/// Search a script for the mode, starting from the hulls of the corpus.
+/// Every hull the droid falls on is added to the corpus and the script
+/// derived again, until the droid makes it across.
+pub fn synthesise(intcodes: &[i64], mode: Mode, corpus: &mut Corpus) -> Result<Synthesis, SynthesisError> {
+    let simulator = Simulator::new(mode);
+    let mut rounds = 0;
+    loop {
+        let hulls: Vec<&HullPattern> = corpus.patterns(mode).collect();
+        let table = derive_table(&hulls, &simulator).ok_or(SynthesisError::Unsolvable)?;
+        let script = logic::script_for(&table, mode).map_err(SynthesisError::Script)?;
+        rounds += 1;
+        match SpringDroid::new(intcodes.to_vec()).execute(&script) {
+            Ok(damage) => return Ok(Synthesis { script, table, damage, rounds }),
+            Err(DroidError::Fell(hull)) => {
+                if !corpus.add(mode, hull.clone()) {
+                    return Err(SynthesisError::Mismatch(script, hull));
+                }
+            }
+            Err(error) => return Err(SynthesisError::Droid(error))
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use crate::network::Packet;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::Error;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// a computer sent a packet
+    Send,
+    /// a computer read a packet from its queue
+    Receive,
+    /// the NAT sent a packet to the idle network
+    NatWakeup,
+    /// the network went idle: nothing was sent or received for two rounds,
+    /// or every node of the threaded network blocked with no packet in
+    /// flight
+    Idle
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Kind::Send => "SEND",
+            Kind::Receive => "RECV",
+            Kind::NatWakeup => "WAKEUP",
+            Kind::Idle => "IDLE"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Kind, String> {
+        match s {
+            "SEND" => Ok(Kind::Send),
+            "RECV" => Ok(Kind::Receive),
+            "WAKEUP" => Ok(Kind::NatWakeup),
+            "IDLE" => Ok(Kind::Idle),
+            _ => Err(format!("Unknown event {}.", s))
+        }
+    }
+}
+
+/// An event of the network. Idle detections have no addresses and packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record {
+    pub round: usize,
+    pub kind: Kind,
+    pub src: Option<i64>,
+    pub dst: Option<i64>,
+    pub packet: Option<Packet>
+}
+
+/// File formats of a capture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `round kind src dst x y`, with - for missing values
+    Lines,
+    /// one JSON object per line, with null for missing values
+    JsonLines
+}
+
+impl Format {
+    /// JSON lines for files ending in .jsonl or .json, lines otherwise
+    pub fn for_path<P: AsRef<Path>>(path: P) -> Format {
+        match path.as_ref().extension().and_then(|extension| extension.to_str()) {
+            Some("jsonl") | Some("json") => Format::JsonLines,
+            _ => Format::Lines
+        }
+    }
+}
+
+fn optional(value: Option<i64>, missing: &str) -> String {
+    value.map(|value| value.to_string()).unwrap_or_else(|| missing.to_string())
+}
+
+impl Record {
+    pub fn to_line(self) -> String {
+        format!(
+            "{} {} {} {} {} {}",
+            self.round,
+            self.kind,
+            optional(self.src, "-"),
+            optional(self.dst, "-"),
+            optional(self.packet.map(|packet| packet.x), "-"),
+            optional(self.packet.map(|packet| packet.y), "-")
+        )
+    }
+
+    pub fn to_json(self) -> String {
+        format!(
+            "{{\"round\":{},\"kind\":\"{}\",\"src\":{},\"dst\":{},\"x\":{},\"y\":{}}}",
+            self.round,
+            self.kind,
+            optional(self.src, "null"),
+            optional(self.dst, "null"),
+            optional(self.packet.map(|packet| packet.x), "null"),
+            optional(self.packet.map(|packet| packet.y), "null")
+        )
+    }
+
+    /// Build the record from the values of its six fields
+    fn from_fields(fields: &[&str], missing: &str) -> Result<Record, String> {
+        let number = |field: &str| -> Result<Option<i64>, String> {
+            if field == missing {
+                return Ok(None);
+            }
+            field.parse().map(Some).map_err(|_| format!("Invalid number {}.", field))
+        };
+        let round = match fields[0] {
+            field if field == missing => return Err(String::from("Missing round.")),
+            field => field.parse().map_err(|_| format!("Invalid round {}.", field))?
+        };
+        let packet = match (number(fields[4])?, number(fields[5])?) {
+            (Some(x), Some(y)) => Some(Packet { x, y }),
+            _ => None
+        };
+        Ok(Record {
+            round,
+            kind: fields[1].parse()?,
+            src: number(fields[2])?,
+            dst: number(fields[3])?,
+            packet
+        })
+    }
+}
+
+impl FromStr for Record {
+    type Err = String;
+
+    /// Parse a record in either format
+    fn from_str(s: &str) -> Result<Record, String> {
+        let s = s.trim();
+        if let Some(object) = s.strip_prefix('{').and_then(|rest| rest.strip_suffix('}')) {
+            let mut values = BTreeMap::new();
+            for pair in object.split(',') {
+                let mut parts = pair.splitn(2, ':');
+                let key = parts.next().unwrap().trim().trim_matches('"');
+                let value = parts.next().ok_or_else(|| format!("Invalid field {}.", pair))?;
+                values.insert(key, value.trim().trim_matches('"'));
+            }
+            let mut fields = Vec::new();
+            for key in ["round", "kind", "src", "dst", "x", "y"].iter() {
+                fields.push(*values.get(key).ok_or_else(|| format!("Missing field {}.", key))?);
+            }
+            Record::from_fields(&fields, "null")
+        } else {
+            let fields: Vec<&str> = s.split_whitespace().collect();
+            if fields.len() != 6 {
+                return Err(format!("Expected 'round kind src dst x y' but found '{}'.", s));
+            }
+            Record::from_fields(&fields, "-")
+        }
+    }
+}
+
+pub fn write<P: AsRef<Path>>(path: P, records: &[Record], format: Format) -> Result<(), Error> {
+    let content: String = records
+        .iter()
+        .map(|record| match format {
+            Format::Lines => record.to_line() + "\n",
+            Format::JsonLines => record.to_json() + "\n"
+        })
+        .collect();
+    fs::write(path, content)
+}
+
+/// Read a capture in either format
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<Record>, String> {
+    let content = fs::read_to_string(&path).map_err(|error| error.to_string())?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| line.parse().map_err(|error| format!("line {}: {}", n + 1, error)))
+        .collect()
+}
+
+/// Traffic of a single computer
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeStats {
+    pub sent: usize,
+    pub received: usize,
+    /// packets sent to the NAT
+    pub to_nat: usize
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Analysis {
+    pub rounds: usize,
+    pub idle: usize,
+    pub nodes: BTreeMap<i64, NodeStats>,
+    /// packets sent to the NAT and sent by it, in order
+    pub nat_history: Vec<Record>
+}
+
+pub fn analyse(records: &[Record], nat_address: i64) -> Analysis {
+    let mut analysis = Analysis::default();
+    for record in records.iter() {
+        analysis.rounds = analysis.rounds.max(record.round);
+        match record.kind {
+            Kind::Send => {
+                let stats = analysis.nodes.entry(record.src.unwrap_or(-1)).or_default();
+                stats.sent += 1;
+                if record.dst == Some(nat_address) {
+                    stats.to_nat += 1;
+                    analysis.nat_history.push(*record);
+                }
+            }
+            Kind::Receive => analysis.nodes.entry(record.dst.unwrap_or(-1)).or_default().received += 1,
+            Kind::NatWakeup => analysis.nat_history.push(*record),
+            Kind::Idle => analysis.idle += 1
+        }
+    }
+    analysis
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} rounds, idle {} times", self.rounds, self.idle)?;
+        writeln!(f, "{:>5} {:>8} {:>8} {:>8}", "node", "sent", "received", "to NAT")?;
+        for (node, stats) in self.nodes.iter() {
+            writeln!(f, "{:>5} {:>8} {:>8} {:>8}", node, stats.sent, stats.received, stats.to_nat)?;
+        }
+        writeln!(f, "NAT history:")?;
+        for record in self.nat_history.iter() {
+            let packet = record.packet.unwrap_or(Packet { x: 0, y: 0 });
+            match record.kind {
+                Kind::NatWakeup => writeln!(
+                    f,
+                    "round {:>5}: woke up {} with ({}, {})",
+                    record.round,
+                    optional(record.dst, "?"),
+                    packet.x,
+                    packet.y
+                )?,
+                _ => writeln!(
+                    f,
+                    "round {:>5}: received ({}, {}) from {}",
+                    record.round,
+                    packet.x,
+                    packet.y,
+                    optional(record.src, "?")
+                )?
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use crate::computer::IntcodeComputer;
+use crate::network;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Where a packet goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Destination {
+    Computer(usize),
+    Nat
+}
+
+/// A packet sent by a computer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Packet {
+    pub dst: Destination,
+    pub x: i64,
+    pub y: i64
+}
+
+impl Packet {
+    /// The packet without its destination, as it is put into a queue
+    pub fn payload(&self) -> network::Packet {
+        network::Packet { x: self.x, y: self.y }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecError {
+    /// the computer sent a packet to an address that is neither a computer
+    /// nor the NAT
+    InvalidDestination { dst: i64, x: i64, y: i64 },
+    /// the computer halted after sending only the destination, or the
+    /// destination and x, of a packet
+    TruncatedPacket { dst: i64, x: Option<i64> }
+}
+
+impl CodecError {
+    /// Destination and packet of what the computer sent
+    pub fn sent(&self) -> (i64, Option<network::Packet>) {
+        match *self {
+            CodecError::InvalidDestination { dst, x, y } => (dst, Some(network::Packet { x, y })),
+            CodecError::TruncatedPacket { dst, .. } => (dst, None)
+        }
+    }
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodecError::InvalidDestination { dst, x, y } => {
+                write!(f, "Trying to send to unknown address: {} ({} {})", dst, x, y)
+            }
+            CodecError::TruncatedPacket { dst, x: None } => {
+                write!(f, "Halted while sending to {}", dst)
+            }
+            CodecError::TruncatedPacket { dst, x: Some(x) } => {
+                write!(f, "Halted while sending to {} ({} ...)", dst, x)
+            }
+        }
+    }
+}
+
+/// Frames the outputs of a network computer into packets and feeds it the
+/// packets it receives. Outputs of a packet that is not complete when the
+/// computer asks for input are kept until the rest follows.
+pub struct PacketCodec {
+    computer: IntcodeComputer,
+    /// number of computers in the network, their addresses are 0..nodes
+    nodes: usize,
+    nat_address: i64,
+    /// outputs of the packet being sent
+    partial: Vec<i64>,
+    /// packets completed while feeding input
+    ready: VecDeque<Result<Packet, CodecError>>
+}
+
+impl PacketCodec {
+    /// Boot a fresh computer and pass it its network address
+    pub fn boot(mut computer: IntcodeComputer, address: usize, nodes: usize, nat_address: i64) -> PacketCodec {
+        computer.run();
+        let mut codec = PacketCodec {
+            computer,
+            nodes,
+            nat_address,
+            partial: Vec::new(),
+            ready: VecDeque::new()
+        };
+        codec.input(address as i64);
+        codec
+    }
+
+    pub fn halted(&self) -> bool {
+        self.computer.terminated && self.ready.is_empty() && self.partial.is_empty()
+    }
+
+    fn decode(&mut self) -> Result<Packet, CodecError> {
+        let (dst, x, y) = (self.partial[0], self.partial[1], self.partial[2]);
+        self.partial.clear();
+        let dst = if (0..self.nodes as i64).contains(&dst) {
+            Destination::Computer(dst as usize)
+        } else if dst == self.nat_address {
+            Destination::Nat
+        } else {
+            return Err(CodecError::InvalidDestination { dst, x, y });
+        };
+        Ok(Packet { dst, x, y })
+    }
+
+    /// Take the output the computer paused at and run it on, returns the
+    /// packet it completes
+    fn read_output(&mut self) -> Option<Result<Packet, CodecError>> {
+        self.partial.push(self.computer.memory_output);
+        self.computer.run();
+        if self.partial.len() == 3 {
+            Some(self.decode())
+        } else {
+            None
+        }
+    }
+
+    fn is_sending(&self) -> bool {
+        !self.computer.requesting_input && !self.computer.terminated
+    }
+
+    /// The next packet the computer sends, None once it asks for input or
+    /// halted. A packet the computer did not finish before halting is an
+    /// error.
+    pub fn next_packet(&mut self) -> Option<Result<Packet, CodecError>> {
+        if let Some(packet) = self.ready.pop_front() {
+            return Some(packet);
+        }
+        while self.is_sending() {
+            if let Some(packet) = self.read_output() {
+                return Some(packet);
+            }
+        }
+        if self.computer.terminated && !self.partial.is_empty() {
+            let error = CodecError::TruncatedPacket { dst: self.partial[0], x: self.partial.get(1).copied() };
+            self.partial.clear();
+            return Some(Err(error));
+        }
+        None
+    }
+
+    /// Pass a value to the computer once it asks for it, the packets it
+    /// sends until then are kept for next_packet
+    fn input(&mut self, value: i64) {
+        while self.is_sending() {
+            if let Some(packet) = self.read_output() {
+                self.ready.push_back(packet);
+            }
+        }
+        if !self.computer.terminated {
+            self.computer.set_memory_input(value);
+            self.computer.run();
+        }
+    }
+
+    pub fn receive(&mut self, packet: network::Packet) {
+        self.input(packet.x);
+        self.input(packet.y);
+    }
+
+    /// Tell the computer there is no packet for it
+    pub fn receive_nothing(&mut self) {
+        self.input(-1);
+    }
+}
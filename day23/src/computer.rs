@@ -1,12 +1,131 @@
+use std::env;
+use std::io::{self, Write};
+
+/// Events emitted by the intcode computer while it executes a program
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// An instruction finished, `values` holds the parameters it worked with
+    /// (read values first, followed by the address it wrote to)
+    InstructionExecuted {
+        ip: usize,
+        opcode: u8,
+        pmodes: [u8; 3],
+        values: Vec<i64>,
+    },
+    /// Program reached an input instruction, but no input is in memory
+    InputRequested,
+    InputConsumed(i64),
+    OutputProduced(i64),
+    /// Program reached opcode 99
+    Halted,
+}
+
+/// Receives every event of the intcode computer it is attached to
+pub trait Observer {
+    fn notify(&mut self, event: &Event);
+}
+
+impl<F: FnMut(&Event)> Observer for F {
+    fn notify(&mut self, event: &Event) {
+        self(event)
+    }
+}
+
+/// Get the three letter name of an opcode
+pub fn mnemonic(opcode: u8) -> &'static str {
+    match opcode {
+        1 => "ADD",
+        2 => "MUL",
+        3 => "INP",
+        4 => "OUT",
+        5 => "JIT",
+        6 => "JIF",
+        7 => "LET",
+        8 => "EQU",
+        9 => "RBO",
+        99 => "TER",
+        _ => "???",
+    }
+}
+
+/// Split an instruction into its opcode and the modes of its three
+/// parameters, e.g. 1002 into opcode 2 with modes [0, 1, 0]. None if the
+/// instruction is negative or has more than three modes.
+pub fn decode(instruction: i64) -> Option<(u8, [u8; 3])> {
+    if !(0..100_000).contains(&instruction) {
+        return None;
+    }
+    let mut pmodes = [0, 0, 0];
+    let mut modes = instruction / 100;
+    for pmode in pmodes.iter_mut() {
+        *pmode = (modes % 10) as u8;
+        modes /= 10;
+    }
+    Some(((instruction % 100) as u8, pmodes))
+}
+
+/// Writes every executed instruction, e.g. `0004 1 [0, 1, 0] | ADD 3 4 7`
+pub struct DebugTrace<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> DebugTrace<W> {
+    pub fn new(writer: W) -> DebugTrace<W> {
+        DebugTrace { writer }
+    }
+}
+
+impl<W: Write> Observer for DebugTrace<W> {
+    fn notify(&mut self, event: &Event) {
+        if let Event::InstructionExecuted {
+            ip,
+            opcode,
+            pmodes,
+            values,
+        } = event
+        {
+            let mut line = format!("{:04} {} {:?} | {}", ip, opcode, pmodes, mnemonic(*opcode));
+            for value in values {
+                line += &format!(" {}", value);
+            }
+            writeln!(self.writer, "{}", line).expect("Error while writing debug trace.");
+        }
+    }
+}
+
+/// Writes the inputs and outputs of the program, e.g. `<< 1` and `>> 42`
+pub struct StdInOutTrace<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> StdInOutTrace<W> {
+    pub fn new(writer: W) -> StdInOutTrace<W> {
+        StdInOutTrace { writer }
+    }
+}
+
+impl<W: Write> Observer for StdInOutTrace<W> {
+    fn notify(&mut self, event: &Event) {
+        let line = match event {
+            Event::InputRequested => String::from("Program halted, requesting for input."),
+            Event::InputConsumed(value) => format!("<< {}", value),
+            Event::OutputProduced(value) => format!(">> {}", value),
+            Event::Halted => String::from("Program terminated"),
+            _ => return,
+        };
+        writeln!(self.writer, "{}", line).expect("Error while writing stdin/out trace.");
+    }
+}
+
 pub struct IntcodeComputer {
-    intcodes: [i64; 125_000], // 125000*8 Bytes = 1 MBytes
-    ip: usize,
-    memory_input: Option<i64>,
+    pub(crate) intcodes: Vec<i64>, // 125000*8 Bytes = 1 MBytes, kept on the heap
+    pub(crate) ip: usize,
+    pub(crate) memory_input: Option<i64>,
     pub memory_output: i64,
     pmodes: [u8; 3],
-    relative_base: i64,
-    pub debug_mode: bool,
-    pub show_stdinout: bool,
+    pub(crate) relative_base: i64,
+    observers: Vec<Box<dyn Observer>>,
+    traced_values: Vec<i64>,
     pub requesting_input: bool,
     pub terminated: bool,
 }
@@ -14,27 +133,53 @@ pub struct IntcodeComputer {
 impl IntcodeComputer {
     /// Initialize the intcode computer
     pub fn new(intcodes: Vec<i64>) -> IntcodeComputer {
-        let mut initial_memory = [0; 125_000];
+        let mut initial_memory = vec![0; 125_000];
         for (i, value) in intcodes.iter().enumerate() {
             initial_memory[i] = *value;
         }
-        IntcodeComputer {
+        let mut computer = IntcodeComputer {
             intcodes: initial_memory,
             ip: 0,
             memory_input: None,
             memory_output: 0,
             pmodes: [0, 0, 0],
             relative_base: 0,
-            debug_mode: false,
-            show_stdinout: false,
+            observers: Vec::new(),
+            traced_values: Vec::new(),
             requesting_input: false,
             terminated: false,
+        };
+        computer.trace_from_env();
+        computer
+    }
+
+    /// Print what the computer does if the environment variable
+    /// INTCODE_TRACE is set, `io` for its inputs and outputs and `debug` for
+    /// every instruction
+    fn trace_from_env(&mut self) {
+        match env::var("INTCODE_TRACE").as_deref() {
+            Ok("io") => self.add_observer(Box::new(StdInOutTrace::new(io::stdout()))),
+            Ok("debug") => self.add_observer(Box::new(DebugTrace::new(io::stdout()))),
+            _ => (),
+        }
+    }
+
+    /// Attach an observer that is notified about every event of the computer
+    pub fn add_observer(&mut self, observer: Box<dyn Observer>) {
+        self.observers.push(observer);
+    }
+
+    fn emit(&mut self, event: Event) {
+        for observer in self.observers.iter_mut() {
+            observer.notify(&event);
         }
     }
 
-    fn debug_print(&self, message: String) {
-        if self.debug_mode {
-            print!("{}", message);
+    /// Remember a parameter value of the current instruction for the
+    /// InstructionExecuted event. Nothing is stored without observers.
+    fn trace(&mut self, value: i64) {
+        if !self.observers.is_empty() {
+            self.traced_values.push(value);
         }
     }
 
@@ -43,104 +188,93 @@ impl IntcodeComputer {
     /// return tuple of current (ip, output). If opcode 99 is reached, ip i set to -1
     /// to flag termination
     pub fn run(&mut self) {
-        loop {
-            if self.terminated {
-                break;
-            }
+        while self.step() {}
+    }
 
-            let instruction = self.intcodes[self.ip];
-            // read instruction, first seperate by digits and transform to vec of digits
-            let instruct_digits: Vec<u8> = instruction
-                .to_string()
-                .chars()
-                .map(|d| d.to_digit(10).unwrap() as u8)
-                .collect();
-
-            // seperate instruction into opcode and parameter mode
-            let n_digits = instruct_digits.len();
-            let opcode: u8;
-            self.pmodes = [0, 0, 0]; // default all modes to 0 first
-            if n_digits == 1 {
-                // speci_al case of single digit opcode
-                opcode = instruct_digits[0];
-            } else {
-                // last two digits are opcode
-                opcode = instruct_digits[n_digits - 2] * 10 + instruct_digits[n_digits - 1];
-                // remaining digits set parameter mode array, if unpresent remain 0
-                for i in 0..n_digits - 2 {
-                    self.pmodes[i] = instruct_digits[n_digits - 3 - i];
-                }
-            }
+    /// Execute the instruction at the instruction pointer. Returns false if
+    /// the computer has to pause: after an output, when an input is requested
+    /// but missing and once the program terminated.
+    pub fn step(&mut self) -> bool {
+        if self.terminated {
+            return false;
+        }
 
-            self.debug_print(format!("{:04} {} {:?} | ", self.ip, opcode, self.pmodes));
+        let instruction = self.intcodes[self.ip];
+        let (opcode, pmodes) = match decode(instruction) {
+            Some(decoded) => decoded,
+            None => panic!(
+                "Error while reading intcodes. Invalid instruction {} at position: {}",
+                instruction, self.ip
+            ),
+        };
+        self.pmodes = pmodes;
 
-            // read opcode and execute command accordingly
-            match opcode {
-                1 => {
-                    self.debug_print(String::from("ADD"));
-                    self.add();
-                }
-                2 => {
-                    self.debug_print(String::from("MUL"));
-                    self.multiply();
-                }
-                3 => {
-                    self.debug_print(String::from("INP"));
-                    // input opcode
-                    if self.memory_input.is_none() {
-                        if self.show_stdinout {
-                            println!("\nProgram halted, requesting for input.");
-                        }
-                        self.requesting_input = true;
-                        break;
-                    } else {
-                        self.input();
-                        self.requesting_input = false;
-                    }
-                }
-                4 => {
-                    self.debug_print(String::from("OUT"));
-                    self.output();
-                    // make a break to be possibly dealing with the new output
-                    break;
-                }
-                5 => {
-                    self.debug_print(String::from("JIT"));
-                    self.jump_if_true();
-                }
-                6 => {
-                    self.debug_print(String::from("JIF"));
-                    self.jump_if_false();
-                }
-                7 => {
-                    self.debug_print(String::from("LET"));
-                    self.less_than();
-                }
-                8 => {
-                    self.debug_print(String::from("EQU"));
-                    self.equals();
-                }
-                9 => {
-                    self.debug_print(String::from("RBO"));
-                    self.relative_base_offset();
-                }
-                99 => {
-                    self.debug_print(String::from("TER"));
-                    if self.show_stdinout {
-                        println!("\nProgram terminated");
-                    }
-                    self.terminated = true;
-                }
-                _ => {
-                    panic!(
-                        "Error while reading intcodes. Unknown opcode {} at \
-                         position: {}",
-                        self.intcodes[self.ip], self.ip
-                    );
+        // read opcode and execute command accordingly
+        let ip = self.ip;
+        let mut pause = false;
+        match opcode {
+            1 => {
+                self.add();
+            }
+            2 => {
+                self.multiply();
+            }
+            3 => {
+                // input opcode
+                if self.memory_input.is_none() {
+                    self.requesting_input = true;
+                    self.emit(Event::InputRequested);
+                    return false;
+                } else {
+                    self.input();
+                    self.requesting_input = false;
                 }
             }
-            self.debug_print(String::from("\n"));
+            4 => {
+                self.output();
+                // make a break to be possibly dealing with the new output
+                pause = true;
+            }
+            5 => {
+                self.jump_if_true();
+            }
+            6 => {
+                self.jump_if_false();
+            }
+            7 => {
+                self.less_than();
+            }
+            8 => {
+                self.equals();
+            }
+            9 => {
+                self.relative_base_offset();
+            }
+            99 => {
+                self.terminated = true;
+            }
+            _ => {
+                panic!(
+                    "Error while reading intcodes. Unknown opcode {} at \
+                     position: {}",
+                    self.intcodes[self.ip], self.ip
+                );
+            }
         }
+        if !self.observers.is_empty() {
+            let values = std::mem::take(&mut self.traced_values);
+            let pmodes = self.pmodes;
+            self.emit(Event::InstructionExecuted {
+                ip,
+                opcode,
+                pmodes,
+                values,
+            });
+        }
+        if self.terminated {
+            self.emit(Event::Halted);
+        }
+        !pause && !self.terminated
     }
 
     /// Set the memory at input, that is read in upon calling of input opcode
@@ -182,18 +316,13 @@ impl IntcodeComputer {
     /// 0 -- position mode (parameter is address)
     /// 2 -- relative mode (parameter is address from relative base)
     fn set_value(&mut self, idx_parameter: usize, value: i64) {
-        let param: i64;
-        match self.pmodes[idx_parameter] {
-            0 => {
-                param = self.intcodes[self.ip + idx_parameter + 1];
-            }
-            2 => {
-                param = self.intcodes[self.ip + idx_parameter + 1] + self.relative_base;
-            }
+        let param = match self.pmodes[idx_parameter] {
+            0 => self.intcodes[self.ip + idx_parameter + 1],
+            2 => self.intcodes[self.ip + idx_parameter + 1] + self.relative_base,
             _ => panic!("Invalid mode for input."),
-        }
+        };
 
-        self.debug_print(format!(" {}", param));
+        self.trace(param);
         if param < 0 {
             panic!("Tried to input to negative memory.");
         }
@@ -205,7 +334,8 @@ impl IntcodeComputer {
         // get first two parameter values
         let p1 = self.get_value(0);
         let p2 = self.get_value(1);
-        self.debug_print(format!(" {} {}", p1, p2));
+        self.trace(p1);
+        self.trace(p2);
         // store result at third position
         self.set_value(2, p1 + p2);
         self.ip += 4;
@@ -216,7 +346,8 @@ impl IntcodeComputer {
         // get first two parameter values
         let p1 = self.get_value(0);
         let p2 = self.get_value(1);
-        self.debug_print(format!(" {} {}", p1, p2));
+        self.trace(p1);
+        self.trace(p2);
 
         // store result at third position
         self.set_value(2, p1 * p2);
@@ -227,9 +358,7 @@ impl IntcodeComputer {
     fn input(&mut self) {
         let input_value = self.memory_input.unwrap();
         self.set_value(0, input_value);
-        if self.show_stdinout {
-            println!("\n<< {}", input_value);
-        }
+        self.emit(Event::InputConsumed(input_value));
         self.memory_input = None;
         self.ip += 2;
     }
@@ -237,11 +366,9 @@ impl IntcodeComputer {
     /// Set value at parameter into the output memory
     fn output(&mut self) {
         let p1 = self.get_value(0);
-        self.debug_print(format!(" {}", p1));
+        self.trace(p1);
         self.memory_output = p1;
-        if self.show_stdinout {
-            println!("\n>> {}", p1);
-        }
+        self.emit(Event::OutputProduced(p1));
         self.ip += 2;
     }
 
@@ -251,7 +378,8 @@ impl IntcodeComputer {
         // jump-if-true
         let p1 = self.get_value(0);
         let p2 = self.get_value(1);
-        self.debug_print(format!(" {} {}", p1, p2));
+        self.trace(p1);
+        self.trace(p2);
         if p1 != 0 {
             if p2 < 0 {
                 panic!("Trying to jump to negative memory");
@@ -266,7 +394,8 @@ impl IntcodeComputer {
     fn jump_if_false(&mut self) {
         let p1 = self.get_value(0);
         let p2 = self.get_value(1);
-        self.debug_print(format!(" {} {}", p1, p2));
+        self.trace(p1);
+        self.trace(p2);
         if p1 == 0 {
             if p2 < 0 {
                 panic!("Trying to jump to negative memory");
@@ -282,7 +411,8 @@ impl IntcodeComputer {
         // get first two parameter values
         let p1 = self.get_value(0);
         let p2 = self.get_value(1);
-        self.debug_print(format!(" {} {}", p1, p2));
+        self.trace(p1);
+        self.trace(p2);
 
         if p1 < p2 {
             self.set_value(2, 1);
@@ -297,7 +427,8 @@ impl IntcodeComputer {
         // get first two parameter values
         let p1 = self.get_value(0);
         let p2 = self.get_value(1);
-        self.debug_print(format!(" {} {}", p1, p2));
+        self.trace(p1);
+        self.trace(p2);
 
         if p1 == p2 {
             self.set_value(2, 1);
@@ -310,7 +441,7 @@ impl IntcodeComputer {
     /// Shift relative base by the first parameter
     fn relative_base_offset(&mut self) {
         let p1 = self.get_value(0);
-        self.debug_print(format!(" {}", p1));
+        self.trace(p1);
         self.relative_base += p1;
         self.ip += 2;
     }
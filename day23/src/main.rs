@@ -1,10 +1,18 @@
+mod capture;
+mod codec;
 mod computer;
 mod network;
+mod schedule;
+mod threaded;
 
+use std::env;
 use std::io::Error;
 use std::fs;
 
-use network::Network;
+use capture::Format;
+use network::{Network, NetworkConfig, NetworkReport, NoopNat, Termination};
+use schedule::Schedule;
+use threaded::ThreadedNetwork;
 
 fn read_input() -> Result<Vec<i64>, Error> {
     let content = fs::read_to_string("./input")?;
@@ -16,8 +24,71 @@ fn read_input() -> Result<Vec<i64>, Error> {
     Ok(digits)
 }
 
+/// Run the network of the puzzle and write everything that happened to
+/// a file, as JSON lines if it ends in .jsonl
+fn capture_network(intcodes: Vec<i64>, path: &str) {
+    let config = NetworkConfig {
+        capture: true,
+        ..NetworkConfig::default()
+    };
+    let report = Network::new(intcodes, config).run();
+    match capture::write(path, &report.capture, Format::for_path(path)) {
+        Ok(()) => println!("Wrote {} events of {} rounds to {}", report.capture.len(), report.rounds, path),
+        Err(error) => println!("Could not write {}: {}", path, error)
+    }
+}
+
+fn analyse_capture(path: &str) {
+    match capture::read(path) {
+        Ok(records) => print!("{}", capture::analyse(&records, NetworkConfig::default().nat_address)),
+        Err(error) => println!("Could not read {}: {}", path, error)
+    }
+}
+
+/// Run the network on a single thread, or with a thread for every computer
+fn run_network(intcodes: Vec<i64>, config: NetworkConfig, threaded: bool) -> Result<NetworkReport, String> {
+    if threaded {
+        Ok(ThreadedNetwork::new(intcodes, config)?.run())
+    } else {
+        Ok(Network::new(intcodes, config).run())
+    }
+}
+
+fn solve(intcodes: Vec<i64>, threaded: bool, schedule: Schedule) {
+    // part 1 only needs the first packet sent to the NAT
+    let config = NetworkConfig {
+        nat: Box::new(NoopNat),
+        termination: vec![Termination::FirstNatPacket],
+        schedule,
+        ..NetworkConfig::default()
+    };
+    let report = match run_network(intcodes.clone(), config, threaded) {
+        Ok(report) => report,
+        Err(error) => return println!("{}", error)
+    };
+    println!("Solution Part 1: {}", report.first_nat_packet.unwrap().y);
+
+    let config = NetworkConfig {
+        schedule,
+        ..NetworkConfig::default()
+    };
+    let report = match run_network(intcodes, config, threaded) {
+        Ok(report) => report,
+        Err(error) => return println!("{}", error)
+    };
+    println!("Solution Part 2: {}", report.repeated_y.unwrap());
+}
+
 fn main() {
-    let intcodes = read_input().unwrap();
-    let mut network = Network::new(intcodes);
-    network.run();
+    let args: Vec<String> = env::args().skip(1).collect();
+    match (args.first().map(|mode| mode.as_str()), args.get(1)) {
+        (Some("capture"), Some(path)) => capture_network(read_input().unwrap(), path),
+        (Some("analyse"), Some(path)) => analyse_capture(path),
+        (Some("threaded"), _) => solve(read_input().unwrap(), true, Schedule::RoundRobin),
+        (Some("schedule"), Some(name)) => match name.parse() {
+            Ok(schedule) => solve(read_input().unwrap(), false, schedule),
+            Err(error) => println!("{}", error)
+        },
+        _ => solve(read_input().unwrap(), false, Schedule::RoundRobin)
+    }
 }
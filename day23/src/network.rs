@@ -1,104 +1,366 @@
+use crate::capture::{Kind, Record};
+use crate::codec::{self, CodecError, Destination, PacketCodec};
 use crate::computer::IntcodeComputer;
+use crate::schedule::{Faults, Rng, Schedule};
 use std::collections::{VecDeque, HashSet};
 
-#[derive(Debug, Clone)]
-struct Packet {
-    x: i64,
-    y: i64
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Packet {
+    pub x: i64,
+    pub y: i64
+}
+
+/// What the NAT does with the packets sent to its address
+pub trait Nat {
+    /// a packet was sent to the NAT
+    fn receive(&mut self, packet: Packet);
+    /// the network is idle, returns the address and packet to wake it up
+    fn wake_up(&mut self) -> Option<(usize, Packet)>;
+}
+
+/// The NAT of the puzzle: keeps the last packet it received and sends it
+/// to `address` once the network is idle
+pub struct IdleWakeupNat {
+    pub address: usize,
+    last: Option<Packet>
+}
+
+impl IdleWakeupNat {
+    pub fn new(address: usize) -> IdleWakeupNat {
+        IdleWakeupNat {
+            address,
+            last: None
+        }
+    }
+}
+
+impl Nat for IdleWakeupNat {
+    fn receive(&mut self, packet: Packet) {
+        self.last = Some(packet);
+    }
+
+    fn wake_up(&mut self) -> Option<(usize, Packet)> {
+        self.last.map(|packet| (self.address, packet))
+    }
+}
+
+/// A NAT dropping every packet, the network stays idle once it is
+pub struct NoopNat;
+
+impl Nat for NoopNat {
+    fn receive(&mut self, _packet: Packet) {}
+
+    fn wake_up(&mut self) -> Option<(usize, Packet)> {
+        None
+    }
+}
+
+/// Condition to stop the network at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// the first packet was sent to the NAT
+    FirstNatPacket,
+    /// the NAT woke the network with a y value it sent before
+    RepeatedWakeupY,
+    /// the network is idle and the NAT does not wake it, the network
+    /// always stops there
+    Idle,
+    /// all nodes ran that many rounds, the threaded network has no rounds
+    Rounds(usize),
+    /// the NAT woke the network that many times
+    Wakeups(usize)
+}
+
+pub struct NetworkConfig {
+    /// computers with the addresses 0 to nodes - 1
+    pub nodes: usize,
+    /// packets sent here go to the NAT
+    pub nat_address: i64,
+    pub nat: Box<dyn Nat>,
+    /// the network stops at the first condition met
+    pub termination: Vec<Termination>,
+    /// record every packet and idle detection in the report
+    pub capture: bool,
+    /// order the computers run in, the threaded network only accepts the
+    /// default
+    pub schedule: Schedule,
+    /// faults of the packets between the computers, the threaded network
+    /// only accepts none
+    pub faults: Faults
+}
+
+impl Default for NetworkConfig {
+    /// The network of the puzzle
+    fn default() -> NetworkConfig {
+        NetworkConfig {
+            nodes: 50,
+            nat_address: 255,
+            nat: Box::new(IdleWakeupNat::new(0)),
+            termination: vec![Termination::RepeatedWakeupY],
+            capture: false,
+            schedule: Schedule::RoundRobin,
+            faults: Faults::default()
+        }
+    }
+}
+
+/// What happened until the network stopped
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkReport {
+    /// condition the network stopped at
+    pub termination: Termination,
+    /// rounds of the schedule, always 0 for the threaded network
+    pub rounds: usize,
+    /// times the network went idle
+    pub idle: usize,
+    pub first_nat_packet: Option<Packet>,
+    /// packets the NAT sent to wake the network, with their address
+    pub wakeups: Vec<(usize, Packet)>,
+    /// first y value the NAT sent a second time
+    pub repeated_y: Option<i64>,
+    /// events of the network if captured
+    pub capture: Vec<Record>,
+    /// packets to unknown addresses or cut short by a halt, which were
+    /// dropped, with their source
+    pub errors: Vec<(usize, CodecError)>,
+    /// wakeup the NAT sent to an address outside the network, the network
+    /// stopped as idle then
+    pub invalid_wakeup: Option<(usize, Packet)>
 }
 
 pub struct Network {
-    computers: Vec<IntcodeComputer>,
-    packet_queues: Vec<VecDeque<Packet>>,
-    nat: Packet
+    computers: Vec<PacketCodec>,
+    /// packets waiting for every computer, with their source
+    packet_queues: Vec<VecDeque<(i64, Packet)>>,
+    /// packets held back by a delay fault: round to deliver them in,
+    /// destination, source and packet
+    delayed: Vec<(usize, usize, i64, Packet)>,
+    schedule_rng: Rng,
+    fault_rng: Rng,
+    config: NetworkConfig
 }
 
 impl Network {
-    pub fn new(intcodes: Vec<i64>) -> Network {
+    pub fn new(intcodes: Vec<i64>, config: NetworkConfig) -> Network {
         let mut computers = Vec::new();
         let mut packet_queues = Vec::new();
 
-        for i in 0..50 {
+        for i in 0..config.nodes {
             // boot up computer
-            let mut next_computer = IntcodeComputer::new(intcodes.clone());
-            // next_computer.show_stdinout = true;
-            next_computer.run();
-            // pass network address to computer
-            next_computer.set_memory_input(i);
-            next_computer.run();
-            computers.push(next_computer);
+            let next_computer = IntcodeComputer::new(intcodes.clone());
+            computers.push(PacketCodec::boot(next_computer, i, config.nodes, config.nat_address));
             packet_queues.push(VecDeque::new());
         }
+        let schedule_seed = match config.schedule {
+            Schedule::Random(seed) => seed,
+            _ => 0
+        };
 
         Network {
             computers,
             packet_queues,
-            nat: Packet { x: 0, y:0 }
+            delayed: Vec::new(),
+            schedule_rng: Rng::new(schedule_seed),
+            fault_rng: Rng::new(config.faults.seed),
+            config
+        }
+    }
+
+    fn stops_at(&self, termination: Termination) -> bool {
+        self.config.termination.contains(&termination)
+    }
+
+    fn record(&self, report: &mut NetworkReport, kind: Kind, src: Option<i64>, dst: Option<i64>, packet: Option<Packet>) {
+        if self.config.capture {
+            let round = report.rounds;
+            report.capture.push(Record { round, kind, src, dst, packet });
+        }
+    }
+
+    /// Computers in the order they run this round
+    fn order(&mut self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.computers.len()).collect();
+        if let Schedule::Random(_) = self.config.schedule {
+            self.schedule_rng.shuffle(&mut order);
         }
+        order
     }
 
-    pub fn run(&mut self) {
+    /// Put a packet into the queue of a computer, at a random position if
+    /// it is reordered
+    fn enqueue(&mut self, dst: usize, src: i64, packet: Packet) {
+        let queue = &mut self.packet_queues[dst];
+        if self.fault_rng.chance(self.config.faults.reorder) {
+            let position = self.fault_rng.below(queue.len() + 1);
+            queue.insert(position, (src, packet));
+        } else {
+            queue.push_back((src, packet));
+        }
+    }
+
+    /// Deliver a packet to a computer, subject to the faults
+    fn deliver(&mut self, dst: usize, src: i64, packet: Packet, round: usize) {
+        let faults = self.config.faults;
+        let copies = if self.fault_rng.chance(faults.duplicate) { 2 } else { 1 };
+        for _ in 0..copies {
+            if self.fault_rng.chance(faults.delay) {
+                let rounds = 1 + self.fault_rng.below(faults.max_delay.max(1));
+                self.delayed.push((round + rounds, dst, src, packet));
+            } else {
+                self.enqueue(dst, src, packet);
+            }
+        }
+    }
+
+    /// Deliver the delayed packets that are due in the round
+    fn release_delayed(&mut self, round: usize) {
+        let (due, held): (Vec<_>, Vec<_>) = self.delayed.drain(..).partition(|&(release, ..)| release <= round);
+        self.delayed = held;
+        for (_, dst, src, packet) in due {
+            self.enqueue(dst, src, packet);
+        }
+    }
+
+    /// Route all packets the computer is sending, returns whether it sent
+    /// anything
+    fn send_packets(&mut self, idx: usize, report: &mut NetworkReport) -> bool {
+        let mut sent = false;
+        let src = idx as i64;
+        while let Some(result) = self.computers[idx].next_packet() {
+            sent = true;
+            match result {
+                Ok(sent @ codec::Packet { dst: Destination::Computer(address), .. }) => {
+                    self.record(report, Kind::Send, Some(src), Some(address as i64), Some(sent.payload()));
+                    self.deliver(address, src, sent.payload(), report.rounds);
+                }
+                Ok(sent @ codec::Packet { dst: Destination::Nat, .. }) => {
+                    let nat = self.config.nat_address;
+                    self.record(report, Kind::Send, Some(src), Some(nat), Some(sent.payload()));
+                    self.config.nat.receive(sent.payload());
+                    if report.first_nat_packet.is_none() {
+                        report.first_nat_packet = Some(sent.payload());
+                    }
+                }
+                Err(error) => {
+                    let (dst, packet) = error.sent();
+                    self.record(report, Kind::Send, Some(src), Some(dst), packet);
+                    report.errors.push((idx, error));
+                }
+            }
+        }
+        sent
+    }
+
+    /// Give the computer its turn of the round, returns whether it sent or
+    /// received anything. With `Schedule::UntilBlocked` the turn only ends
+    /// once the computer reads -1 without sending anything: a computer that
+    /// never blocks loops here forever, and `Termination::Rounds` cannot
+    /// stop it since the round never ends.
+    fn run_computer(&mut self, idx: usize, report: &mut NetworkReport) -> bool {
+        let until_blocked = self.config.schedule == Schedule::UntilBlocked;
+        let mut active = false;
+        loop {
+            active |= self.send_packets(idx, report);
+            match self.packet_queues[idx].pop_front() {
+                Some((src, packet)) => {
+                    self.record(report, Kind::Receive, Some(src), Some(idx as i64), Some(packet));
+                    self.computers[idx].receive(packet);
+                    active = true;
+                }
+                None => {
+                    self.computers[idx].receive_nothing();
+                    // blocked once it reads -1 without sending anything
+                    if !until_blocked || !self.send_packets(idx, report) {
+                        return active;
+                    }
+                    active = true;
+                }
+            }
+            if !until_blocked {
+                return active;
+            }
+        }
+    }
+
+    pub fn run(&mut self) -> NetworkReport {
+        let mut report = NetworkReport {
+            termination: Termination::Idle,
+            rounds: 0,
+            idle: 0,
+            first_nat_packet: None,
+            wakeups: Vec::new(),
+            repeated_y: None,
+            capture: Vec::new(),
+            errors: Vec::new(),
+            invalid_wakeup: None
+        };
         let mut nat_sent_y_vals: HashSet<i64> = HashSet::new();
         let mut idle_check = [false, false]; // check if network idle
                                              // if two times nothing happened
                                              // ( true, true ), it's idle
-        'network_loop: loop {
+        let max_rounds = self.config.termination.iter().find_map(|termination| match termination {
+            Termination::Rounds(rounds) => Some(*rounds),
+            _ => None
+        });
+        loop {
+            if let Some(rounds) = max_rounds.filter(|&rounds| report.rounds >= rounds) {
+                report.termination = Termination::Rounds(rounds);
+                return report;
+            }
+            report.rounds += 1;
             idle_check[0] = true; // init check 0
+            self.release_delayed(report.rounds);
 
-            for (idx, computer) in self.computers.iter_mut().enumerate() {
-                loop {
-                    // computer is requesting input
-                    if computer.requesting_input {
-                        break
-                    }
-                    // computer is sending a package
-                    let address = computer.memory_output;
-                    computer.run();
-                    let x = computer.memory_output;
-                    computer.run();
-                    let y = computer.memory_output;
-                    computer.run();
-                    if address >= 0 && address < 50 { // valid address
-                        self.packet_queues.get_mut(address as usize).unwrap().push_back(Packet { x, y });
-                        // println!("SEND: {} -> {}: ({} {})", idx, address, x, y);
-                    } else if address == 255 {
-                        self.nat = Packet { x, y };
-                        // println!("SEND: {} -> {}: ({} {})", idx, address, x, y);
-                    } else {
-                        panic!("Trying to send to unknown address: SEND: {} -> {}: ({} {})", idx, address, x, y);
-                    }
-
+            for idx in self.order() {
+                if self.run_computer(idx, &mut report) {
                     idle_check[0] = false; // network not idle
                 }
-                let queue = &mut self.packet_queues[idx];
-                if queue.len() == 0 {
-                    computer.set_memory_input(-1);
-                    computer.run();
-                } else {
-                    let packet = queue.pop_front().unwrap();
-                    // println!("RECV: {}: ({} {})", idx, packet.x, packet.y);
-                    computer.set_memory_input(packet.x);
-                    computer.run();
-                    computer.set_memory_input(packet.y);
-                    computer.run();
-                    idle_check[0] = false; // network not idle
+                if report.first_nat_packet.is_some() && self.stops_at(Termination::FirstNatPacket) {
+                    report.termination = Termination::FirstNatPacket;
+                    return report;
                 }
             }
+            if !self.delayed.is_empty() {
+                // packets still on their way
+                idle_check[0] = false;
+            }
             if idle_check[0] {
                  // network has once been idle
                 if idle_check[1] {
                     // no sending and no receiving, two times in a row -> network is idle
-                    // send nat package to 0
-                    self.packet_queues.get_mut(0).unwrap().push_back(self.nat.clone());
-                    if nat_sent_y_vals.len() == 0 {
-                        println!("Solution Part 1: {}", self.nat.y);
+                    // let the NAT wake it up
+                    report.idle += 1;
+                    self.record(&mut report, Kind::Idle, None, None, None);
+                    let (address, packet) = match self.config.nat.wake_up() {
+                        Some(wakeup) => wakeup,
+                        None => {
+                            // the network stays idle forever
+                            report.termination = Termination::Idle;
+                            return report;
+                        }
+                    };
+                    if address >= self.computers.len() {
+                        report.invalid_wakeup = Some((address, packet));
+                        report.termination = Termination::Idle;
+                        return report;
                     }
-                    let is_new_value = nat_sent_y_vals.insert(self.nat.y);
-                    if !is_new_value {
-                        println!("Solution Part 2: {}", self.nat.y);
-                        break
+                    let nat = self.config.nat_address;
+                    self.record(&mut report, Kind::NatWakeup, Some(nat), Some(address as i64), Some(packet));
+                    self.packet_queues[address].push_back((nat, packet));
+                    report.wakeups.push((address, packet));
+                    let is_new_value = nat_sent_y_vals.insert(packet.y);
+                    if !is_new_value && report.repeated_y.is_none() {
+                        report.repeated_y = Some(packet.y);
+                        if self.stops_at(Termination::RepeatedWakeupY) {
+                            report.termination = Termination::RepeatedWakeupY;
+                            return report;
+                        }
+                    }
+                    let wakeups = report.wakeups.len();
+                    if self.stops_at(Termination::Wakeups(wakeups)) {
+                        report.termination = Termination::Wakeups(wakeups);
+                        return report;
                     }
-                    // println!("SEND: NAT -> 0: ({} {})", self.nat.x, self.nat.y);
                 } else {
                     // one time, set checker for second round
                     idle_check[1] = true;
@@ -109,4 +371,4 @@ impl Network {
             }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,88 @@
+use std::str::FromStr;
+
+/// Small xorshift generator, so a schedule is reproducible from its seed
+pub struct Rng {
+    state: u64
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // splitmix64 spreads close seeds over the whole state, which must
+        // never be zero
+        let mut state = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        state ^= state >> 31;
+        Rng {
+            state: if state == 0 { 1 } else { state }
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Random number in 0..n
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// True with the given probability in percent
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.next_u64() % 100 < percent
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Order in which the computers run within a round
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    /// every computer in order of its address sends all its packets and
+    /// reads one packet or -1
+    RoundRobin,
+    /// as round-robin, but the computers run in a random order every round
+    Random(u64),
+    /// every computer in order of its address runs until it reads -1
+    /// without sending anything, which a computer that never blocks does not
+    UntilBlocked
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    /// round-robin, until-blocked or random:SEED
+    fn from_str(s: &str) -> Result<Schedule, String> {
+        match s {
+            "round-robin" => Ok(Schedule::RoundRobin),
+            "until-blocked" => Ok(Schedule::UntilBlocked),
+            _ => match s.strip_prefix("random:") {
+                Some(seed) => seed.parse().map(Schedule::Random).map_err(|_| format!("Invalid seed {}.", seed)),
+                None => Err(format!("Unknown schedule {}.", s))
+            }
+        }
+    }
+}
+
+/// Faults of the packet delivery to the computers, as chances in percent
+/// for every packet. Packets of the NAT waking the network are delivered
+/// without faults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Faults {
+    pub seed: u64,
+    /// the packet is put at a random position of the queue
+    pub reorder: u64,
+    /// the packet is delivered twice
+    pub duplicate: u64,
+    /// the packet is held back for 1 to `max_delay` rounds
+    pub delay: u64,
+    pub max_delay: usize
+}
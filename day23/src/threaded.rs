@@ -0,0 +1,259 @@
+use crate::capture::{Kind, Record};
+use crate::codec::{self, CodecError, Destination, PacketCodec};
+use crate::computer::IntcodeComputer;
+use crate::network::{NetworkConfig, NetworkReport, Packet, Termination};
+use crate::schedule::{Faults, Schedule};
+use std::collections::HashSet;
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::thread;
+
+/// What a node gets through its queue
+enum Message {
+    /// a packet with its source
+    Packet(i64, Packet),
+    Stop
+}
+
+/// What a node tells the coordinator
+enum Event {
+    /// a node sent a packet, or one to an unknown address
+    Sent(usize, Result<codec::Packet, CodecError>),
+    /// a node took a packet from its queue: node, source, packet
+    Received(usize, i64, Packet),
+    /// a node read -1, asked for input again without sending anything and
+    /// waits for a packet
+    Blocked(usize),
+    Halted(usize)
+}
+
+/// Run the computer of a node until it is stopped or halts. The first time
+/// the queue is empty the computer reads -1. If it asks again without
+/// sending anything, the node blocks on its queue.
+fn run_node(mut codec: PacketCodec, address: usize, inbox: Receiver<Message>, events: Sender<Event>) {
+    let mut polled = false;
+    loop {
+        while let Some(packet) = codec.next_packet() {
+            polled = false;
+            if events.send(Event::Sent(address, packet)).is_err() {
+                return;
+            }
+        }
+        if codec.halted() {
+            events.send(Event::Halted(address)).ok();
+            return;
+        }
+        let message = match inbox.try_recv() {
+            Ok(message) => message,
+            Err(TryRecvError::Empty) if !polled => {
+                polled = true;
+                codec.receive_nothing();
+                continue;
+            }
+            Err(TryRecvError::Empty) => {
+                if events.send(Event::Blocked(address)).is_err() {
+                    return;
+                }
+                match inbox.recv() {
+                    Ok(message) => message,
+                    Err(_) => return
+                }
+            }
+            Err(TryRecvError::Disconnected) => return
+        };
+        match message {
+            Message::Packet(src, packet) => {
+                if events.send(Event::Received(address, src, packet)).is_err() {
+                    return;
+                }
+                polled = false;
+                codec.receive(packet);
+            }
+            Message::Stop => return
+        }
+    }
+}
+
+/// The network with a thread for every computer. Packets are routed by the
+/// calling thread, which also runs the NAT. The network is idle once every
+/// node is blocked on its empty queue and no packet is in flight, i.e.
+/// routed but not yet taken by its destination. The nodes run in whatever
+/// order the threads do, so there are no rounds: `rounds` of the report
+/// and of the captured records stay 0.
+pub struct ThreadedNetwork {
+    intcodes: Vec<i64>,
+    config: NetworkConfig
+}
+
+impl ThreadedNetwork {
+    /// Fails for a configuration only the single threaded network can run:
+    /// a schedule, faults or a number of rounds to stop at
+    pub fn new(intcodes: Vec<i64>, config: NetworkConfig) -> Result<ThreadedNetwork, String> {
+        if config.schedule != Schedule::RoundRobin {
+            return Err(format!("The threaded network has no schedule, {:?} is not supported.", config.schedule));
+        }
+        if config.faults != Faults::default() {
+            return Err(String::from("The threaded network does not inject faults."));
+        }
+        if config.termination.iter().any(|termination| matches!(termination, Termination::Rounds(_))) {
+            return Err(String::from("The threaded network has no rounds, stop it after a number of wakeups."));
+        }
+        Ok(ThreadedNetwork {
+            intcodes,
+            config
+        })
+    }
+
+    pub fn run(&mut self) -> NetworkReport {
+        let ThreadedNetwork { intcodes, config } = self;
+        let intcodes: &[i64] = intcodes;
+        let nodes = config.nodes;
+        let (event_sender, events) = channel();
+        thread::scope(|scope| {
+            // dropped on a panic as well, which lets blocked nodes return
+            let mut inboxes = Vec::new();
+            for address in 0..nodes {
+                let (sender, inbox) = channel();
+                let events = event_sender.clone();
+                let nat_address = config.nat_address;
+                scope.spawn(move || {
+                    let computer = IntcodeComputer::new(intcodes.to_vec());
+                    run_node(PacketCodec::boot(computer, address, nodes, nat_address), address, inbox, events)
+                });
+                inboxes.push(sender);
+            }
+            drop(event_sender);
+            let report = route(config, &inboxes, &events);
+            for inbox in inboxes.iter() {
+                inbox.send(Message::Stop).ok();
+            }
+            report
+        })
+    }
+}
+
+fn stops_at(config: &NetworkConfig, termination: Termination) -> bool {
+    config.termination.contains(&termination)
+}
+
+/// Route the packets of the nodes until a termination condition is met
+fn route(config: &mut NetworkConfig, inboxes: &[Sender<Message>], events: &Receiver<Event>) -> NetworkReport {
+    let mut report = NetworkReport {
+        termination: Termination::Idle,
+        rounds: 0,
+        idle: 0,
+        first_nat_packet: None,
+        wakeups: Vec::new(),
+        repeated_y: None,
+        capture: Vec::new(),
+        errors: Vec::new(),
+        invalid_wakeup: None
+    };
+    let capture = config.capture;
+    let record = |report: &mut NetworkReport, kind, src, dst, packet| {
+        if capture {
+            report.capture.push(Record { round: 0, kind, src, dst, packet });
+        }
+    };
+    let mut nat_sent_y_vals: HashSet<i64> = HashSet::new();
+    let mut blocked = vec![false; inboxes.len()];
+    let mut halted = vec![false; inboxes.len()];
+    // packets routed to every node but not yet taken
+    let mut in_flight = vec![0; inboxes.len()];
+    loop {
+        if in_flight.iter().all(|&count| count == 0) && blocked.iter().zip(halted.iter()).all(|(&blocked, &halted)| blocked || halted) {
+            // nothing can happen anymore until the NAT wakes the network
+            report.idle += 1;
+            record(&mut report, Kind::Idle, None, None, None);
+            let (address, packet) = match config.nat.wake_up() {
+                Some(wakeup) if wakeup.0 >= inboxes.len() => {
+                    report.invalid_wakeup = Some(wakeup);
+                    report.termination = Termination::Idle;
+                    return report;
+                }
+                Some((address, packet)) if !halted[address] => (address, packet),
+                _ => {
+                    // the network stays idle forever
+                    report.termination = Termination::Idle;
+                    return report;
+                }
+            };
+            let nat = config.nat_address;
+            if inboxes[address].send(Message::Packet(nat, packet)).is_err() {
+                // the node halted before its event arrived, the network
+                // stays idle forever
+                halted[address] = true;
+                report.termination = Termination::Idle;
+                return report;
+            }
+            record(&mut report, Kind::NatWakeup, Some(nat), Some(address as i64), Some(packet));
+            in_flight[address] += 1;
+            report.wakeups.push((address, packet));
+            let is_new_value = nat_sent_y_vals.insert(packet.y);
+            if !is_new_value && report.repeated_y.is_none() {
+                report.repeated_y = Some(packet.y);
+                if stops_at(config, Termination::RepeatedWakeupY) {
+                    report.termination = Termination::RepeatedWakeupY;
+                    return report;
+                }
+            }
+            let wakeups = report.wakeups.len();
+            if stops_at(config, Termination::Wakeups(wakeups)) {
+                report.termination = Termination::Wakeups(wakeups);
+                return report;
+            }
+        }
+        let event = match events.recv() {
+            Ok(event) => event,
+            Err(_) => {
+                // all nodes halted
+                report.termination = Termination::Idle;
+                return report;
+            }
+        };
+        match event {
+            Event::Sent(idx, Ok(sent @ codec::Packet { dst: Destination::Computer(address), .. })) => {
+                let src = idx as i64;
+                record(&mut report, Kind::Send, Some(src), Some(address as i64), Some(sent.payload()));
+                if halted[address] {
+                    continue;
+                }
+                if inboxes[address].send(Message::Packet(src, sent.payload())).is_ok() {
+                    in_flight[address] += 1;
+                } else {
+                    // the node halted before its event arrived, the packet
+                    // is dropped like any other to a halted node
+                    halted[address] = true;
+                    in_flight[address] = 0;
+                }
+            }
+            Event::Sent(idx, Ok(sent @ codec::Packet { dst: Destination::Nat, .. })) => {
+                let packet = sent.payload();
+                record(&mut report, Kind::Send, Some(idx as i64), Some(config.nat_address), Some(packet));
+                config.nat.receive(packet);
+                if report.first_nat_packet.is_none() {
+                    report.first_nat_packet = Some(packet);
+                    if stops_at(config, Termination::FirstNatPacket) {
+                        report.termination = Termination::FirstNatPacket;
+                        return report;
+                    }
+                }
+            }
+            Event::Sent(idx, Err(error)) => {
+                let (dst, packet) = error.sent();
+                record(&mut report, Kind::Send, Some(idx as i64), Some(dst), packet);
+                report.errors.push((idx, error));
+            }
+            Event::Received(idx, src, packet) => {
+                record(&mut report, Kind::Receive, Some(src), Some(idx as i64), Some(packet));
+                in_flight[idx] -= 1;
+                blocked[idx] = false;
+            }
+            Event::Blocked(idx) => blocked[idx] = true,
+            Event::Halted(idx) => {
+                // packets still queued for the node are never taken
+                halted[idx] = true;
+                in_flight[idx] = 0;
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West
+}
+
+impl Direction {
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    /// The command to walk in the direction
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let command = match self {
+            Direction::North => "north",
+            Direction::East => "east",
+            Direction::South => "south",
+            Direction::West => "west"
+        };
+        write!(f, "{}", command)
+    }
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Direction, String> {
+        match s {
+            "north" => Ok(Direction::North),
+            "east" => Ok(Direction::East),
+            "south" => Ok(Direction::South),
+            "west" => Ok(Direction::West),
+            _ => Err(format!("Unknown direction {}.", s))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Room {
+    pub name: String,
+    pub description: String,
+    pub doors: Vec<Direction>,
+    pub items: Vec<String>
+}
+
+/// Result of the pressure-sensitive floor weighing the droid
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Alert {
+    /// "Droids on this ship are heavier than the detected value!"
+    DroidTooLight,
+    /// "Droids on this ship are lighter than the detected value!"
+    DroidTooHeavy
+}
+
+/// Everything the game prints, one message per room or line
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Room(Room),
+    /// "Command?", the game waits for the next command
+    Prompt,
+    Taken(String),
+    Dropped(String),
+    /// the droid was weighed and sent back to the checkpoint
+    Ejected(Alert),
+    /// the droid passed the floor, the password for the main airlock
+    Passcode(u64),
+    Other(String)
+}
+
+/// Parse the list of a "Doors here lead:" or "Items here:" section
+fn parse_list<'a, I: Iterator<Item = &'a str>>(lines: &mut std::iter::Peekable<I>) -> Vec<String> {
+    let mut entries = Vec::new();
+    while let Some(entry) = lines.peek().and_then(|line| line.strip_prefix("- ")) {
+        entries.push(entry.to_string());
+        lines.next();
+    }
+    entries
+}
+
+fn parse_room<'a, I: Iterator<Item = &'a str>>(name: &str, lines: &mut std::iter::Peekable<I>) -> Room {
+    let mut room = Room {
+        name: name.to_string(),
+        description: String::new(),
+        doors: Vec::new(),
+        items: Vec::new()
+    };
+    while let Some(&line) = lines.peek() {
+        if line.is_empty() || line.ends_with(':') {
+            break;
+        }
+        if !room.description.is_empty() {
+            room.description.push('\n');
+        }
+        room.description += line;
+        lines.next();
+    }
+    loop {
+        while lines.peek() == Some(&"") {
+            lines.next();
+        }
+        match lines.peek() {
+            Some(&"Doors here lead:") => {
+                lines.next();
+                room.doors = parse_list(lines)
+                    .iter()
+                    .filter_map(|door| door.parse().ok())
+                    .collect();
+            }
+            Some(&"Items here:") => {
+                lines.next();
+                room.items = parse_list(lines);
+            }
+            _ => break
+        }
+    }
+    room
+}
+
+fn parse_line(line: &str) -> Message {
+    if line == "Command?" {
+        return Message::Prompt;
+    }
+    if let Some(item) = line.strip_prefix("You take the ").and_then(|rest| rest.strip_suffix('.')) {
+        return Message::Taken(item.to_string());
+    }
+    if let Some(item) = line.strip_prefix("You drop the ").and_then(|rest| rest.strip_suffix('.')) {
+        return Message::Dropped(item.to_string());
+    }
+    if line.contains("you are ejected back to the checkpoint") {
+        if line.contains("are heavier than the detected value") {
+            return Message::Ejected(Alert::DroidTooLight);
+        }
+        if line.contains("are lighter than the detected value") {
+            return Message::Ejected(Alert::DroidTooHeavy);
+        }
+    }
+    if let Some(rest) = line.split("by typing ").nth(1) {
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        if let Ok(passcode) = digits.parse() {
+            return Message::Passcode(passcode);
+        }
+    }
+    Message::Other(line.to_string())
+}
+
+/// Split the output of the game into messages
+pub fn parse(text: &str) -> Vec<Message> {
+    let mut messages = Vec::new();
+    let mut lines = text.lines().map(|line| line.trim_end()).peekable();
+    while let Some(line) = lines.next() {
+        if line.is_empty() {
+            continue;
+        }
+        let room_name = line.strip_prefix("== ").and_then(|rest| rest.strip_suffix(" =="));
+        match room_name {
+            Some(name) => messages.push(Message::Room(parse_room(name, &mut lines))),
+            None => messages.push(parse_line(line))
+        }
+    }
+    messages
+}
+
+
+/// The last room described in the messages, where the droid is now
+pub fn current_room(messages: &[Message]) -> Option<&Room> {
+    messages.iter().rev().find_map(|message| match message {
+        Message::Room(room) => Some(room),
+        _ => None
+    })
+}
@@ -1,42 +1,196 @@
 use crate::computer::IntcodeComputer;
+use crate::snapshot;
+use std::collections::VecDeque;
+
+/// Output of the program, split into lines of text
+#[derive(Debug, Clone, PartialEq)]
+pub enum Output {
+    /// a line of ascii text, without the newline
+    Line(String),
+    /// an output value outside of the ascii range
+    Value(i64)
+}
 
 pub struct AsciiComputer {
     computer: IntcodeComputer,
+    /// values printed but not read yet
+    buffered: VecDeque<i64>,
+    /// non ascii values skipped by run, not taken yet
+    values: Vec<i64>,
+    /// instructions left to execute, if limited
+    budget: Option<usize>,
+    exhausted: bool,
     pub terminated: bool
 }
 
+/// State of an AsciiComputer to continue from later
+#[derive(Clone)]
+pub struct Snapshot {
+    computer: snapshot::Snapshot,
+    buffered: VecDeque<i64>,
+    values: Vec<i64>,
+    terminated: bool
+}
+
 impl AsciiComputer {
     pub fn new(intcodes: Vec<i64>) -> AsciiComputer {
-        let mut computer = IntcodeComputer::new(intcodes);
+        let computer = IntcodeComputer::new(intcodes);
         AsciiComputer {
             computer,
+            buffered: VecDeque::new(),
+            values: Vec::new(),
+            budget: None,
+            exhausted: false,
             terminated: false
         }
     }
 
-    pub fn run(&mut self) {
+    /// Run the computer until it pauses or the budget is used up
+    fn run_computer(&mut self) {
+        match self.budget.as_mut() {
+            None => self.computer.run(),
+            Some(budget) => loop {
+                if *budget == 0 {
+                    self.exhausted = true;
+                    break;
+                }
+                *budget -= 1;
+                if !self.computer.step() {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Next output value, None once the program waits for input or terminated
+    fn next_value(&mut self) -> Option<i64> {
+        if let Some(value) = self.buffered.pop_front() {
+            return Some(value);
+        }
+        self.run_computer();
+        if self.exhausted {
+            return None;
+        }
+        if self.computer.terminated || self.computer.requesting_input {
+            self.terminated = self.computer.terminated;
+            return None;
+        }
+        Some(self.computer.memory_output)
+    }
+
+    /// Run until the program asks for input or terminates and return the
+    /// text printed so far. Non ascii values are kept aside, take_values
+    /// returns them.
+    pub fn run(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(value) = self.next_value() {
+            if is_ascii(value) {
+                text.push(value as u8 as char);
+            } else {
+                self.values.push(value);
+            }
+        }
+        text
+    }
+
+    /// Non ascii values skipped by run since the last call
+    pub fn take_values(&mut self) -> Vec<i64> {
+        std::mem::take(&mut self.values)
+    }
+
+    /// Read the next line or non ascii value. Returns None once the program
+    /// waits for input or terminated.
+    pub fn read_line(&mut self) -> Option<Output> {
+        let mut line = String::new();
         loop {
-            self.computer.run();
-            if self.computer.terminated || self.computer.requesting_input {
-                self.terminated = self.computer.terminated;
-                break;
+            match self.next_value() {
+                Some(10) => return Some(Output::Line(line)),
+                Some(value) if is_ascii(value) => line.push(value as u8 as char),
+                Some(value) if line.is_empty() => return Some(Output::Value(value)),
+                Some(value) => {
+                    self.buffered.push_front(value);
+                    return Some(Output::Line(line));
+                }
+                None if line.is_empty() => return None,
+                None => return Some(Output::Line(line))
             }
-            print!("{}", self.computer.memory_output as u8 as char);
         }
-        println!();
     }
 
-    pub fn input_ascii(&mut self, command: &str) {
+    /// Iterate over the lines until the program waits for input
+    pub fn lines(&mut self) -> Lines<'_> {
+        Lines { computer: self }
+    }
+
+    /// Feed the command and a newline as input. Fails without feeding
+    /// anything if the command is not ascii.
+    pub fn input_ascii(&mut self, command: &str) -> Result<(), String> {
+        if let Some(character) = command.chars().find(|character| !character.is_ascii()) {
+            return Err(format!("Non ascii character {:?} in command.", character));
+        }
         for character in command.chars() {
             self.computer.set_memory_input((character as u8) as i64);
-            self.computer.run();
+            loop {
+                self.run_computer();
+                if self.exhausted || self.computer.terminated || self.computer.requesting_input {
+                    break;
+                }
+                self.buffered.push_back(self.computer.memory_output);
+            }
         }
         self.computer.set_memory_input(10);
-        // self.run();
+        Ok(())
+    }
+
+    /// Enter a command and return the response up to the next prompt
+    pub fn enter_command(&mut self, command: &str) -> Result<String, String> {
+        self.input_ascii(command)?;
+        Ok(self.run())
+    }
+
+    /// Enter a command, but give up if the program executes more than
+    /// max_steps instructions before the next prompt. The computer is left
+    /// in the middle of its execution then, restore a snapshot to continue.
+    pub fn try_command(&mut self, command: &str, max_steps: usize) -> Result<Option<String>, String> {
+        self.budget = Some(max_steps);
+        let response = self.enter_command(command);
+        self.budget = None;
+        if self.exhausted {
+            self.exhausted = false;
+            return Ok(None);
+        }
+        response.map(Some)
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            computer: self.computer.snapshot(),
+            buffered: self.buffered.clone(),
+            values: self.values.clone(),
+            terminated: self.terminated
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.computer.restore(&snapshot.computer);
+        self.buffered = snapshot.buffered.clone();
+        self.values = snapshot.values.clone();
+        self.terminated = snapshot.terminated;
     }
+}
+
+pub struct Lines<'a> {
+    computer: &'a mut AsciiComputer
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = Output;
 
-    pub fn enter_command(&mut self, command: &str) {
-        self.input_ascii(command);
-        self.run();
+    fn next(&mut self) -> Option<Output> {
+        self.computer.read_line()
     }
-}
\ No newline at end of file
+}
+
+fn is_ascii(value: i64) -> bool {
+    (0..128).contains(&value)
+}
@@ -0,0 +1,79 @@
+use crate::adventure::{self, Alert, Direction, Message};
+use crate::ascii_computer::AsciiComputer;
+
+/// Items that got the droid past the pressure-sensitive floor
+#[derive(Debug)]
+pub struct Solution {
+    pub items: Vec<String>,
+    pub passcode: u64,
+    /// number of times the droid was weighed
+    pub attempts: usize
+}
+
+/// Item sets as bit masks over the list of items
+fn items_of(items: &[String], set: u32) -> Vec<String> {
+    items
+        .iter()
+        .enumerate()
+        .filter(|(n, _)| set & (1 << n) != 0)
+        .map(|(_, item)| item.clone())
+        .collect()
+}
+
+fn is_subset(set: u32, of: u32) -> bool {
+    set & !of == 0
+}
+
+/// Find the items to carry over the floor. The droid has to stand in the
+/// security checkpoint carrying all the items, door leads to the floor.
+///
+/// Item sets are tried in Gray code order, so that a single item is taken
+/// or dropped between two neighbouring sets. Every ejection rules out more
+/// sets: if a set is too light, so are all of its subsets, if it is too
+/// heavy, so are all of its supersets. These are skipped, so the droid may
+/// have to swap several items between two attempts.
+///
+/// Returns None if no set passes, or without trying any if there are too
+/// many items for the sets to fit a u32.
+pub fn solve(computer: &mut AsciiComputer, items: &[String], door: Direction) -> Option<Solution> {
+    if items.len() >= u32::BITS as usize {
+        return None;
+    }
+    let mut too_light: Vec<u32> = Vec::new();
+    let mut too_heavy: Vec<u32> = Vec::new();
+    let mut held: u32 = (1 << items.len()) - 1;
+    let mut attempts = 0;
+    for i in 0..(1u32 << items.len()) {
+        let set = i ^ (i >> 1);
+        if too_light.iter().any(|&light| is_subset(set, light))
+            || too_heavy.iter().any(|&heavy| is_subset(heavy, set)) {
+            continue;
+        }
+        for (n, item) in items.iter().enumerate() {
+            let bit = 1 << n;
+            if held & bit != 0 && set & bit == 0 {
+                computer.enter_command(&format!("drop {}", item)).unwrap();
+            } else if held & bit == 0 && set & bit != 0 {
+                computer.enter_command(&format!("take {}", item)).unwrap();
+            }
+        }
+        held = set;
+
+        attempts += 1;
+        for message in adventure::parse(&computer.enter_command(&door.to_string()).unwrap()) {
+            match message {
+                Message::Ejected(Alert::DroidTooLight) => too_light.push(set),
+                Message::Ejected(Alert::DroidTooHeavy) => too_heavy.push(set),
+                Message::Passcode(passcode) => {
+                    return Some(Solution {
+                        items: items_of(items, set),
+                        passcode,
+                        attempts
+                    });
+                }
+                _ => ()
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,213 @@
+use crate::adventure::{self, Direction, Message, Room};
+use crate::ascii_computer::AsciiComputer;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+
+/// Instructions a single command may take before the droid counts as trapped
+const MAX_STEPS: usize = 1_000_000;
+
+pub const CHECKPOINT: &str = "Security Checkpoint";
+pub const FLOOR: &str = "Pressure-Sensitive Floor";
+
+/// Rooms of the ship and the doors between them
+#[derive(Debug, Clone, Default)]
+pub struct ShipMap {
+    pub rooms: BTreeMap<String, Room>,
+    /// room behind every explored door
+    pub doors: BTreeMap<(String, Direction), String>
+}
+
+impl ShipMap {
+    /// Add the room or update it with its current items
+    pub fn add_room(&mut self, room: &Room) {
+        self.rooms.insert(room.name.clone(), room.clone());
+    }
+
+    /// Add the door between two rooms and the way back
+    pub fn connect(&mut self, from: &str, direction: Direction, to: &str) {
+        self.doors.insert((from.to_string(), direction), to.to_string());
+        self.doors.insert((to.to_string(), direction.opposite()), from.to_string());
+    }
+
+    /// Shortest way between two rooms
+    pub fn path(&self, from: &str, to: &str) -> Option<Vec<Direction>> {
+        let mut previous: HashMap<&str, (&str, Direction)> = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+        while let Some(room) = queue.pop_front() {
+            if room == to {
+                let mut path = Vec::new();
+                let mut current = to;
+                while current != from {
+                    let (before, direction) = previous[current];
+                    path.push(direction);
+                    current = before;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for ((start, direction), next) in self.doors.iter() {
+                if start == room && next != from && !previous.contains_key(next.as_str()) {
+                    previous.insert(next, (room, *direction));
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// Graphviz graph of the rooms, with the items found in them and a
+    /// labelled edge for every door
+    pub fn to_dot(&self) -> String {
+        let quote = |name: &str| format!("\"{}\"", name.replace('"', "\\\""));
+        let mut dot = String::from("digraph ship {\n    node [shape=box];\n");
+        for room in self.rooms.values() {
+            let mut label = room.name.clone();
+            for item in room.items.iter() {
+                label += "\\n- ";
+                label += item;
+            }
+            let mut attributes = vec![format!("label={}", quote(&label))];
+            match room.name.as_str() {
+                CHECKPOINT => attributes.push(String::from("style=filled, fillcolor=gold")),
+                FLOOR => attributes.push(String::from("style=filled, fillcolor=tomato")),
+                _ => ()
+            }
+            dot += &format!("    {} [{}];\n", quote(&room.name), attributes.join(", "));
+        }
+        for ((from, direction), to) in self.doors.iter() {
+            dot += &format!("    {} -> {} [label=\"{}\"];\n", quote(from), quote(to), direction);
+        }
+        dot += "}\n";
+        dot
+    }
+}
+
+impl fmt::Display for ShipMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for room in self.rooms.values() {
+            let doors: Vec<String> = room.doors
+                .iter()
+                .map(|door| match self.doors.get(&(room.name.clone(), *door)) {
+                    Some(next) => format!("{} -> {}", door, next),
+                    None => format!("{} -> ?", door)
+                })
+                .collect();
+            write!(f, "{}: {}", room.name, doors.join(", "))?;
+            if !room.items.is_empty() {
+                write!(f, " [{}]", room.items.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// What the droid found on its way through the ship
+#[derive(Debug, Default)]
+pub struct Exploration {
+    pub map: ShipMap,
+    /// room the droid started in and returned to
+    pub start: String,
+    /// items taken, in the order they were found
+    pub inventory: Vec<String>,
+    /// items that end the game or trap the droid, left where they are
+    pub dangerous: Vec<String>,
+    /// room and door leading to the pressure-sensitive floor
+    pub checkpoint: Option<(String, Direction)>
+}
+
+struct Explorer<'a> {
+    computer: &'a mut AsciiComputer,
+    exploration: Exploration
+}
+
+impl<'a> Explorer<'a> {
+    /// Walk through all doors of the room that are not explored yet and
+    /// come back to it
+    fn visit(&mut self, room: Room) {
+        self.exploration.map.add_room(&room);
+        for item in room.items.iter() {
+            self.take(item, &room);
+        }
+        for &door in room.doors.iter() {
+            if self.exploration.map.doors.contains_key(&(room.name.clone(), door)) {
+                continue;
+            }
+            let messages = adventure::parse(&self.computer.enter_command(&door.to_string()).unwrap());
+            if self.computer.terminated {
+                return;
+            }
+            let ejected = messages.iter().any(|message| matches!(message, Message::Ejected(_)));
+            if ejected {
+                // the floor sent the droid back to this room
+                if let Some(Message::Room(floor)) = messages.first() {
+                    self.exploration.map.add_room(floor);
+                    self.exploration.map.connect(&room.name, door, &floor.name);
+                    self.exploration.checkpoint = Some((room.name.clone(), door));
+                }
+                continue;
+            }
+            let next = match adventure::current_room(&messages) {
+                Some(next) => next.clone(),
+                None => continue
+            };
+            let known = self.exploration.map.rooms.contains_key(&next.name);
+            self.exploration.map.connect(&room.name, door, &next.name);
+            if !known {
+                self.visit(next);
+            }
+            self.computer.enter_command(&door.opposite().to_string()).unwrap();
+        }
+    }
+
+    /// Take the item, unless doing so ends the game, never returns to the
+    /// prompt or keeps the droid from moving
+    fn take(&mut self, item: &str, room: &Room) {
+        let snapshot = self.computer.snapshot();
+        let taken = match self.computer.try_command(&format!("take {}", item), MAX_STEPS) {
+            Ok(Some(response)) => {
+                !self.computer.terminated
+                    && adventure::parse(&response).contains(&Message::Taken(item.to_string()))
+                    && self.can_move(room)
+            }
+            _ => false
+        };
+        if taken {
+            self.exploration.inventory.push(item.to_string());
+        } else {
+            self.computer.restore(&snapshot);
+            self.exploration.dangerous.push(item.to_string());
+        }
+    }
+
+    /// Try to walk through a door of the room and undo the step
+    fn can_move(&mut self, room: &Room) -> bool {
+        let door = match room.doors.first() {
+            Some(door) => door,
+            None => return true
+        };
+        let snapshot = self.computer.snapshot();
+        let moved = match self.computer.try_command(&door.to_string(), MAX_STEPS) {
+            Ok(Some(response)) => adventure::current_room(&adventure::parse(&response)).is_some(),
+            _ => false
+        };
+        self.computer.restore(&snapshot);
+        moved
+    }
+}
+
+/// Explore the whole ship with a depth first search, taking every item that
+/// is safe to carry. `intro` is the text printed so far, describing the
+/// room the droid is in. The droid ends up in that room again.
+pub fn explore(computer: &mut AsciiComputer, intro: &str) -> Exploration {
+    let mut explorer = Explorer {
+        computer,
+        exploration: Exploration::default()
+    };
+    if let Some(start) = adventure::current_room(&adventure::parse(intro)) {
+        explorer.exploration.start = start.name.clone();
+        explorer.visit(start.clone());
+    }
+    explorer.exploration
+}
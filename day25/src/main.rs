@@ -2,8 +2,14 @@ use std::fs;
 use std::io::{Error, stdin};
 
 mod computer;
+mod snapshot;
 mod ascii_computer;
-use ascii_computer::AsciiComputer;
+mod adventure;
+mod explorer;
+mod checkpoint;
+mod repl;
+use ascii_computer::{AsciiComputer, Output};
+use repl::Repl;
 
 fn read_input() -> Result<Vec<i64>, Error> {
     let content = fs::read_to_string("./input")?;
@@ -26,99 +32,67 @@ fn get_input() -> String {
 fn part1(intcodes: Vec<i64>) {
     println!("Part 1");
     let mut computer = AsciiComputer::new(intcodes);
-    computer.run();
-    let start_commands = 
-    [
-        "east",
-        "take food ration",
-        "east",
-        "take manifold",
-        "east",
-        "east",
-        "take jam",
-        "west",
-        "north",
-        "north",
-        "take fuel cell",
-        "south",
-        "east",
-        "take spool of cat6",
-        "west",
-        "south",
-        "west",
-        "west",
-        "south",
-        "take prime number",
-        "north",
-        "west", // back at start now
-        "north",
-        "north",
-        "north",
-        "east",
-        "east",
-        "take loom",
-        "west",
-        "west",
-        "south",
-        "west",
-        "take mug",
-        "east",
-        "south",
-        "west",
-        "north",
-        "west",
-        "drop spool of cat6"// in front of pressure sensitive room now
-    ];
-    for &command in start_commands.iter() {
-        computer.enter_command(command);
-    }
-    let mut items = [
-        "jam",
-        "loom",
-        "mug",
-        "prime number",
-        "food ration",
-        "fuel cell",
-        "manifold"
-    ];
-    // cat6 spool too heavy if tested alone -> excluded
-    // 7 binary options -> 2^7 = 128 possibilites
-    for i in 0..128 {
-        // drop all items 
-        for &item in items.iter() {
-            computer.enter_command(
-                ("drop ".to_string() +
-                 &item.to_string()).as_ref());
-        }
-        for n in 0..7 {
-            let take_item = i & (1 << n) != 0;
-            if take_item {
-                computer.enter_command(
-                    ("take ".to_string() +
-                     &items[n].to_string()).as_ref());
+    let mut intro = String::new();
+    for output in computer.lines() {
+        match output {
+            Output::Line(line) => {
+                println!("{}", line);
+                intro += &line;
+                intro.push('\n');
             }
+            Output::Value(value) => println!("{}", value)
         }
-        computer.enter_command("north");
     }
+    let exploration = explorer::explore(&mut computer, &intro);
+    println!("{}", exploration.map);
+    println!("Inventory: {}", exploration.inventory.join(", "));
+    println!("Left behind: {}", exploration.dangerous.join(", "));
 
-// solution:
-// You take the mug.
-// You take the prime number.
-// You take the food ration.
-// You take the fuel cell.
-
-
+    let (checkpoint, door) = exploration.checkpoint
+        .expect("Pressure-sensitive floor not found.");
+    let path = exploration.map.path(&exploration.start, &checkpoint).unwrap();
+    for direction in path.iter() {
+        computer.enter_command(&direction.to_string()).unwrap();
+    }
+    // in front of pressure sensitive room now
+    let solution = checkpoint::solve(&mut computer, &exploration.inventory, door)
+        .expect("No combination of items passes the floor.");
+    println!("Items: {}", solution.items.join(", "));
+    println!("Weighed {} times", solution.attempts);
+    println!("Passcode: {}", solution.passcode);
 }
 
-fn part2(intcodes: Vec<i64>) {
-    println!("Part 2");
-    // let mut computer = AsciiComputer::new(intcodes);
-    // computer.run();
+/// Play the game interactively, optionally replaying a transcript first
+fn play(intcodes: Vec<i64>, replay: Option<String>) {
+    let mut repl = Repl::new(AsciiComputer::new(intcodes));
+    print!("{}", repl.start());
+    if let Some(path) = replay {
+        print!("{}", repl.handle(&format!(":replay {}", path)));
+    }
+    while !repl.finished() {
+        let line = get_input();
+        if line.is_empty() {
+            // end of input
+            break;
+        }
+        print!("{}", repl.handle(&line));
+    }
+}
 
+/// Explore the ship and print its map in the DOT format of Graphviz
+fn dot(intcodes: Vec<i64>) {
+    let mut computer = AsciiComputer::new(intcodes);
+    let intro = computer.run();
+    let exploration = explorer::explore(&mut computer, &intro);
+    print!("{}", exploration.map.to_dot());
 }
 
 fn main() {
     let intcodes = read_input().unwrap();
-    part1(intcodes.clone());
-    // part2(intcodes);
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("play") => play(intcodes, args.next()),
+        Some("dot") => dot(intcodes),
+        _ => part1(intcodes)
+    }
 }
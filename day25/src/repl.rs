@@ -0,0 +1,210 @@
+use crate::adventure::{self, Direction, Message};
+use crate::ascii_computer::{AsciiComputer, Snapshot};
+use crate::explorer::ShipMap;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+
+/// Number of commands that can be undone
+const UNDO_LIMIT: usize = 100;
+
+const HELP: &str = "\
+Game commands are passed to the droid. Meta commands:
+:save NAME         save the game
+:load NAME         continue from a saved game
+:map               show the rooms visited so far
+:dot FILE          write the rooms visited so far as Graphviz graph
+:inv               show the items carried
+:undo              take back the last game command
+:transcript FILE   write the game commands leading here to a file
+:replay FILE       enter the commands of a transcript
+:help              show this help
+";
+
+/// What the player knows about the game, saved together with the computer
+#[derive(Clone, Default)]
+struct State {
+    map: ShipMap,
+    room: Option<String>,
+    inventory: Vec<String>,
+    /// game commands that led here
+    transcript: Vec<String>
+}
+
+impl State {
+    /// Learn from the response to a game command
+    fn update(&mut self, command: &str, messages: &[Message]) {
+        if !command.is_empty() {
+            self.transcript.push(command.to_string());
+        }
+        // the first room described is the one behind the door
+        let entered = messages.iter().find_map(|message| match message {
+            Message::Room(room) => Some(room),
+            _ => None
+        });
+        if let (Ok(direction), Some(from), Some(to)) =
+            (command.parse::<Direction>(), self.room.as_ref(), entered) {
+            self.map.connect(from, direction, &to.name);
+        }
+        for message in messages.iter() {
+            match message {
+                Message::Room(room) => self.map.add_room(room),
+                Message::Taken(item) => self.inventory.push(item.clone()),
+                Message::Dropped(item) => self.inventory.retain(|carried| carried != item),
+                _ => ()
+            }
+        }
+        if let Some(room) = adventure::current_room(messages) {
+            self.room = Some(room.name.clone());
+        }
+    }
+}
+
+/// Interactive play of the text adventure. Every line of the player is
+/// either a game command or a meta command starting with ':'.
+pub struct Repl {
+    computer: AsciiComputer,
+    state: State,
+    saves: HashMap<String, (Snapshot, State)>,
+    /// states before the last game commands and loads
+    history: VecDeque<(Snapshot, State)>
+}
+
+impl Repl {
+    pub fn new(computer: AsciiComputer) -> Repl {
+        Repl {
+            computer,
+            state: State::default(),
+            saves: HashMap::new(),
+            history: VecDeque::new()
+        }
+    }
+
+    /// Run the game up to the first prompt and return its text
+    pub fn start(&mut self) -> String {
+        let text = self.computer.run();
+        self.state.update("", &adventure::parse(&text));
+        text
+    }
+
+    /// True once the game is over
+    pub fn finished(&self) -> bool {
+        self.computer.terminated
+    }
+
+    /// Game commands that led to the current state, one per line, ready
+    /// for replay
+    pub fn transcript(&self) -> String {
+        self.state.transcript.iter().map(|command| format!("{}\n", command)).collect()
+    }
+
+    /// Handle a line of the player and return the text to show
+    pub fn handle(&mut self, line: &str) -> String {
+        let line = line.trim();
+        if !line.starts_with(':') {
+            return self.command(line);
+        }
+        let mut words = line.splitn(2, ' ');
+        let meta = words.next().unwrap();
+        let argument = words.next().map(|argument| argument.trim()).unwrap_or("");
+        match (meta, argument) {
+            (":save", name) if !name.is_empty() => {
+                let saved = (self.computer.snapshot(), self.state.clone());
+                self.saves.insert(name.to_string(), saved);
+                format!("Saved {}.\n", name)
+            }
+            (":load", name) if !name.is_empty() => match self.saves.get(name) {
+                Some((snapshot, state)) => {
+                    let (snapshot, state) = (snapshot.clone(), state.clone());
+                    self.remember();
+                    self.computer.restore(&snapshot);
+                    self.state = state;
+                    format!("Loaded {}.\n", name)
+                }
+                None => format!("No save named {}.\n", name)
+            },
+            (":map", "") => self.state.map.to_string(),
+            (":dot", path) if !path.is_empty() => match fs::write(path, self.state.map.to_dot()) {
+                Ok(()) => format!("Wrote the map to {}.\n", path),
+                Err(error) => format!("Could not write {}: {}\n", path, error)
+            },
+            (":inv", "") => {
+                if self.state.inventory.is_empty() {
+                    String::from("You aren't carrying any items.\n")
+                } else {
+                    self.state.inventory.iter().map(|item| format!("- {}\n", item)).collect()
+                }
+            }
+            (":undo", "") => match self.history.pop_back() {
+                Some((snapshot, state)) => {
+                    self.computer.restore(&snapshot);
+                    self.state = state;
+                    match self.state.room.as_ref() {
+                        Some(room) => format!("Back in {}.\n", room),
+                        None => String::from("Undone.\n")
+                    }
+                }
+                None => String::from("Nothing to undo.\n")
+            },
+            (":transcript", path) if !path.is_empty() => match fs::write(path, self.transcript()) {
+                Ok(()) => format!("Wrote {} commands to {}.\n", self.state.transcript.len(), path),
+                Err(error) => format!("Could not write {}: {}\n", path, error)
+            },
+            (":replay", path) if !path.is_empty() => match fs::read_to_string(path) {
+                Ok(content) => self.replay(&content),
+                Err(error) => format!("Could not read {}: {}\n", path, error)
+            },
+            (":help", "") => String::from(HELP),
+            _ => format!("Unknown meta command {}, try :help.\n", line)
+        }
+    }
+
+    /// Handle every line of a transcript, showing the commands as if typed.
+    /// Replays within the transcript are skipped, a transcript replaying
+    /// itself would never end.
+    pub fn replay(&mut self, transcript: &str) -> String {
+        let mut text = String::new();
+        for line in transcript.lines().filter(|line| !line.trim().is_empty()) {
+            if self.finished() {
+                break;
+            }
+            text += &format!("> {}\n", line);
+            if line.trim().split(' ').next() == Some(":replay") {
+                text += "Skipped replay within a replay.\n";
+                continue;
+            }
+            text += &self.handle(line);
+        }
+        text
+    }
+
+    /// Save the current state for undo
+    fn remember(&mut self) {
+        let saved = (self.computer.snapshot(), self.state.clone());
+        self.history.push_back(saved);
+        if self.history.len() > UNDO_LIMIT {
+            self.history.pop_front();
+        }
+    }
+
+    fn command(&mut self, command: &str) -> String {
+        if self.finished() {
+            return String::from("The game is over.\n");
+        }
+        self.remember();
+        match self.computer.enter_command(command) {
+            Ok(mut response) => {
+                self.state.update(command, &adventure::parse(&response));
+                // values outside of the text are shown as numbers
+                for value in self.computer.take_values() {
+                    response += &format!("{}\n", value);
+                }
+                response
+            }
+            Err(error) => {
+                // nothing was entered, so there is nothing to undo
+                self.history.pop_back();
+                format!("{}\n", error)
+            }
+        }
+    }
+}
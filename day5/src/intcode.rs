@@ -0,0 +1,208 @@
+/// Get either the value as position `parameter` in mode 0
+/// or return parameter in mode 1
+fn get_value(instructions: &[i32], parameter: i32, mode: u8) -> i32 {
+    match mode {
+        0 => {
+            if parameter < 0 {
+                panic!("Tried to access negative memory.");
+            }
+            instructions[parameter as usize]
+        },
+        1 => {
+            parameter
+        },
+        _ => {
+            panic!("Passed unknown parameter mode: {}", mode);
+        }
+    }
+}
+
+fn add(instructions: &mut [i32], ip: usize, pmodes: [u8; 3]) {
+    // get first two parameter values
+    let p1 = get_value(instructions, instructions[ip+1], pmodes[0]);
+    let p2 = get_value(instructions, instructions[ip+2], pmodes[1]);
+
+    // store result at third position, must always be position mode
+    if pmodes[2] != 0 {
+        panic!("Write instruction is called in immediate mode");
+    }
+    let p3 = instructions[ip+3];
+    if p3 < 0 {
+        panic!("Trying to store result of addition to negative memory.");
+    }
+    instructions[p3 as usize] = p1 + p2;
+}
+
+fn multiply(instructions: &mut [i32], ip: usize, pmodes: [u8; 3]) {
+    // get first two parameter values
+    let p1 = get_value(instructions, instructions[ip+1], pmodes[0]);
+    let p2 = get_value(instructions, instructions[ip+2], pmodes[1]);
+
+    // store result at third position, must always be position mode
+    if pmodes[2] != 0 {
+        panic!("Write instruction is called in immediate mode");
+    }
+    let p3 = instructions[ip+3];
+    if p3 < 0 {
+        panic!("Trying to store result of multiplication to negative memory.");
+    }
+    instructions[p3 as usize] = p1 * p2;
+}
+
+
+fn less_than(instructions: &mut [i32], ip: usize, pmodes: [u8; 3]) {
+    // get first two parameter values
+    let p1 = get_value(instructions, instructions[ip+1], pmodes[0]);
+    let p2 = get_value(instructions, instructions[ip+2], pmodes[1]);
+
+    // store result at third position, must always be position mode
+    if pmodes[2] != 0 {
+        panic!("Write instruction is called in immediate mode");
+    }
+    let p3 = instructions[ip+3];
+    if p3 < 0 {
+        panic!("Trying to set negative memory.");
+    }
+    if p1 < p2 {
+        instructions[p3 as usize] = 1;
+    } else {
+        instructions[p3 as usize] = 0;
+    }
+}
+
+fn equals(instructions: &mut [i32], ip: usize, pmodes: [u8; 3]) {
+    // get first two parameter values
+    let p1 = get_value(instructions, instructions[ip+1], pmodes[0]);
+    let p2 = get_value(instructions, instructions[ip+2], pmodes[1]);
+
+    // store result at third position, must always be position mode
+    if pmodes[2] != 0 {
+        panic!("Write instruction is called in immediate mode");
+    }
+    let p3 = instructions[ip+3];
+    if p3 < 0 {
+        panic!("Trying to set negative memory.");
+    }
+    if p1 == p2 {
+        instructions[p3 as usize] = 1;
+    } else {
+        instructions[p3 as usize] = 0;
+    }
+}
+
+/// Read input from `inputs` to position at parameter, inputs are consumed in order
+fn input(instructions: &mut [i32], ip: usize, pmodes: [u8; 3], input_value: i32) {
+    if pmodes[0] != 0 {
+        panic!("Input instruction is called in immediate mode");
+    }
+    let p1 = instructions[ip+1];
+    if p1 < 0 {
+        panic!("Tried to input to negative memory.");
+    }
+    instructions[p1 as usize] = input_value;
+}
+
+fn output(instructions: &[i32], ip: usize, pmodes: [u8; 3]) -> i32 {
+    get_value(instructions, instructions[ip+1], pmodes[0])
+}
+
+/// Run the intcodes until opcode 99 is reached. Every input opcode consumes the
+/// next value of `inputs`, all values passed to the output opcode are returned
+pub fn calculate_output(intcodes: &mut [i32], inputs: &[i32]) -> Vec<i32> {
+    calculate_output_within(intcodes, inputs, usize::MAX)
+        .expect("Program did not halt.")
+}
+
+/// Same as calculate_output, but gives up after `max_steps` instructions.
+/// Returns None if opcode 99 was not reached by then
+pub fn calculate_output_within(intcodes: &mut [i32], inputs: &[i32], max_steps: usize) -> Option<Vec<i32>> {
+    let mut inputs = inputs.iter();
+    let mut outputs = Vec::new();
+    let mut ip = 0; // instruction pointer
+    for _ in 0..max_steps {
+        let instruction = intcodes[ip];
+        // read instruction, first seperate by digits and transform to vec of digits
+        let instruct_digits: Vec<u8> = instruction.to_string().chars()
+        .map(|d| d.to_digit(10).unwrap() as u8)
+        .collect();
+
+        let n_digits = instruct_digits.len();
+        let opcode: u8;
+        let mut pmodes: [u8; 3] = [0, 0, 0];
+        if n_digits == 1 {
+            // special case of single digit opcode
+            opcode = instruct_digits[0];
+        } else {
+            // last two digits are opcode
+            opcode = instruct_digits[n_digits-2]*10+instruct_digits[n_digits-1];
+            for i in 0..n_digits-2 {
+                pmodes[i] = instruct_digits[n_digits-3 - i];
+            }
+        }
+        match opcode {
+            1 => {
+                add(intcodes, ip, pmodes);
+                ip += 4;
+            },
+            2 => {
+                multiply(intcodes, ip, pmodes);
+                ip += 4;
+            },
+            3 => {
+                let input_value = *inputs.next()
+                .expect("Program requested more inputs than were passed.");
+                input(intcodes, ip, pmodes, input_value);
+                ip += 2;
+            },
+            4 => {
+                outputs.push(output(intcodes, ip, pmodes));
+                ip += 2;
+            },
+            5 => {
+                // jump-if-true
+                let p1 = get_value(intcodes, intcodes[ip+1], pmodes[0]);
+                let p2 = get_value(intcodes, intcodes[ip+2], pmodes[1]);
+                if p1 != 0 {
+                    if p2 < 0 {
+                        panic!("Trying to jump to negative memory");
+                    }
+                    ip = p2 as usize;
+                } else {
+                    ip += 3;
+                }
+            },
+            6 => {
+                // jump-if-false
+                let p1 = get_value(intcodes, intcodes[ip+1], pmodes[0]);
+                let p2 = get_value(intcodes, intcodes[ip+2], pmodes[1]);
+                if p1 == 0 {
+                    if p2 < 0 {
+                        panic!("Trying to jump to negative memory");
+                    }
+                    ip = p2 as usize;
+                } else {
+                    ip += 3;
+                }
+            },
+            7 => {
+                // less than
+                less_than(intcodes, ip, pmodes);
+                ip += 4;
+            },
+            8 => {
+                // equals
+                equals(intcodes, ip, pmodes);
+                ip += 4;
+            },
+            99 => {
+                return Some(outputs);
+            },
+            _ => {
+                panic!(
+                    "Error while reading intcodes. Unknown opcode {} at \
+                    position: {}", intcodes[ip], ip);
+            }
+        }
+    }
+    None
+}
@@ -1,7 +1,10 @@
 use std::fs;
 use std::io::{Error};
 
-/// Extending day5 solution
+mod intcode;
+use intcode::{calculate_output, part2_machine};
+
+// Extending day5 solution
 
 /// Read file "input" and return content
 fn read_input() -> Result<String, Error> {
@@ -11,208 +14,6 @@ fn read_input() -> Result<String, Error> {
     Ok(content)
 }
 
-/// Get either the value as position `parameter` in mode 0
-/// or return parameter in mode 1
-fn get_value(instructions: &[i32], parameter: i32, mode: u8) -> i32 {
-    match mode {
-        0 => {
-            if parameter < 0 {
-                panic!("Tried to access negative memory.");
-            }
-            instructions[parameter as usize]
-        },
-        1 => {
-            parameter
-        },
-        _ => {
-            panic!("Passed unknown parameter mode: {}", mode);
-        }
-    }
-}
-
-fn add(instructions: &mut [i32], ip: usize, pmodes: [u8; 3]) {
-    // get first two parameter values
-    let p1 = get_value(&instructions, instructions[ip+1], pmodes[0]);
-    let p2 = get_value(&instructions, instructions[ip+2], pmodes[1]);
-
-    // store result at third position, must always be position mode
-    if pmodes[2] != 0 {
-        panic!("Write instruction is called in immedi_ate mode");
-    }
-    let p3 = instructions[ip+3];
-    if p3 < 0 {
-        panic!("Trying to store result of addition to negative memory.");
-    }
-    instructions[p3 as usize] = p1 + p2;
-}
-
-fn multiply(instructions: &mut [i32], ip: usize, pmodes: [u8; 3]) {
-    // get first two parameter values
-    let p1 = get_value(&instructions, instructions[ip+1], pmodes[0]);
-    let p2 = get_value(&instructions, instructions[ip+2], pmodes[1]);
-
-    // store result at third position, must always be position mode
-    if pmodes[2] != 0 {
-        panic!("Write instruction is called in immedi_ate mode");
-    }
-    let p3 = instructions[ip+3];
-    if p3 < 0 {
-        panic!("Trying to store result of multiplication to negative memory.");
-    }
-    instructions[p3 as usize] = p1 * p2;
-}
-
-fn less_than(instructions: &mut [i32], ip: usize, pmodes: [u8; 3]) {
-    // get first two parameter values
-    let p1 = get_value(&instructions, instructions[ip+1], pmodes[0]);
-    let p2 = get_value(&instructions, instructions[ip+2], pmodes[1]);
-
-    // store result at third position, must always be position mode
-    if pmodes[2] != 0 {
-        panic!("Write instruction is called in immedi_ate mode");
-    }
-    let p3 = instructions[ip+3];
-    if p3 < 0 {
-        panic!("Trying to set negative memory.");
-    }
-    if p1 < p2 {
-        instructions[p3 as usize] = 1;
-    } else {
-        instructions[p3 as usize] = 0;
-    }
-}
-
-fn equals(instructions: &mut [i32], ip: usize, pmodes: [u8; 3]) {
-    // get first two parameter values
-    let p1 = get_value(&instructions, instructions[ip+1], pmodes[0]);
-    let p2 = get_value(&instructions, instructions[ip+2], pmodes[1]);
-
-    // store result at third position, must always be position mode
-    if pmodes[2] != 0 {
-        panic!("Write instruction is called in immedi_ate mode");
-    }
-    let p3 = instructions[ip+3];
-    if p3 < 0 {
-        panic!("Trying to set negative memory.");
-    }
-    if p1 == p2 {
-        instructions[p3 as usize] = 1;
-    } else {
-        instructions[p3 as usize] = 0;
-    }
-}
-
-/// calculate output for given intcode table, with inputs and starting from ip
-/// makes a break at every output to be restarted from ip
-/// return tuple of current (ip, output). If opcode 99 is reached, ip i set to -1
-/// to flag termination
-fn calculate_output(intcodes: &mut [i32], inputs: [usize; 2], ip: usize) -> (i32, i32) {
-    let mut ip = ip; // instruction pointer
-    let mut input_counter = 0;
-    let mut output = -1;
-    loop {
-        let instruction = intcodes[ip];
-        // read instruction, first seperate by digits and transform to vec of digits
-        let instruct_digits: Vec<u8> = instruction.to_string().chars()
-        .map(|d| d.to_digit(10).unwrap() as u8)
-        .collect();
-    
-        let n_digits = instruct_digits.len();
-        let opcode: u8;
-        let mut pmodes: [u8; 3] = [0, 0, 0];
-        if n_digits == 1 {
-            // speci_al case of single digit opcode
-            opcode = instruct_digits[0];
-        } else {
-            // last two digits are opcode
-            opcode = instruct_digits[n_digits-2]*10+instruct_digits[n_digits-1];
-            for i in 0..n_digits-2 {
-                pmodes[i] = instruct_digits[n_digits-3 - i];
-            }
-        }
-        match opcode {
-            1 => {
-                add(intcodes, ip, pmodes);
-                ip += 4;
-            },
-            2 => {
-                multiply(intcodes, ip, pmodes);
-                ip += 4;
-            },
-            3 => {
-                // input
-                if pmodes[0] != 0 {
-                    panic!("Input instruction is not called in position mode");
-                }
-                let p1 = intcodes[ip+1];
-                if p1 < 0 {
-                    panic!("Tried to input to negative memory.");
-                }
-                intcodes[p1 as usize] = inputs[input_counter] as i32;
-                input_counter += 1;
-                ip += 2;
-            },
-            4 => {
-                //output
-                let p1 = get_value(&intcodes, intcodes[ip+1], pmodes[0]);
-                output = p1;
-                ip += 2;
-                println!("OUTPUT: {}", output);
-                return (ip as i32, output)
-            },
-            5 => {
-                // jump-if-true
-                let p1 = get_value(&intcodes, intcodes[ip+1], pmodes[0]);
-                let p2 = get_value(&intcodes, intcodes[ip+2], pmodes[1]);
-                if p1 != 0 {
-                    if p2 < 0 {
-                        panic!("Trying to jump to negative memory");
-                    }
-                    ip = p2 as usize;
-                } else {
-                    ip += 3;
-                }
-            },
-            6 => {
-                // jump-if-false
-                let p1 = get_value(&intcodes, intcodes[ip+1], pmodes[0]);
-                let p2 = get_value(&intcodes, intcodes[ip+2], pmodes[1]);
-                if p1 == 0 {
-                    if p2 < 0 {
-                        panic!("Trying to jump to negative memory");
-                    }
-                    ip = p2 as usize;
-                } else {
-                    ip += 3;
-                }
-            },
-            7 => {
-                // less than
-                less_than(intcodes, ip, pmodes);
-                ip += 4;
-            },
-            8 => {
-                // equals
-                equals(intcodes, ip, pmodes);
-                ip += 4;
-            },
-            99 => {
-                return (-1, output);
-            },
-            _ => {
-                panic!(
-                    "Error while reading intcodes. Unknown opcode {} at \
-                    position: {}", intcodes[ip], ip);
-            }
-        }
-    }
-}
-
-
-
-
-
-
 fn part1() {
     let intcode_string = read_input().unwrap();
     let intcodes = intcode_string.split(",")
@@ -265,116 +66,6 @@ fn part1() {
 
 
 
-/// calculate output for given intcode table, with inputs and starting from ip
-/// makes a break at every output to be restarted from ip
-/// return tuple of current (ip, output). If opcode 99 is reached, ip i set to -1
-/// to flag termination
-fn part2_machine(intcodes: &mut [i32], input: i32, ip: usize) -> (i32, i32) {
-    let mut ip = ip; // instruction pointer
-    let mut input = input;
-    let mut output = -1;
-
-    loop {
-        let instruction = intcodes[ip];
-        // read instruction, first seperate by digits and transform to vec of digits
-        let instruct_digits: Vec<u8> = instruction.to_string().chars()
-        .map(|d| d.to_digit(10).unwrap() as u8)
-        .collect();
-    
-        let n_digits = instruct_digits.len();
-        let opcode: u8;
-        let mut pmodes: [u8; 3] = [0, 0, 0];
-        if n_digits == 1 {
-            // speci_al case of single digit opcode
-            opcode = instruct_digits[0];
-        } else {
-            // last two digits are opcode
-            opcode = instruct_digits[n_digits-2]*10+instruct_digits[n_digits-1];
-            for i in 0..n_digits-2 {
-                pmodes[i] = instruct_digits[n_digits-3 - i];
-            }
-        }
-        match opcode {
-            1 => {
-                add(intcodes, ip, pmodes);
-                ip += 4;
-            },
-            2 => {
-                multiply(intcodes, ip, pmodes);
-                ip += 4;
-            },
-            3 => {
-                // input
-                if input == -1 {
-                    // previous input was already consumed, halt and wait for
-                    // new input
-                    return (ip as i32, output)
-                }
-                if pmodes[0] != 0 {
-                    panic!("Input instruction is not called in position mode");
-                }
-                let p1 = intcodes[ip+1];
-                if p1 < 0 {
-                    panic!("Tried to input to negative memory.");
-                }
-                intcodes[p1 as usize] = input;
-                ip += 2;
-                input = -1; // consumed input
-            },
-            4 => {
-                //output
-                let p1 = get_value(&intcodes, intcodes[ip+1], pmodes[0]);
-                output = p1;
-                ip += 2;
-            },
-            5 => {
-                // jump-if-true
-                let p1 = get_value(&intcodes, intcodes[ip+1], pmodes[0]);
-                let p2 = get_value(&intcodes, intcodes[ip+2], pmodes[1]);
-                if p1 != 0 {
-                    if p2 < 0 {
-                        panic!("Trying to jump to negative memory");
-                    }
-                    ip = p2 as usize;
-                } else {
-                    ip += 3;
-                }
-            },
-            6 => {
-                // jump-if-false
-                let p1 = get_value(&intcodes, intcodes[ip+1], pmodes[0]);
-                let p2 = get_value(&intcodes, intcodes[ip+2], pmodes[1]);
-                if p1 == 0 {
-                    if p2 < 0 {
-                        panic!("Trying to jump to negative memory");
-                    }
-                    ip = p2 as usize;
-                } else {
-                    ip += 3;
-                }
-            },
-            7 => {
-                // less than
-                less_than(intcodes, ip, pmodes);
-                ip += 4;
-            },
-            8 => {
-                // equals
-                equals(intcodes, ip, pmodes);
-                ip += 4;
-            },
-            99 => {
-                return (-1, output);
-            },
-            _ => {
-                panic!(
-                    "Error while reading intcodes. Unknown opcode {} at \
-                    position: {}", intcodes[ip], ip);
-            }
-        }
-    }
-}
-
 /// feedback loop where machine waits at every input request
 fn part2() {
     let intcode_string = read_input().unwrap();
@@ -460,6 +151,6 @@ fn part2() {
 
 
 fn main() {
-    // part1();
+    part1();
     part2();
 }
@@ -0,0 +1,46 @@
+// Compiler of a tiny language to intcode. A program is a list of functions,
+// execution starts at `main`:
+//
+//     fn square(x) { return x * x; }
+//     fn main() {
+//         let n = input();
+//         while n > 0 {
+//             output(square(n));
+//             n = n - 1;
+//         }
+//     }
+//
+// Values are integers, conditions are true if non zero. Supported are
+// `+ - *`, comparisons, `&& || !`, `let`, assignments, `if`/`else`, `while`,
+// `return`, `input()` and `output(x)`. The relative base is used as stack
+// pointer, see codegen for the calling convention.
+
+mod codegen;
+mod parser;
+
+use std::fmt;
+
+/// Error while compiling, with the line of the source it occurred in
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompileError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl CompileError {
+    fn new(line: usize, message: String) -> CompileError {
+        CompileError { line, message }
+    }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Compile the source code to intcodes runnable by the IntcodeComputer
+pub fn compile(source: &str) -> Result<Vec<i64>, CompileError> {
+    let functions = parser::parse(source)?;
+    codegen::generate(&functions)
+}
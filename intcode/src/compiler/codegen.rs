@@ -0,0 +1,459 @@
+// Code generation. The relative base points to the frame of the running
+// function, which holds
+//
+//     rel(0)        return address
+//     rel(1..=n)    parameters
+//     rel(n+1..)    local variables and temporaries
+//
+// A call evaluates the arguments into temporaries of the caller, copies the
+// return address and the arguments to the start of a new frame behind the
+// frame of the caller, moves the relative base onto it and jumps to the
+// function. The function leaves its result in a fixed return cell and jumps
+// back to rel(0), after which the caller moves the relative base back.
+
+use super::parser::{BinaryOp, Expr, Function, Statement, UnaryOp};
+use super::CompileError;
+use std::collections::HashMap;
+
+const ADD: i64 = 1;
+const MUL: i64 = 2;
+const INP: i64 = 3;
+const OUT: i64 = 4;
+const JIT: i64 = 5;
+const JIF: i64 = 6;
+const LET: i64 = 7;
+const EQU: i64 = 8;
+const RBO: i64 = 9;
+const TER: i64 = 99;
+
+/// A word of the program, some only known once everything is generated
+#[derive(Debug, Clone, Copy)]
+enum Word {
+    Value(i64),
+    Label(usize),
+    /// size of the frame of a function plus an offset
+    FrameSize(usize, i64),
+    NegativeFrameSize(usize),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operand {
+    Position(Word),
+    Immediate(Word),
+    Relative(Word),
+}
+
+impl Operand {
+    fn mode(&self) -> i64 {
+        match self {
+            Operand::Position(_) => 0,
+            Operand::Immediate(_) => 1,
+            Operand::Relative(_) => 2,
+        }
+    }
+
+    fn word(&self) -> Word {
+        match self {
+            Operand::Position(word) | Operand::Immediate(word) | Operand::Relative(word) => *word,
+        }
+    }
+}
+
+fn value(value: i64) -> Operand {
+    Operand::Immediate(Word::Value(value))
+}
+
+fn slot(slot: i64) -> Operand {
+    Operand::Relative(Word::Value(slot))
+}
+
+struct Signature {
+    label: usize,
+    arity: usize,
+}
+
+struct Generator<'a> {
+    code: Vec<Word>,
+    /// address of every label, once it is placed
+    labels: Vec<Option<usize>>,
+    signatures: HashMap<&'a str, Signature>,
+    frame_sizes: Vec<i64>,
+    return_cell: usize,
+    /// index of the function currently generated
+    function: usize,
+    /// variables by name and slot, innermost scope last
+    scopes: Vec<Vec<(&'a str, i64)>>,
+    next_slot: i64,
+}
+
+impl<'a> Generator<'a> {
+    fn new_label(&mut self) -> usize {
+        self.labels.push(None);
+        self.labels.len() - 1
+    }
+
+    fn place_label(&mut self, label: usize) {
+        self.labels[label] = Some(self.code.len());
+    }
+
+    fn emit(&mut self, opcode: i64, operands: &[Operand]) {
+        let mut instruction = opcode;
+        for (i, operand) in operands.iter().enumerate() {
+            instruction += operand.mode() * 10_i64.pow(i as u32 + 2);
+        }
+        self.code.push(Word::Value(instruction));
+        self.code
+            .extend(operands.iter().map(|operand| operand.word()));
+    }
+
+    fn copy(&mut self, from: Operand, to: Operand) {
+        self.emit(ADD, &[from, value(0), to]);
+    }
+
+    fn jump(&mut self, label: usize) {
+        self.emit(JIT, &[value(1), Operand::Immediate(Word::Label(label))]);
+    }
+
+    /// Reserve the next free slot of the frame
+    fn temporary(&mut self) -> Operand {
+        let slot_index = self.next_slot;
+        self.next_slot += 1;
+        let frame_size = &mut self.frame_sizes[self.function];
+        *frame_size = (*frame_size).max(self.next_slot);
+        slot(slot_index)
+    }
+
+    fn variable(&self, name: &str, line: usize) -> Result<Operand, CompileError> {
+        for scope in self.scopes.iter().rev() {
+            if let Some((_, slot_index)) =
+                scope.iter().rev().find(|(variable, _)| *variable == name)
+            {
+                return Ok(slot(*slot_index));
+            }
+        }
+        Err(CompileError::new(
+            line,
+            format!("Unknown variable {}.", name),
+        ))
+    }
+
+    fn function(&mut self, index: usize, function: &'a Function) -> Result<(), CompileError> {
+        self.function = index;
+        let label = self.signatures[function.name.as_str()].label;
+        self.place_label(label);
+        let mut params: Vec<(&str, i64)> = Vec::new();
+        for (i, param) in function.params.iter().enumerate() {
+            if params.iter().any(|(name, _)| name == param) {
+                return Err(CompileError::new(
+                    function.line,
+                    format!("Parameter {} is declared twice.", param),
+                ));
+            }
+            params.push((param, i as i64 + 1));
+        }
+        self.next_slot = params.len() as i64 + 1;
+        self.frame_sizes[index] = self.next_slot;
+        self.scopes = vec![params];
+        self.block(&function.body)?;
+        self.return_value(value(0));
+        Ok(())
+    }
+
+    fn block(&mut self, statements: &'a [Statement]) -> Result<(), CompileError> {
+        let saved = self.next_slot;
+        self.scopes.push(Vec::new());
+        for statement in statements.iter() {
+            self.statement(statement)?;
+        }
+        self.scopes.pop();
+        self.next_slot = saved;
+        Ok(())
+    }
+
+    fn return_value(&mut self, result: Operand) {
+        self.copy(result, Operand::Position(Word::Label(self.return_cell)));
+        self.emit(JIT, &[value(1), slot(0)]);
+    }
+
+    fn statement(&mut self, statement: &'a Statement) -> Result<(), CompileError> {
+        let saved = self.next_slot;
+        match statement {
+            Statement::Let(name, expr, line) => {
+                let scope = self.scopes.last().unwrap();
+                if scope.iter().any(|(variable, _)| variable == name) {
+                    return Err(CompileError::new(
+                        *line,
+                        format!("Variable {} is already declared.", name),
+                    ));
+                }
+                let result = self.expression(expr)?;
+                self.next_slot = saved;
+                let variable = self.temporary();
+                self.copy(result, variable);
+                self.scopes.last_mut().unwrap().push((name.as_str(), saved));
+                // the slot stays reserved for the variable
+                return Ok(());
+            }
+            Statement::Assign(name, expr, line) => {
+                let variable = self.variable(name, *line)?;
+                let result = self.expression(expr)?;
+                self.copy(result, variable);
+            }
+            Statement::If(condition, then, otherwise) => {
+                let else_label = self.new_label();
+                let end_label = self.new_label();
+                let condition = self.expression(condition)?;
+                self.emit(
+                    JIF,
+                    &[condition, Operand::Immediate(Word::Label(else_label))],
+                );
+                self.next_slot = saved;
+                self.block(then)?;
+                self.jump(end_label);
+                self.place_label(else_label);
+                self.block(otherwise)?;
+                self.place_label(end_label);
+            }
+            Statement::While(condition, body) => {
+                let start_label = self.new_label();
+                let end_label = self.new_label();
+                self.place_label(start_label);
+                let condition = self.expression(condition)?;
+                self.emit(
+                    JIF,
+                    &[condition, Operand::Immediate(Word::Label(end_label))],
+                );
+                self.next_slot = saved;
+                self.block(body)?;
+                self.jump(start_label);
+                self.place_label(end_label);
+            }
+            Statement::Return(expr) => {
+                let result = match expr {
+                    Some(expr) => self.expression(expr)?,
+                    None => value(0),
+                };
+                self.return_value(result);
+            }
+            Statement::Expr(expr) => {
+                self.expression(expr)?;
+            }
+        }
+        self.next_slot = saved;
+        Ok(())
+    }
+
+    /// Generate code evaluating the expression, returns where its value is.
+    /// Temporaries used for it stay reserved until the caller resets
+    /// next_slot.
+    fn expression(&mut self, expr: &'a Expr) -> Result<Operand, CompileError> {
+        let saved = self.next_slot;
+        match expr {
+            Expr::Number(number) => Ok(value(*number)),
+            Expr::Variable(name, line) => self.variable(name, *line),
+            Expr::Unary(op, operand) => {
+                let operand = self.expression(operand)?;
+                self.next_slot = saved;
+                let result = self.temporary();
+                match op {
+                    UnaryOp::Negate => self.emit(MUL, &[operand, value(-1), result]),
+                    UnaryOp::Not => self.emit(EQU, &[operand, value(0), result]),
+                }
+                Ok(result)
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.expression(left)?;
+                let right = self.expression(right)?;
+                // every sequence ends with a single instruction reading the
+                // operands, the result may reuse their slots
+                match op {
+                    BinaryOp::Sub => {
+                        let negated = self.temporary();
+                        self.emit(MUL, &[right, value(-1), negated]);
+                        self.next_slot = saved;
+                        let result = self.temporary();
+                        self.emit(ADD, &[left, negated, result]);
+                        Ok(result)
+                    }
+                    BinaryOp::And | BinaryOp::Or => {
+                        let left_false = self.temporary();
+                        let right_false = self.temporary();
+                        self.emit(EQU, &[left, value(0), left_false]);
+                        self.emit(EQU, &[right, value(0), right_false]);
+                        self.next_slot = saved;
+                        let result = self.temporary();
+                        let combine = if *op == BinaryOp::And { ADD } else { MUL };
+                        self.emit(combine, &[left_false, right_false, result]);
+                        self.emit(EQU, &[result, value(0), result]);
+                        Ok(result)
+                    }
+                    _ => {
+                        self.next_slot = saved;
+                        let result = self.temporary();
+                        let (opcode, operands, negate) = match op {
+                            BinaryOp::Add => (ADD, [left, right], false),
+                            BinaryOp::Mul => (MUL, [left, right], false),
+                            BinaryOp::Less => (LET, [left, right], false),
+                            BinaryOp::Greater => (LET, [right, left], false),
+                            BinaryOp::LessEqual => (LET, [right, left], true),
+                            BinaryOp::GreaterEqual => (LET, [left, right], true),
+                            BinaryOp::Equal => (EQU, [left, right], false),
+                            _ => (EQU, [left, right], true),
+                        };
+                        self.emit(opcode, &[operands[0], operands[1], result]);
+                        if negate {
+                            self.emit(EQU, &[result, value(0), result]);
+                        }
+                        Ok(result)
+                    }
+                }
+            }
+            Expr::Call(name, args, line) => self.call(name, args, *line),
+        }
+    }
+
+    fn call(
+        &mut self,
+        name: &'a str,
+        args: &'a [Expr],
+        line: usize,
+    ) -> Result<Operand, CompileError> {
+        let saved = self.next_slot;
+        let arity = match name {
+            "input" => 0,
+            "output" => 1,
+            _ => match self.signatures.get(name) {
+                Some(signature) => signature.arity,
+                None => {
+                    return Err(CompileError::new(
+                        line,
+                        format!("Unknown function {}.", name),
+                    ));
+                }
+            },
+        };
+        if args.len() != arity {
+            return Err(CompileError::new(
+                line,
+                format!(
+                    "Function {} takes {} arguments but got {}.",
+                    name,
+                    arity,
+                    args.len()
+                ),
+            ));
+        }
+        let mut values = Vec::new();
+        for arg in args.iter() {
+            values.push(self.expression(arg)?);
+        }
+        match name {
+            "input" => {
+                let result = self.temporary();
+                self.emit(INP, &[result]);
+                Ok(result)
+            }
+            "output" => {
+                self.emit(OUT, &[values[0]]);
+                self.next_slot = saved;
+                Ok(value(0))
+            }
+            _ => {
+                let function = self.function;
+                let frame = |offset: i64| Operand::Relative(Word::FrameSize(function, offset));
+                let return_label = self.new_label();
+                self.copy(Operand::Immediate(Word::Label(return_label)), frame(0));
+                for (i, arg) in values.iter().enumerate() {
+                    self.copy(*arg, frame(i as i64 + 1));
+                }
+                self.emit(RBO, &[Operand::Immediate(Word::FrameSize(function, 0))]);
+                self.jump(self.signatures[name].label);
+                self.place_label(return_label);
+                self.emit(
+                    RBO,
+                    &[Operand::Immediate(Word::NegativeFrameSize(function))],
+                );
+                self.next_slot = saved;
+                let result = self.temporary();
+                self.copy(Operand::Position(Word::Label(self.return_cell)), result);
+                Ok(result)
+            }
+        }
+    }
+}
+
+/// Generate the intcodes of the program, starting with a call to main
+pub fn generate(functions: &[Function]) -> Result<Vec<i64>, CompileError> {
+    let mut generator = Generator {
+        code: Vec::new(),
+        labels: Vec::new(),
+        signatures: HashMap::new(),
+        frame_sizes: vec![0; functions.len()],
+        return_cell: 0,
+        function: 0,
+        scopes: Vec::new(),
+        next_slot: 0,
+    };
+    for function in functions.iter() {
+        let name = function.name.as_str();
+        if name == "input" || name == "output" || generator.signatures.contains_key(name) {
+            return Err(CompileError::new(
+                function.line,
+                format!("Function {} is already defined.", name),
+            ));
+        }
+        let label = generator.new_label();
+        generator.signatures.insert(
+            name,
+            Signature {
+                label,
+                arity: function.params.len(),
+            },
+        );
+    }
+    let main = match generator.signatures.get("main") {
+        Some(main) if main.arity == 0 => main.label,
+        Some(_) => {
+            return Err(CompileError::new(
+                1,
+                String::from("Function main takes no arguments."),
+            ))
+        }
+        None => {
+            return Err(CompileError::new(
+                1,
+                String::from("Function main is missing."),
+            ))
+        }
+    };
+    generator.return_cell = generator.new_label();
+    let stack = generator.new_label();
+    let halt = generator.new_label();
+
+    // set up the stack and call main, which returns to the halt
+    generator.emit(RBO, &[Operand::Immediate(Word::Label(stack))]);
+    generator.copy(Operand::Immediate(Word::Label(halt)), slot(0));
+    generator.jump(main);
+    generator.place_label(halt);
+    generator.emit(TER, &[]);
+
+    for (index, function) in functions.iter().enumerate() {
+        generator.function(index, function)?;
+    }
+    generator.place_label(generator.return_cell);
+    generator.code.push(Word::Value(0));
+    generator.place_label(stack);
+
+    let labels = &generator.labels;
+    let frame_sizes = &generator.frame_sizes;
+    Ok(generator
+        .code
+        .iter()
+        .map(|word| match word {
+            Word::Value(value) => *value,
+            Word::Label(label) => labels[*label].expect("Label was never placed.") as i64,
+            Word::FrameSize(function, offset) => frame_sizes[*function] + offset,
+            Word::NegativeFrameSize(function) => -frame_sizes[*function],
+        })
+        .collect())
+}
@@ -0,0 +1,358 @@
+use super::CompileError;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(i64),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+/// Symbols of the language, two character symbols first so they match first
+const SYMBOLS: [&str; 19] = [
+    "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "<", ">", "!", "=", "(", ")", "{", "}", ",",
+    ";",
+];
+
+const KEYWORDS: [&str; 6] = ["fn", "let", "if", "else", "while", "return"];
+
+/// Split the source into tokens, each with its line number
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, CompileError> {
+    let mut tokens = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let line_number = i + 1;
+        // comments run until the end of the line
+        let code = line.split("//").next().unwrap();
+        let mut rest = code.trim_start();
+        while !rest.is_empty() {
+            let first = rest.chars().next().unwrap();
+            let length;
+            if first.is_ascii_digit() {
+                length = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                let number = rest[..length].parse().map_err(|_| {
+                    CompileError::new(
+                        line_number,
+                        format!("Number {} is too large.", &rest[..length]),
+                    )
+                })?;
+                tokens.push((Token::Number(number), line_number));
+            } else if first.is_alphabetic() || first == '_' {
+                length = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                tokens.push((Token::Ident(String::from(&rest[..length])), line_number));
+            } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(*symbol)) {
+                length = symbol.len();
+                tokens.push((Token::Symbol(symbol), line_number));
+            } else {
+                return Err(CompileError::new(
+                    line_number,
+                    format!("Unexpected character '{}'.", first),
+                ));
+            }
+            rest = rest[length..].trim_start();
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    Negate,
+    Not,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(i64),
+    Variable(String, usize),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Unary(UnaryOp, Box<Expr>),
+    /// call of a function (or the builtins input and output) in a line
+    Call(String, Vec<Expr>, usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Let(String, Expr, usize),
+    Assign(String, Expr, usize),
+    If(Expr, Vec<Statement>, Vec<Statement>),
+    While(Expr, Vec<Statement>),
+    Return(Option<Expr>),
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Vec<Statement>,
+    pub line: usize,
+}
+
+/// Binary operators by precedence, loosest binding first
+const PRECEDENCE: [&[(&str, BinaryOp)]; 5] = [
+    &[("||", BinaryOp::Or)],
+    &[("&&", BinaryOp::And)],
+    &[("==", BinaryOp::Equal), ("!=", BinaryOp::NotEqual)],
+    &[
+        ("<", BinaryOp::Less),
+        (">", BinaryOp::Greater),
+        ("<=", BinaryOp::LessEqual),
+        (">=", BinaryOp::GreaterEqual),
+    ],
+    &[("+", BinaryOp::Add), ("-", BinaryOp::Sub)],
+];
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    /// Line of the current token, or of the last one at the end of the source
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or_else(|| self.tokens.last())
+            .map(|(_, line)| *line)
+            .unwrap_or(1)
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, CompileError> {
+        Err(CompileError::new(self.line(), message))
+    }
+
+    fn describe(&self) -> String {
+        match self.peek() {
+            Some(Token::Number(number)) => format!("'{}'", number),
+            Some(Token::Ident(name)) => format!("'{}'", name),
+            Some(Token::Symbol(symbol)) => format!("'{}'", symbol),
+            None => String::from("end of input"),
+        }
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Token::Symbol(next)) if *next == symbol)
+            || self.is_keyword(symbol)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(name)) if name == keyword)
+    }
+
+    /// Consume the symbol or keyword if it is next
+    fn accept(&mut self, symbol: &str) -> bool {
+        if self.is_symbol(symbol) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), CompileError> {
+        if self.accept(symbol) {
+            Ok(())
+        } else {
+            self.error(format!(
+                "Expected '{}' but found {}.",
+                symbol,
+                self.describe()
+            ))
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, CompileError> {
+        match self.peek() {
+            Some(Token::Ident(name)) if !KEYWORDS.contains(&name.as_str()) => {
+                let name = name.clone();
+                self.position += 1;
+                Ok(name)
+            }
+            _ => self.error(format!("Expected a name but found {}.", self.describe())),
+        }
+    }
+
+    fn function(&mut self) -> Result<Function, CompileError> {
+        let line = self.line();
+        self.expect("fn")?;
+        let name = self.identifier()?;
+        self.expect("(")?;
+        let mut params = Vec::new();
+        if !self.accept(")") {
+            loop {
+                params.push(self.identifier()?);
+                if self.accept(")") {
+                    break;
+                }
+                self.expect(",")?;
+            }
+        }
+        let body = self.block()?;
+        Ok(Function {
+            name,
+            params,
+            body,
+            line,
+        })
+    }
+
+    fn block(&mut self) -> Result<Vec<Statement>, CompileError> {
+        self.expect("{")?;
+        let mut statements = Vec::new();
+        while !self.accept("}") {
+            if self.peek().is_none() {
+                return self.error(String::from("Block is not closed."));
+            }
+            statements.push(self.statement()?);
+        }
+        Ok(statements)
+    }
+
+    fn statement(&mut self) -> Result<Statement, CompileError> {
+        let line = self.line();
+        if self.accept("let") {
+            let name = self.identifier()?;
+            self.expect("=")?;
+            let value = self.expression(0)?;
+            self.expect(";")?;
+            Ok(Statement::Let(name, value, line))
+        } else if self.accept("if") {
+            let condition = self.expression(0)?;
+            let then = self.block()?;
+            let otherwise = if !self.accept("else") {
+                Vec::new()
+            } else if self.is_keyword("if") {
+                vec![self.statement()?]
+            } else {
+                self.block()?
+            };
+            Ok(Statement::If(condition, then, otherwise))
+        } else if self.accept("while") {
+            let condition = self.expression(0)?;
+            Ok(Statement::While(condition, self.block()?))
+        } else if self.accept("return") {
+            let value = if self.is_symbol(";") {
+                None
+            } else {
+                Some(self.expression(0)?)
+            };
+            self.expect(";")?;
+            Ok(Statement::Return(value))
+        } else {
+            let expr = self.expression(0)?;
+            if self.accept("=") {
+                let name = match expr {
+                    Expr::Variable(name, _) => name,
+                    _ => return self.error(String::from("Can only assign to a variable.")),
+                };
+                let value = self.expression(0)?;
+                self.expect(";")?;
+                Ok(Statement::Assign(name, value, line))
+            } else {
+                self.expect(";")?;
+                Ok(Statement::Expr(expr))
+            }
+        }
+    }
+
+    /// Parse binary operators of the given precedence level and tighter
+    fn expression(&mut self, level: usize) -> Result<Expr, CompileError> {
+        if level == PRECEDENCE.len() {
+            return self.product();
+        }
+        let mut left = self.expression(level + 1)?;
+        'operators: loop {
+            for (symbol, op) in PRECEDENCE[level].iter() {
+                if self.accept(symbol) {
+                    let right = self.expression(level + 1)?;
+                    left = Expr::Binary(*op, Box::new(left), Box::new(right));
+                    continue 'operators;
+                }
+            }
+            return Ok(left);
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, CompileError> {
+        let mut left = self.unary()?;
+        while self.accept("*") {
+            let right = self.unary()?;
+            left = Expr::Binary(BinaryOp::Mul, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, CompileError> {
+        if self.accept("-") {
+            Ok(Expr::Unary(UnaryOp::Negate, Box::new(self.unary()?)))
+        } else if self.accept("!") {
+            Ok(Expr::Unary(UnaryOp::Not, Box::new(self.unary()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, CompileError> {
+        let line = self.line();
+        if let Some(Token::Number(number)) = self.peek() {
+            let number = *number;
+            self.position += 1;
+            return Ok(Expr::Number(number));
+        }
+        if self.accept("(") {
+            let expr = self.expression(0)?;
+            self.expect(")")?;
+            return Ok(expr);
+        }
+        let name = self.identifier()?;
+        if !self.accept("(") {
+            return Ok(Expr::Variable(name, line));
+        }
+        let mut args = Vec::new();
+        if !self.accept(")") {
+            loop {
+                args.push(self.expression(0)?);
+                if self.accept(")") {
+                    break;
+                }
+                self.expect(",")?;
+            }
+        }
+        Ok(Expr::Call(name, args, line))
+    }
+}
+
+/// Parse the source into its functions
+pub fn parse(source: &str) -> Result<Vec<Function>, CompileError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+    };
+    let mut functions = Vec::new();
+    while parser.peek().is_some() {
+        functions.push(parser.function()?);
+    }
+    Ok(functions)
+}
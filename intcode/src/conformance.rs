@@ -0,0 +1,698 @@
+use std::panic::{self, AssertUnwindSafe};
+
+/// Language features a test case relies on. Interpreters that do not support
+/// one of them skip the case.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Feature {
+    /// opcodes 3 and 4
+    Io,
+    /// more than two inputs or inputs that are requested after an output
+    StreamingIo,
+    /// immediate mode and relative mode parameters
+    ParameterModes,
+    /// opcodes 5 to 8
+    Conditionals,
+    /// opcode 9 and relative mode parameters
+    RelativeBase,
+    /// numbers below zero anywhere in program, inputs or results
+    NegativeNumbers,
+    /// numbers that do not fit into an i32
+    LargeNumbers,
+    /// reading or writing addresses past the end of the program
+    MemoryBeyondProgram,
+}
+
+/// What running a test case has to result in
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expected {
+    /// Program reaches opcode 99 having produced `outputs`. If `memory` is
+    /// given, the memory of the program has to match it afterwards.
+    Halts {
+        outputs: Vec<i64>,
+        memory: Option<Vec<i64>>,
+    },
+    /// Program is invalid and the interpreter has to panic
+    Panics,
+}
+
+pub struct Case {
+    pub name: String,
+    pub program: Vec<i64>,
+    pub inputs: Vec<i64>,
+    pub expected: Expected,
+    pub features: Vec<Feature>,
+}
+
+impl Case {
+    fn halts(name: &str, program: &[i64], inputs: &[i64], outputs: &[i64]) -> Case {
+        Case {
+            name: name.to_string(),
+            program: program.to_vec(),
+            inputs: inputs.to_vec(),
+            expected: Expected::Halts {
+                outputs: outputs.to_vec(),
+                memory: None,
+            },
+            features: Vec::new(),
+        }
+    }
+
+    fn ends_with_memory(name: &str, program: &[i64], memory: &[i64]) -> Case {
+        Case {
+            name: name.to_string(),
+            program: program.to_vec(),
+            inputs: Vec::new(),
+            expected: Expected::Halts {
+                outputs: Vec::new(),
+                memory: Some(memory.to_vec()),
+            },
+            features: Vec::new(),
+        }
+    }
+
+    fn panics(name: &str, program: &[i64], inputs: &[i64]) -> Case {
+        Case {
+            name: name.to_string(),
+            program: program.to_vec(),
+            inputs: inputs.to_vec(),
+            expected: Expected::Panics,
+            features: Vec::new(),
+        }
+    }
+
+    fn with(mut self, features: &[Feature]) -> Case {
+        self.features.extend_from_slice(features);
+        self
+    }
+
+    /// All features needed to run the case, the declared ones plus the ones
+    /// that follow from the numbers used
+    pub fn required_features(&self) -> Vec<Feature> {
+        let mut features = self.features.clone();
+        let mut numbers: Vec<i64> = self
+            .program
+            .iter()
+            .chain(self.inputs.iter())
+            .copied()
+            .collect();
+        if let Expected::Halts { outputs, memory } = &self.expected {
+            numbers.extend(outputs.iter());
+            if let Some(memory) = memory {
+                numbers.extend(memory.iter());
+            }
+        }
+        if numbers.iter().any(|&number| number < 0) {
+            features.push(Feature::NegativeNumbers);
+        }
+        if numbers
+            .iter()
+            .any(|&number| number > i32::MAX as i64 || number < i32::MIN as i64)
+        {
+            features.push(Feature::LargeNumbers);
+        }
+        if self.inputs.len() > 2 {
+            features.push(Feature::StreamingIo);
+        }
+        features
+    }
+}
+
+/// Result of running a program on an interpreter until it halts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub outputs: Vec<i64>,
+    /// memory of the interpreter, at least as long as the program
+    pub memory: Vec<i64>,
+}
+
+/// An intcode interpreter the suite can be run against
+pub trait Interpreter {
+    fn name(&self) -> &'static str;
+    fn supports(&self, feature: Feature) -> bool;
+    /// Run program with the inputs until opcode 99, but at most `max_steps`
+    /// instructions. None if it did not halt by then. Invalid programs
+    /// panic.
+    fn execute_within(&self, program: &[i64], inputs: &[i64], max_steps: usize) -> Option<Outcome>;
+
+    /// Run program with the inputs until opcode 99. Invalid programs panic.
+    fn execute(&self, program: &[i64], inputs: &[i64]) -> Outcome {
+        self.execute_within(program, inputs, usize::MAX)
+            .expect("Program did not halt.")
+    }
+}
+
+#[derive(Debug)]
+pub struct Failure {
+    pub case: String,
+    pub reason: String,
+}
+
+#[derive(Debug)]
+pub struct Report {
+    pub interpreter: &'static str,
+    pub passed: Vec<String>,
+    pub skipped: Vec<String>,
+    pub failures: Vec<Failure>,
+}
+
+/// Run a single case and describe what went wrong, if anything
+pub fn check_case(interpreter: &dyn Interpreter, case: &Case) -> Result<(), String> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        interpreter.execute(&case.program, &case.inputs)
+    }));
+    match (&case.expected, result) {
+        (Expected::Panics, Err(_)) => Ok(()),
+        (Expected::Panics, Ok(outcome)) => Err(format!(
+            "expected a panic, but program halted with outputs {:?}",
+            outcome.outputs
+        )),
+        (Expected::Halts { .. }, Err(_)) => Err(String::from("interpreter panicked")),
+        (Expected::Halts { outputs, memory }, Ok(outcome)) => {
+            if &outcome.outputs != outputs {
+                return Err(format!(
+                    "expected outputs {:?}, got {:?}",
+                    outputs, outcome.outputs
+                ));
+            }
+            if let Some(memory) = memory {
+                if outcome.memory.len() < memory.len()
+                    || &outcome.memory[..memory.len()] != memory.as_slice()
+                {
+                    return Err(format!(
+                        "expected memory {:?}, got {:?}",
+                        memory, outcome.memory
+                    ));
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Run every case the interpreter supports
+pub fn run_suite(interpreter: &dyn Interpreter) -> Report {
+    let mut report = Report {
+        interpreter: interpreter.name(),
+        passed: Vec::new(),
+        skipped: Vec::new(),
+        failures: Vec::new(),
+    };
+    for case in cases() {
+        if !case
+            .required_features()
+            .iter()
+            .all(|&feature| interpreter.supports(feature))
+        {
+            report.skipped.push(case.name);
+            continue;
+        }
+        match check_case(interpreter, &case) {
+            Ok(()) => report.passed.push(case.name),
+            Err(reason) => report.failures.push(Failure {
+                case: case.name,
+                reason,
+            }),
+        }
+    }
+    report
+}
+
+/// The whole conformance suite
+pub fn cases() -> Vec<Case> {
+    let mut cases = Vec::new();
+    cases.extend(day2_examples());
+    cases.extend(day5_examples());
+    cases.extend(day7_examples());
+    cases.extend(day9_examples());
+    cases.extend(arithmetic_cases());
+    cases.extend(io_cases());
+    cases.extend(jump_cases());
+    cases.extend(relative_base_cases());
+    cases.extend(error_cases());
+    cases
+}
+
+fn day2_examples() -> Vec<Case> {
+    vec![
+        Case::ends_with_memory(
+            "day2 example",
+            &[1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50],
+            &[3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50],
+        ),
+        Case::ends_with_memory("day2 add", &[1, 0, 0, 0, 99], &[2, 0, 0, 0, 99]),
+        Case::ends_with_memory("day2 multiply", &[2, 3, 0, 3, 99], &[2, 3, 0, 6, 99]),
+        Case::ends_with_memory(
+            "day2 multiply past halt",
+            &[2, 4, 4, 5, 99, 0],
+            &[2, 4, 4, 5, 99, 9801],
+        ),
+        Case::ends_with_memory(
+            "day2 self modifying",
+            &[1, 1, 1, 4, 99, 5, 6, 0, 99],
+            &[30, 1, 1, 4, 2, 5, 6, 0, 99],
+        ),
+    ]
+}
+
+fn day5_examples() -> Vec<Case> {
+    use Feature::*;
+    let equal_8_position = [3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+    let less_8_position = [3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8];
+    let equal_8_immediate = [3, 3, 1108, -1, 8, 3, 4, 3, 99];
+    let less_8_immediate = [3, 3, 1107, -1, 8, 3, 4, 3, 99];
+    let jump_position = [3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9];
+    let jump_immediate = [3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1];
+    let compare_8 = [
+        3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0,
+        1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20,
+        1105, 1, 46, 98, 99,
+    ];
+
+    let mut cases = vec![
+        Case::halts("day5 echo", &[3, 0, 4, 0, 99], &[37], &[37]).with(&[Io]),
+        Case::ends_with_memory(
+            "day5 immediate multiply",
+            &[1002, 4, 3, 4, 33],
+            &[1002, 4, 3, 4, 99],
+        )
+        .with(&[ParameterModes]),
+        Case::ends_with_memory(
+            "day5 negative add",
+            &[1101, 100, -1, 4, 0],
+            &[1101, 100, -1, 4, 99],
+        )
+        .with(&[ParameterModes]),
+    ];
+    for &(input, equal, less, compared) in [(7, 0, 1, 999), (8, 1, 0, 1000), (9, 0, 0, 1001)].iter()
+    {
+        let features = [Io, Conditionals, ParameterModes];
+        cases.push(
+            Case::halts(
+                &format!("day5 equal 8 position ({})", input),
+                &equal_8_position,
+                &[input],
+                &[equal],
+            )
+            .with(&features),
+        );
+        cases.push(
+            Case::halts(
+                &format!("day5 less 8 position ({})", input),
+                &less_8_position,
+                &[input],
+                &[less],
+            )
+            .with(&features),
+        );
+        cases.push(
+            Case::halts(
+                &format!("day5 equal 8 immediate ({})", input),
+                &equal_8_immediate,
+                &[input],
+                &[equal],
+            )
+            .with(&features),
+        );
+        cases.push(
+            Case::halts(
+                &format!("day5 less 8 immediate ({})", input),
+                &less_8_immediate,
+                &[input],
+                &[less],
+            )
+            .with(&features),
+        );
+        cases.push(
+            Case::halts(
+                &format!("day5 compare 8 ({})", input),
+                &compare_8,
+                &[input],
+                &[compared],
+            )
+            .with(&features),
+        );
+    }
+    for &(input, output) in [(0, 0), (5, 1)].iter() {
+        let features = [Io, Conditionals, ParameterModes];
+        cases.push(
+            Case::halts(
+                &format!("day5 jump position ({})", input),
+                &jump_position,
+                &[input],
+                &[output],
+            )
+            .with(&features),
+        );
+        cases.push(
+            Case::halts(
+                &format!("day5 jump immediate ({})", input),
+                &jump_immediate,
+                &[input],
+                &[output],
+            )
+            .with(&features),
+        );
+    }
+    cases
+}
+
+fn day7_examples() -> Vec<Case> {
+    use Feature::*;
+    // single amplifier of the first example, outputs signal * 10 + phase
+    let amplifier = [
+        3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+    ];
+    vec![
+        Case::halts("day7 amplifier", &amplifier, &[4, 0], &[4]).with(&[Io, ParameterModes]),
+        Case::halts("day7 amplifier chained", &amplifier, &[0, 4321], &[43210])
+            .with(&[Io, ParameterModes]),
+    ]
+}
+
+fn day9_examples() -> Vec<Case> {
+    use Feature::*;
+    let quine = [
+        109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+    ];
+    vec![
+        Case::halts("day9 quine", &quine, &[], &quine).with(&[
+            Io,
+            ParameterModes,
+            Conditionals,
+            RelativeBase,
+            MemoryBeyondProgram,
+        ]),
+        Case::halts(
+            "day9 16 digit number",
+            &[1102, 34_915_192, 34_915_192, 7, 4, 7, 99, 0],
+            &[],
+            &[1_219_070_632_396_864],
+        )
+        .with(&[Io, ParameterModes]),
+        Case::halts(
+            "day9 large number",
+            &[104, 1_125_899_906_842_624, 99],
+            &[],
+            &[1_125_899_906_842_624],
+        )
+        .with(&[Io, ParameterModes]),
+    ]
+}
+
+/// Relative base used by the generated cases, every program using relative
+/// mode starts by setting it
+const RELATIVE_BASE: i64 = 4;
+
+/// Build the parameter that refers to the value at address in the given mode
+fn parameter(mode: u8, address: usize, value: i64) -> i64 {
+    match mode {
+        0 => address as i64,
+        1 => value,
+        _ => address as i64 - RELATIVE_BASE,
+    }
+}
+
+/// Features the parameter modes of one instruction rely on
+fn mode_features(modes: &[u8]) -> Vec<Feature> {
+    let mut features = Vec::new();
+    if modes.iter().any(|&mode| mode != 0) {
+        features.push(Feature::ParameterModes);
+    }
+    if modes.contains(&2) {
+        features.push(Feature::RelativeBase);
+    }
+    features
+}
+
+/// Every instruction that takes two values and writes a result, in every
+/// combination of modes for the read parameters and the write parameter
+fn arithmetic_cases() -> Vec<Case> {
+    let operations: [(i64, &str, i64, i64, i64); 6] = [
+        (1, "add", 7, 5, 12),
+        (2, "multiply", 7, 5, 35),
+        (7, "less than (true)", 3, 7, 1),
+        (7, "less than (false)", 7, 3, 0),
+        (8, "equals (true)", 7, 7, 1),
+        (8, "equals (false)", 7, 5, 0),
+    ];
+    let mut cases = Vec::new();
+    for &(opcode, name, a, b, result) in operations.iter() {
+        for &m1 in [0, 1, 2].iter() {
+            for &m2 in [0, 1, 2].iter() {
+                for &m3 in [0, 2].iter() {
+                    let modes = [m1, m2, m3];
+                    let start = if modes.contains(&2) { 2 } else { 0 };
+                    // instruction, halt, followed by the two values and the result cell,
+                    // which starts out with a value no operation results in
+                    let address_a = start + 5;
+                    let address_b = start + 6;
+                    let address_result = start + 7;
+                    let mut program = Vec::new();
+                    if start == 2 {
+                        program.extend_from_slice(&[109, RELATIVE_BASE]);
+                    }
+                    program.extend_from_slice(&[
+                        opcode + 100 * m1 as i64 + 1000 * m2 as i64 + 10000 * m3 as i64,
+                        parameter(m1, address_a, a),
+                        parameter(m2, address_b, b),
+                        parameter(m3, address_result, 0),
+                        99,
+                        a,
+                        b,
+                        9,
+                    ]);
+                    let mut memory = program.clone();
+                    memory[address_result] = result;
+
+                    let mut features = mode_features(&modes);
+                    if opcode > 2 {
+                        features.push(Feature::Conditionals);
+                    }
+                    cases.push(
+                        Case::ends_with_memory(
+                            &format!("{} in modes {:?}", name, modes),
+                            &program,
+                            &memory,
+                        )
+                        .with(&features),
+                    );
+                }
+            }
+        }
+    }
+    cases
+}
+
+/// Input in the writing modes and output in every mode
+fn io_cases() -> Vec<Case> {
+    use Feature::*;
+    vec![
+        Case::halts("input in mode 0", &[3, 5, 4, 5, 99, 0], &[11], &[11]).with(&[Io]),
+        Case::halts(
+            "input in mode 2",
+            &[109, 3, 203, 4, 4, 7, 99, 0],
+            &[12],
+            &[12],
+        )
+        .with(&[Io, ParameterModes, RelativeBase]),
+        Case::halts("output in mode 0", &[4, 3, 99, 13], &[], &[13]).with(&[Io]),
+        Case::halts("output in mode 1", &[104, 14, 99], &[], &[14]).with(&[Io, ParameterModes]),
+        Case::halts("output in mode 2", &[109, 2, 204, 3, 99, 15], &[], &[15]).with(&[
+            Io,
+            ParameterModes,
+            RelativeBase,
+        ]),
+        Case::halts(
+            "several inputs",
+            &[3, 9, 3, 10, 3, 11, 4, 11, 99, 0, 0, 0],
+            &[1, 2, 3],
+            &[3],
+        )
+        .with(&[Io]),
+        Case::halts(
+            "input after output",
+            &[3, 9, 4, 9, 3, 9, 4, 9, 99, 0],
+            &[1, 2],
+            &[1, 2],
+        )
+        .with(&[Io, StreamingIo]),
+    ]
+}
+
+/// Both jump instructions in every combination of modes, once jumping and
+/// once falling through
+fn jump_cases() -> Vec<Case> {
+    let mut cases = Vec::new();
+    for &opcode in [5, 6].iter() {
+        for &m1 in [0, 1, 2].iter() {
+            for &m2 in [0, 1, 2].iter() {
+                for &condition in [0, 7].iter() {
+                    let modes = [m1, m2];
+                    let start = if modes.contains(&2) { 2 } else { 0 };
+                    // jump, fall through branch, target branch, followed by
+                    // condition, target address and marker cell
+                    let target = start + 8;
+                    let address_condition = start + 13;
+                    let address_target = start + 14;
+                    let address_marker = start + 15;
+                    let mut program = Vec::new();
+                    if start == 2 {
+                        program.extend_from_slice(&[109, RELATIVE_BASE]);
+                    }
+                    program.extend_from_slice(&[
+                        opcode + 100 * m1 as i64 + 1000 * m2 as i64,
+                        parameter(m1, address_condition, condition),
+                        parameter(m2, address_target, target as i64),
+                        1101,
+                        0,
+                        0,
+                        address_marker as i64,
+                        99,
+                        1101,
+                        0,
+                        1,
+                        address_marker as i64,
+                        99,
+                        condition,
+                        target as i64,
+                        2,
+                    ]);
+                    let jumps = (opcode == 5) == (condition != 0);
+                    let mut memory = program.clone();
+                    memory[address_marker] = if jumps { 1 } else { 0 };
+
+                    let name = if opcode == 5 {
+                        "jump if true"
+                    } else {
+                        "jump if false"
+                    };
+                    let mut features = mode_features(&modes);
+                    features.push(Feature::ParameterModes);
+                    features.push(Feature::Conditionals);
+                    cases.push(
+                        Case::ends_with_memory(
+                            &format!("{} in modes {:?} on {}", name, modes, condition),
+                            &program,
+                            &memory,
+                        )
+                        .with(&features),
+                    );
+                }
+            }
+        }
+    }
+    cases
+}
+
+fn relative_base_cases() -> Vec<Case> {
+    use Feature::*;
+    let features = [Io, ParameterModes, RelativeBase];
+    vec![
+        Case::halts(
+            "relative base offset in mode 0",
+            &[9, 7, 204, -1, 99, 0, 0, 5],
+            &[],
+            &[99],
+        )
+        .with(&features),
+        Case::halts(
+            "relative base offset in mode 1",
+            &[109, 6, 204, 1, 99, 0, 0, 77],
+            &[],
+            &[77],
+        )
+        .with(&features),
+        Case::halts(
+            "relative base offset in mode 2",
+            &[109, 3, 209, 4, 204, 2, 99, 4, 0, 55],
+            &[],
+            &[55],
+        )
+        .with(&features),
+        Case::halts(
+            "negative relative parameter",
+            &[109, 10, 204, -6, 99],
+            &[],
+            &[99],
+        )
+        .with(&features),
+        Case::halts(
+            "negative relative base",
+            &[109, -1, 204, 3, 99],
+            &[],
+            &[204],
+        )
+        .with(&features),
+        Case::halts(
+            "relative base adds up",
+            &[109, 5, 109, -2, 204, 0, 99],
+            &[],
+            &[-2],
+        )
+        .with(&features),
+        Case::halts(
+            "relative write",
+            &[109, 9, 21101, 20, 22, 0, 204, 0, 99, 0],
+            &[],
+            &[42],
+        )
+        .with(&features),
+        Case::halts(
+            "relative base kept while waiting for input",
+            &[109, 6, 3, 13, 204, 7, 99, 0, 0, 0, 0, 0, 0, 0],
+            &[31],
+            &[31],
+        )
+        .with(&features),
+        Case::halts("read unused memory", &[4, 1000, 99], &[], &[0])
+            .with(&[Io, MemoryBeyondProgram]),
+        Case::halts(
+            "write beyond program",
+            &[109, 1000, 21101, 3, 4, 0, 204, 0, 99],
+            &[],
+            &[7],
+        )
+        .with(&[Io, ParameterModes, RelativeBase, MemoryBeyondProgram]),
+    ]
+}
+
+fn error_cases() -> Vec<Case> {
+    use Feature::*;
+    vec![
+        Case::panics("unknown opcode", &[42, 0, 0, 0, 99], &[]),
+        Case::panics(
+            "add writing in immediate mode",
+            &[11101, 1, 1, 5, 99, 0],
+            &[],
+        )
+        .with(&[ParameterModes]),
+        Case::panics(
+            "multiply writing in immediate mode",
+            &[11102, 1, 1, 5, 99, 0],
+            &[],
+        )
+        .with(&[ParameterModes]),
+        Case::panics(
+            "less than writing in immediate mode",
+            &[11107, 1, 2, 5, 99, 0],
+            &[],
+        )
+        .with(&[ParameterModes, Conditionals]),
+        Case::panics(
+            "equals writing in immediate mode",
+            &[11108, 1, 1, 5, 99, 0],
+            &[],
+        )
+        .with(&[ParameterModes, Conditionals]),
+        Case::panics("input in immediate mode", &[103, 3, 99, 0], &[1]).with(&[Io, ParameterModes]),
+        Case::panics("unknown parameter mode", &[301, 0, 0, 0, 99], &[]).with(&[ParameterModes]),
+        Case::panics("read negative address", &[1, -1, 0, 0, 99], &[]),
+        Case::panics("jump to negative address", &[1105, 1, -1, 99], &[])
+            .with(&[ParameterModes, Conditionals]),
+        Case::panics("relative read below zero", &[109, -5, 204, 2, 99], &[]).with(&[
+            Io,
+            ParameterModes,
+            RelativeBase,
+        ]),
+    ]
+}
@@ -0,0 +1,392 @@
+// Remote debugging of an IntcodeComputer over a local socket. The protocol is
+// line based, every command is answered by a line `ok [RESULT]` or
+// `error MESSAGE`:
+//
+//     read ADDRESS [COUNT]     ok VALUE...
+//     write ADDRESS VALUE...   ok
+//     break ADDRESS            ok
+//     clear ADDRESS            ok
+//     breakpoints              ok ADDRESS...
+//     input VALUE...           ok       (queued until the program reads them)
+//     registers                ok ip IP rb RELATIVE_BASE
+//     step [COUNT]             ok
+//     continue [LIMIT]         ok       (at most 10000000 instructions
+//                                        unless a LIMIT is given)
+//     quit                     ok       (closes the connection)
+//
+// While executing, step and continue report what happens before their reply
+// in lines `event output VALUE`, followed by the reason the computer stopped:
+// `event stopped breakpoint IP`, `event stopped step IP`,
+// `event stopped input IP`, `event halted` or `event crashed MESSAGE`.
+
+use crate::computer::IntcodeComputer;
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(unix)]
+use std::path::Path;
+use std::str::FromStr;
+
+/// Something that happened while the debugged computer was executing
+#[derive(Debug, Clone, PartialEq)]
+pub enum StopEvent {
+    Output(i64),
+    Breakpoint(usize),
+    /// the requested number of instructions was executed
+    Step(usize),
+    /// program waits for input and none is queued
+    InputNeeded(usize),
+    Halted,
+    Crashed(String),
+}
+
+impl fmt::Display for StopEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StopEvent::Output(value) => write!(f, "output {}", value),
+            StopEvent::Breakpoint(ip) => write!(f, "stopped breakpoint {}", ip),
+            StopEvent::Step(ip) => write!(f, "stopped step {}", ip),
+            StopEvent::InputNeeded(ip) => write!(f, "stopped input {}", ip),
+            StopEvent::Halted => write!(f, "halted"),
+            StopEvent::Crashed(message) => write!(f, "crashed {}", message),
+        }
+    }
+}
+
+impl FromStr for StopEvent {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<StopEvent, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let number = |i: usize| -> Result<i64, String> {
+            words
+                .get(i)
+                .and_then(|word| word.parse().ok())
+                .ok_or_else(|| format!("Invalid event: {}", line))
+        };
+        match words.as_slice() {
+            ["output", _] => Ok(StopEvent::Output(number(1)?)),
+            ["stopped", "breakpoint", _] => Ok(StopEvent::Breakpoint(number(2)? as usize)),
+            ["stopped", "step", _] => Ok(StopEvent::Step(number(2)? as usize)),
+            ["stopped", "input", _] => Ok(StopEvent::InputNeeded(number(2)? as usize)),
+            ["halted"] => Ok(StopEvent::Halted),
+            ["crashed", ..] => Ok(StopEvent::Crashed(words[1..].join(" "))),
+            _ => Err(format!("Invalid event: {}", line)),
+        }
+    }
+}
+
+/// Instructions a continue without limit executes at most, so a program that
+/// never halts does not block the server
+pub const CONTINUE_LIMIT: usize = 10_000_000;
+
+/// Serves one debugged computer to the clients connecting to it
+pub struct DebugServer {
+    computer: IntcodeComputer,
+    breakpoints: BTreeSet<usize>,
+    inputs: VecDeque<i64>,
+    crashed: Option<String>,
+    continue_limit: usize,
+}
+
+fn parse_number<T: FromStr>(word: Option<&str>) -> Result<T, String> {
+    let word = word.ok_or_else(|| String::from("Missing argument."))?;
+    word.parse()
+        .map_err(|_| format!("Invalid number {}.", word))
+}
+
+impl DebugServer {
+    pub fn new(computer: IntcodeComputer) -> DebugServer {
+        DebugServer {
+            computer,
+            breakpoints: BTreeSet::new(),
+            inputs: VecDeque::new(),
+            crashed: None,
+            continue_limit: CONTINUE_LIMIT,
+        }
+    }
+
+    /// Change how many instructions a continue without limit executes
+    pub fn set_continue_limit(&mut self, limit: usize) {
+        self.continue_limit = limit;
+    }
+
+    pub fn computer(&self) -> &IntcodeComputer {
+        &self.computer
+    }
+
+    fn address(&self, word: Option<&str>) -> Result<usize, String> {
+        let address = parse_number(word)?;
+        if address >= self.computer.memory_size() {
+            return Err(format!("Address {} is out of memory.", address));
+        }
+        Ok(address)
+    }
+
+    /// Execute a single command line, the events that occurred are pushed to
+    /// events. Returns the result of the reply line.
+    pub fn handle(&mut self, line: &str, events: &mut Vec<StopEvent>) -> Result<String, String> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let result = match command {
+            "read" => {
+                let address = self.address(words.next())?;
+                let count: usize = match words.next() {
+                    Some(word) => parse_number(Some(word))?,
+                    None => 1,
+                };
+                let end = address.saturating_add(count);
+                if end > self.computer.memory_size() {
+                    return Err(format!("Address {} is out of memory.", end - 1));
+                }
+                let values: Vec<String> = (address..end)
+                    .map(|address| self.computer.read_memory(address).to_string())
+                    .collect();
+                values.join(" ")
+            }
+            "write" => {
+                let address = self.address(words.next())?;
+                let values = words
+                    .map(|word| parse_number(Some(word)))
+                    .collect::<Result<Vec<i64>, String>>()?;
+                if values.is_empty() {
+                    return Err(String::from("Missing argument."));
+                }
+                if address + values.len() > self.computer.memory_size() {
+                    return Err(String::from("Values do not fit into memory."));
+                }
+                for (i, value) in values.into_iter().enumerate() {
+                    self.computer.write_memory(address + i, value);
+                }
+                String::new()
+            }
+            "break" => {
+                let address = self.address(words.next())?;
+                self.breakpoints.insert(address);
+                String::new()
+            }
+            "clear" => {
+                let address = self.address(words.next())?;
+                if !self.breakpoints.remove(&address) {
+                    return Err(format!("No breakpoint at {}.", address));
+                }
+                String::new()
+            }
+            "breakpoints" => {
+                let addresses: Vec<String> = self
+                    .breakpoints
+                    .iter()
+                    .map(|address| address.to_string())
+                    .collect();
+                addresses.join(" ")
+            }
+            "input" => {
+                let values = words
+                    .map(|word| parse_number(Some(word)))
+                    .collect::<Result<Vec<i64>, String>>()?;
+                self.inputs.extend(values);
+                String::new()
+            }
+            "registers" => format!(
+                "ip {} rb {}",
+                self.computer.instruction_pointer(),
+                self.computer.relative_base()
+            ),
+            "step" => {
+                let count = match words.next() {
+                    Some(word) => parse_number(Some(word))?,
+                    None => 1,
+                };
+                self.execute(count, events)?;
+                String::new()
+            }
+            "continue" => {
+                let limit = match words.next() {
+                    Some(word) => parse_number(Some(word))?,
+                    None => self.continue_limit,
+                };
+                self.execute(limit, events)?;
+                String::new()
+            }
+            "quit" => String::new(),
+            _ => return Err(format!("Unknown command {}.", command)),
+        };
+        Ok(result)
+    }
+
+    /// Execute instructions until the limit is reached, a breakpoint is hit,
+    /// input is missing or the program ends
+    fn execute(&mut self, limit: usize, events: &mut Vec<StopEvent>) -> Result<(), String> {
+        if let Some(message) = &self.crashed {
+            return Err(format!("Computer crashed: {}", message));
+        }
+        let mut executed = 0;
+        loop {
+            let ip = self.computer.instruction_pointer();
+            if self.computer.terminated {
+                events.push(StopEvent::Halted);
+                return Ok(());
+            }
+            // a breakpoint does not stop continuing from itself
+            if executed > 0 && self.breakpoints.contains(&ip) {
+                events.push(StopEvent::Breakpoint(ip));
+                return Ok(());
+            }
+            if executed == limit {
+                events.push(StopEvent::Step(ip));
+                return Ok(());
+            }
+            let computer = &mut self.computer;
+            let continues = match panic::catch_unwind(AssertUnwindSafe(|| computer.step())) {
+                Ok(continues) => continues,
+                Err(payload) => {
+                    let message = payload
+                        .downcast_ref::<String>()
+                        .cloned()
+                        .or_else(|| {
+                            payload
+                                .downcast_ref::<&str>()
+                                .map(|message| message.to_string())
+                        })
+                        .unwrap_or_else(|| String::from("unknown error"));
+                    events.push(StopEvent::Crashed(message.clone()));
+                    self.crashed = Some(message);
+                    return Ok(());
+                }
+            };
+            if !continues && self.computer.requesting_input {
+                match self.inputs.pop_front() {
+                    // the input instruction is executed by the next step
+                    Some(input) => self.computer.set_memory_input(input),
+                    None => {
+                        events.push(StopEvent::InputNeeded(ip));
+                        return Ok(());
+                    }
+                }
+                continue;
+            }
+            executed += 1;
+            if !continues && !self.computer.terminated {
+                events.push(StopEvent::Output(self.computer.memory_output));
+            }
+        }
+    }
+
+    /// Answer the commands read from the reader until the client quits or
+    /// disconnects
+    pub fn serve<R: BufRead, W: Write>(&mut self, reader: R, mut writer: W) -> io::Result<()> {
+        for line in reader.lines() {
+            let line = line?;
+            let mut events = Vec::new();
+            let result = self.handle(&line, &mut events);
+            for event in events.iter() {
+                writeln!(writer, "event {}", event)?;
+            }
+            match result {
+                Ok(reply) if reply.is_empty() => writeln!(writer, "ok")?,
+                Ok(reply) => writeln!(writer, "ok {}", reply)?,
+                Err(message) => writeln!(writer, "error {}", message)?,
+            }
+            writer.flush()?;
+            if line.trim() == "quit" {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Accept a single client on the TCP listener and serve it
+    pub fn serve_tcp(&mut self, listener: &TcpListener) -> io::Result<()> {
+        let (stream, _) = listener.accept()?;
+        self.serve(BufReader::new(stream.try_clone()?), stream)
+    }
+
+    /// Accept a single client on the Unix domain socket and serve it
+    #[cfg(unix)]
+    pub fn serve_unix(&mut self, listener: &UnixListener) -> io::Result<()> {
+        let (stream, _) = listener.accept()?;
+        self.serve(BufReader::new(stream.try_clone()?), stream)
+    }
+}
+
+/// Reply of the server to a command
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reply {
+    pub events: Vec<StopEvent>,
+    pub result: Result<String, String>,
+}
+
+/// Client side of the protocol
+pub struct DebugClient<R: BufRead, W: Write> {
+    reader: R,
+    writer: W,
+}
+
+impl DebugClient<BufReader<TcpStream>, TcpStream> {
+    pub fn connect_tcp<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        Ok(DebugClient::new(
+            BufReader::new(stream.try_clone()?),
+            stream,
+        ))
+    }
+}
+
+#[cfg(unix)]
+impl DebugClient<BufReader<UnixStream>, UnixStream> {
+    pub fn connect_unix<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let stream = UnixStream::connect(path)?;
+        Ok(DebugClient::new(
+            BufReader::new(stream.try_clone()?),
+            stream,
+        ))
+    }
+}
+
+impl<R: BufRead, W: Write> DebugClient<R, W> {
+    pub fn new(reader: R, writer: W) -> DebugClient<R, W> {
+        DebugClient { reader, writer }
+    }
+
+    /// Send a command and wait for its reply
+    pub fn command(&mut self, command: &str) -> io::Result<Reply> {
+        writeln!(self.writer, "{}", command)?;
+        self.writer.flush()?;
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut events = Vec::new();
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Server closed the connection.",
+                ));
+            }
+            let line = line.trim_end();
+            if let Some(event) = line.strip_prefix("event ") {
+                events.push(event.parse().map_err(invalid)?);
+            } else if line == "ok" {
+                return Ok(Reply {
+                    events,
+                    result: Ok(String::new()),
+                });
+            } else if let Some(reply) = line.strip_prefix("ok ") {
+                return Ok(Reply {
+                    events,
+                    result: Ok(reply.to_string()),
+                });
+            } else if let Some(message) = line.strip_prefix("error ") {
+                return Ok(Reply {
+                    events,
+                    result: Err(message.to_string()),
+                });
+            } else {
+                return Err(invalid(format!("Unexpected line: {}", line)));
+            }
+        }
+    }
+}
@@ -0,0 +1,1101 @@
+// Decompiler of intcode programs to structured pseudo-code. Code is found by
+// following the control flow from address 0, everything never reached is
+// considered data. The recognised idioms are
+//
+// - calls: the return address (the address behind the jump) is written to
+//   a relative cell right before an unconditional jump. Arguments are the
+//   relative cells written behind it. The frame is set up either by the
+//   callee (`rb += N` on entry and `rb -= N` before returning) or by the
+//   caller around the jump.
+// - returns: unconditional jumps to the address in rel(0)
+// - array accesses: an instruction writing into a parameter of the next
+//   instruction, shown as `mem[address]`
+// - conditionals and loops from forward and backward jumps
+//
+// Control flow that does not fit these is shown as labels and gotos.
+
+use crate::computer::decode;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Relative cells further out than this are not taken as call arguments
+const MAX_ARGUMENTS: i64 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Add,
+    Mul,
+    Less,
+    Equal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Const(i64),
+    /// memory cell at a fixed address
+    Global(i64),
+    /// memory cell relative to the relative base
+    Slot(i64),
+    /// memory cell at a computed address
+    Load(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone)]
+struct Instruction {
+    address: usize,
+    opcode: u8,
+    /// mode and raw value of every parameter
+    params: Vec<(u8, i64)>,
+}
+
+impl Instruction {
+    fn next(&self) -> usize {
+        self.address + 1 + self.params.len()
+    }
+
+    /// Jump target, if it is known without running the program
+    fn static_target(&self) -> Option<usize> {
+        match self.params[1] {
+            (1, target) if target >= 0 => Some(target as usize),
+            _ => None,
+        }
+    }
+
+    /// For jumps: Some(true) if always taken, Some(false) if never taken
+    fn constant_jump(&self) -> Option<bool> {
+        match self.params[0] {
+            (1, condition) => Some((condition != 0) == (self.opcode == 5)),
+            _ => None,
+        }
+    }
+
+    /// The value written by an add or multiply, if it is a constant
+    fn constant_result(&self) -> Option<i64> {
+        match (self.opcode, self.params[0], self.params[1]) {
+            (1, (1, a), (1, b)) => a.checked_add(b),
+            (2, (1, a), (1, b)) => a.checked_mul(b),
+            _ => None,
+        }
+    }
+}
+
+/// Number of parameters an opcode takes, None for unknown opcodes
+pub fn parameter_count(opcode: u8) -> Option<usize> {
+    match opcode {
+        1 | 2 | 7 | 8 => Some(3),
+        5 | 6 => Some(2),
+        3 | 4 | 9 => Some(1),
+        99 => Some(0),
+        _ => None,
+    }
+}
+
+fn decode_at(memory: &[i64], address: usize) -> Option<Instruction> {
+    let (opcode, pmodes) = decode(*memory.get(address)?)?;
+    let count = parameter_count(opcode)?;
+    if address + count >= memory.len() || pmodes.iter().any(|&mode| mode > 2) {
+        return None;
+    }
+    let params = (0..count)
+        .map(|i| (pmodes[i], memory[address + 1 + i]))
+        .collect();
+    Some(Instruction {
+        address,
+        opcode,
+        params,
+    })
+}
+
+#[derive(Debug, Clone)]
+enum Stmt {
+    Assign(Expr, Expr),
+    Input(Expr),
+    Output(Expr),
+    AdjustBase(Expr),
+    /// target and arguments, missing arguments are None
+    Call(Expr, Vec<Option<Expr>>),
+    Return,
+    Halt,
+    Goto(usize),
+    /// jump to target if condition is non zero (true) or zero (false)
+    Branch(Expr, bool, usize),
+    IndirectGoto(Option<(Expr, bool)>, Expr),
+    Invalid(i64),
+    /// jump that is never taken
+    Nop,
+}
+
+impl Stmt {
+    fn is_control(&self) -> bool {
+        matches!(
+            self,
+            Stmt::Return
+                | Stmt::Halt
+                | Stmt::Goto(_)
+                | Stmt::Branch(..)
+                | Stmt::IndirectGoto(..)
+                | Stmt::Invalid(_)
+        )
+    }
+
+    fn jump_target(&self) -> Option<usize> {
+        match self {
+            Stmt::Goto(target) | Stmt::Branch(_, _, target) => Some(*target),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Item {
+    address: usize,
+    stmt: Stmt,
+    /// address executed next if the statement does not jump away
+    fallthrough: Option<usize>,
+    /// inserted goto, does not correspond to code at its address
+    synthetic: bool,
+}
+
+/// A function: its entry and the addresses of all instructions reached
+/// from it
+struct Function {
+    entry: usize,
+    addresses: BTreeSet<usize>,
+}
+
+struct Program<'a> {
+    memory: &'a [i64],
+    instructions: BTreeMap<usize, Option<Instruction>>,
+    /// addresses of the jumps that are calls
+    calls: BTreeSet<usize>,
+}
+
+impl<'a> Program<'a> {
+    fn instruction(&mut self, address: usize) -> Option<Instruction> {
+        let memory = self.memory;
+        self.instructions
+            .entry(address)
+            .or_insert_with(|| decode_at(memory, address))
+            .clone()
+    }
+
+    /// Follow the control flow from the entry, calls are stepped over.
+    /// Returns the function and the entries of the functions it calls.
+    fn explore(&mut self, entry: usize) -> (Function, Vec<usize>) {
+        let mut addresses = BTreeSet::new();
+        let mut callees = Vec::new();
+        let mut starts = vec![entry];
+        while let Some(start) = starts.pop() {
+            let mut address = start;
+            // instructions of the straight run leading to the current one
+            let mut run: Vec<Instruction> = Vec::new();
+            while addresses.insert(address) {
+                let instruction = match self.instruction(address) {
+                    Some(instruction) => instruction,
+                    None => break,
+                };
+                let next = instruction.next();
+                match instruction.opcode {
+                    99 => break,
+                    5 | 6 => match instruction.constant_jump() {
+                        Some(false) => (),
+                        Some(true) => {
+                            let stores_return = run.iter().rev().take(8).any(|stored| {
+                                stored.params.get(2).map(|param| param.0) == Some(2)
+                                    && stored.constant_result() == Some(next as i64)
+                            });
+                            if stores_return {
+                                self.calls.insert(address);
+                                callees.extend(instruction.static_target());
+                                // stores in front of the call belong to it
+                                run.clear();
+                                address = next;
+                                continue;
+                            } else {
+                                starts.extend(instruction.static_target());
+                                break;
+                            }
+                        }
+                        None => starts.extend(instruction.static_target()),
+                    },
+                    _ => (),
+                }
+                run.push(instruction);
+                address = next;
+            }
+        }
+        (Function { entry, addresses }, callees)
+    }
+}
+
+/// Turns the instructions of a function into statements
+struct Lowering<'p, 'a> {
+    program: &'p Program<'a>,
+    /// parameter cells computed by the instruction in front of them
+    patches: HashMap<usize, Expr>,
+}
+
+impl<'p, 'a> Lowering<'p, 'a> {
+    fn read(&self, instruction: &Instruction, i: usize) -> Expr {
+        let (mode, value) = instruction.params[i];
+        let cell = instruction.address + 1 + i;
+        match (mode, self.patches.get(&cell)) {
+            (0, Some(address)) => Expr::Load(Box::new(address.clone())),
+            (1, Some(value)) => value.clone(),
+            (0, None) => Expr::Global(value),
+            (1, None) => Expr::Const(value),
+            _ => Expr::Slot(value),
+        }
+    }
+
+    /// Location written by the parameter
+    fn place(&self, instruction: &Instruction, i: usize) -> Expr {
+        let (mode, value) = instruction.params[i];
+        let cell = instruction.address + 1 + i;
+        match (mode, self.patches.get(&cell)) {
+            (0, Some(address)) => Expr::Load(Box::new(address.clone())),
+            (0, None) => Expr::Global(value),
+            _ => Expr::Slot(value),
+        }
+    }
+
+    fn value(&self, instruction: &Instruction) -> Option<Expr> {
+        let op = match instruction.opcode {
+            1 => BinaryOp::Add,
+            2 => BinaryOp::Mul,
+            7 => BinaryOp::Less,
+            8 => BinaryOp::Equal,
+            _ => return None,
+        };
+        Some(Expr::Binary(
+            op,
+            Box::new(self.read(instruction, 0)),
+            Box::new(self.read(instruction, 1)),
+        ))
+    }
+
+    fn lower(&self, instruction: &Instruction) -> Stmt {
+        match instruction.opcode {
+            1 | 2 | 7 | 8 => {
+                Stmt::Assign(self.place(instruction, 2), self.value(instruction).unwrap())
+            }
+            3 => Stmt::Input(self.place(instruction, 0)),
+            4 => Stmt::Output(self.read(instruction, 0)),
+            9 => Stmt::AdjustBase(self.read(instruction, 0)),
+            99 => Stmt::Halt,
+            _ => {
+                let condition = (self.read(instruction, 0), instruction.opcode == 5);
+                let target = instruction.static_target();
+                match (instruction.constant_jump(), target) {
+                    (Some(false), _) => Stmt::Nop,
+                    (Some(true), _) if self.program.calls.contains(&instruction.address) => {
+                        Stmt::Call(self.read(instruction, 1), Vec::new())
+                    }
+                    (Some(true), Some(target)) => Stmt::Goto(target),
+                    (Some(true), None) if instruction.params[1] == (2, 0) => Stmt::Return,
+                    (Some(true), None) => Stmt::IndirectGoto(None, self.read(instruction, 1)),
+                    (None, Some(target)) => Stmt::Branch(condition.0, condition.1, target),
+                    (None, None) => Stmt::IndirectGoto(Some(condition), self.read(instruction, 1)),
+                }
+            }
+        }
+    }
+}
+
+/// A function ready for structuring
+struct Lowered {
+    entry: usize,
+    items: Vec<Item>,
+    /// frame size set up by the function itself on entry
+    frame: i64,
+}
+
+fn lower_function(program: &Program, function: &Function) -> Lowered {
+    let instructions: Vec<Option<Instruction>> = function
+        .addresses
+        .iter()
+        .map(|address| program.instructions[address].clone())
+        .collect();
+
+    // addresses jumped to, including the return addresses of calls
+    let mut targets = BTreeSet::new();
+    for instruction in instructions.iter().flatten() {
+        if instruction.opcode == 5 || instruction.opcode == 6 {
+            targets.extend(instruction.static_target());
+            if program.calls.contains(&instruction.address) {
+                targets.insert(instruction.next());
+            }
+        }
+    }
+
+    // instructions writing into a parameter of one of the next instructions
+    let mut lowering = Lowering {
+        program,
+        patches: HashMap::new(),
+    };
+    let mut folded = BTreeSet::new();
+    for (i, first) in instructions.iter().enumerate() {
+        let first = match first {
+            Some(first) => first,
+            None => continue,
+        };
+        let patched = match first.params.get(2) {
+            Some(&(0, address)) if [1, 2, 7, 8].contains(&first.opcode) => address,
+            _ => continue,
+        };
+        let reads_slot = first.params[..2].iter().any(|param| param.0 == 2);
+        let mut previous = first;
+        for second in instructions[i + 1..].iter().take(4) {
+            let second = match second {
+                Some(second) => second,
+                None => break,
+            };
+            if previous.next() != second.address || targets.contains(&second.address) {
+                break;
+            }
+            let index = patched.checked_sub(second.address as i64 + 1);
+            if let Some(index) =
+                index.filter(|&index| index >= 0 && index < second.params.len() as i64)
+            {
+                if second.params[index as usize].0 != 2 {
+                    let value = lowering.value(first).unwrap();
+                    lowering.patches.insert(patched as usize, value);
+                    folded.insert(first.address);
+                }
+                break;
+            }
+            // the instructions in between must leave the patched value alone
+            let writes_memory = [1, 2, 3, 7, 8].contains(&second.opcode)
+                && second.params.last().map(|param| param.0) == Some(0);
+            let reads_patched = second.params.contains(&(0, patched));
+            if [5, 6, 99].contains(&second.opcode)
+                || writes_memory
+                || reads_patched
+                || (second.opcode == 9 && reads_slot)
+            {
+                break;
+            }
+            previous = second;
+        }
+    }
+
+    let mut items: Vec<Item> = Vec::new();
+    // address of a folded instruction, taken over by the next statement
+    let mut folded_address = None;
+    for (address, instruction) in function.addresses.iter().zip(instructions.iter()) {
+        if folded.contains(address) {
+            folded_address = folded_address.or(Some(*address));
+            continue;
+        }
+        let value = program.memory.get(*address).cloned().unwrap_or(0);
+        let address = folded_address.take().unwrap_or(*address);
+        let instruction = match instruction {
+            Some(instruction) => instruction,
+            None => {
+                items.push(Item {
+                    address,
+                    stmt: Stmt::Invalid(value),
+                    fallthrough: None,
+                    synthetic: false,
+                });
+                continue;
+            }
+        };
+        let stmt = lowering.lower(instruction);
+        let fallthrough = match stmt {
+            Stmt::Goto(_) | Stmt::Return | Stmt::Halt | Stmt::IndirectGoto(None, _) => None,
+            _ => Some(instruction.next()),
+        };
+        items.push(Item {
+            address,
+            stmt,
+            fallthrough,
+            synthetic: false,
+        });
+    }
+
+    // frame set up by the callee
+    let mut frame = 0;
+    if function.entry != 0 && !targets.contains(&function.entry) {
+        if let Some(Item {
+            stmt: Stmt::AdjustBase(Expr::Const(size)),
+            address,
+            ..
+        }) = items.first()
+        {
+            if *size > 0 && *address == function.entry {
+                frame = *size;
+                items.remove(0);
+            }
+        }
+    }
+
+    let mut lowered: Vec<Item> = Vec::new();
+    // caller side frame to tear down behind a call
+    let mut unshift: Option<(usize, i64)> = None;
+    for mut item in items.into_iter() {
+        let targeted = |address: usize| targets.contains(&address);
+        match &mut item.stmt {
+            Stmt::Return => {
+                // epilogue in front of the return
+                if let Some(previous) = lowered.last() {
+                    if let Stmt::AdjustBase(Expr::Const(size)) = previous.stmt {
+                        if frame > 0
+                            && size == -frame
+                            && previous.fallthrough == Some(item.address)
+                            && !targeted(item.address)
+                        {
+                            item.address = lowered.pop().unwrap().address;
+                        }
+                    }
+                }
+            }
+            Stmt::Call(_, args) => {
+                let mut shift = 0;
+                if let Some(previous) = lowered.last() {
+                    if let Stmt::AdjustBase(Expr::Const(size)) = previous.stmt {
+                        if previous.fallthrough == Some(item.address) && !targeted(item.address) {
+                            shift = size;
+                            item.address = lowered.pop().unwrap().address;
+                        }
+                    }
+                }
+                let mut arguments: BTreeMap<i64, Expr> = BTreeMap::new();
+                while let Some(previous) = lowered.last() {
+                    let slot = match &previous.stmt {
+                        Stmt::Assign(Expr::Slot(offset), _) => match offset.checked_sub(shift) {
+                            Some(slot) => slot,
+                            None => break,
+                        },
+                        _ => break,
+                    };
+                    if !(0..=MAX_ARGUMENTS).contains(&slot)
+                        || arguments.contains_key(&slot)
+                        || previous.fallthrough != Some(item.address)
+                        || targeted(item.address)
+                    {
+                        break;
+                    }
+                    let previous = lowered.pop().unwrap();
+                    if let Stmt::Assign(_, value) = previous.stmt {
+                        arguments.insert(slot, value);
+                    }
+                    item.address = previous.address;
+                }
+                // slot 0 holds the return address
+                arguments.remove(&0);
+                let count = arguments.keys().max().cloned().unwrap_or(0);
+                *args = (1..=count).map(|slot| arguments.remove(&slot)).collect();
+                if shift != 0 {
+                    unshift = Some((item.fallthrough.unwrap(), shift));
+                }
+                lowered.push(item);
+                continue;
+            }
+            Stmt::AdjustBase(Expr::Const(size)) => {
+                if let Some((address, shift)) = unshift {
+                    if address == item.address && *size == -shift {
+                        lowered.last_mut().unwrap().fallthrough = item.fallthrough;
+                        unshift = None;
+                        continue;
+                    }
+                }
+            }
+            _ => (),
+        }
+        unshift = None;
+        lowered.push(item);
+    }
+
+    // explicit jumps where the next statement is not the one executed next
+    let mut items: Vec<Item> = Vec::new();
+    for i in 0..lowered.len() {
+        items.push(lowered[i].clone());
+        if let Some(fallthrough) = lowered[i].fallthrough {
+            if lowered.get(i + 1).map(|next| next.address) != Some(fallthrough) {
+                items.push(Item {
+                    address: lowered[i].address,
+                    stmt: Stmt::Goto(fallthrough),
+                    fallthrough: None,
+                    synthetic: true,
+                });
+            }
+        }
+    }
+    Lowered {
+        entry: function.entry,
+        items,
+        frame,
+    }
+}
+
+/// Structured statements, referring to the items of the function by index
+#[derive(Debug)]
+enum Node {
+    Item(usize),
+    If {
+        start: usize,
+        condition: (Expr, bool),
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    /// evaluates pre, leaves the loop at test unless condition holds
+    While {
+        start: usize,
+        pre: Vec<usize>,
+        test: usize,
+        condition: (Expr, bool),
+        body: Vec<Node>,
+    },
+    DoWhile {
+        start: usize,
+        body: Vec<Node>,
+        test: usize,
+        condition: (Expr, bool),
+    },
+    Loop {
+        start: usize,
+        body: Vec<Node>,
+    },
+}
+
+impl Node {
+    fn start(&self) -> usize {
+        match self {
+            Node::Item(start)
+            | Node::If { start, .. }
+            | Node::While { start, .. }
+            | Node::DoWhile { start, .. }
+            | Node::Loop { start, .. } => *start,
+        }
+    }
+}
+
+struct Structurer<'l> {
+    items: &'l [Item],
+    /// item index of every code address
+    index: HashMap<usize, usize>,
+    /// how often every address is jumped to
+    jumps_to: HashMap<usize, usize>,
+}
+
+impl<'l> Structurer<'l> {
+    fn new(items: &'l [Item]) -> Structurer<'l> {
+        let mut index = HashMap::new();
+        let mut jumps_to = HashMap::new();
+        for (i, item) in items.iter().enumerate() {
+            if !item.synthetic {
+                index.insert(item.address, i);
+            }
+            if let Some(target) = item.stmt.jump_target() {
+                *jumps_to.entry(target).or_insert(0) += 1;
+            }
+        }
+        Structurer {
+            items,
+            index,
+            jumps_to,
+        }
+    }
+
+    fn target_index(&self, i: usize) -> Option<usize> {
+        self.items[i]
+            .stmt
+            .jump_target()
+            .and_then(|target| self.index.get(&target).cloned())
+    }
+
+    /// True if the item is no jump target, so it can disappear in a structure
+    fn untargeted(&self, i: usize) -> bool {
+        let item = &self.items[i];
+        item.synthetic || !self.jumps_to.contains_key(&item.address)
+    }
+
+    /// Remove the nodes from the item index on, if a node starts there
+    fn take_from(nodes: &mut Vec<Node>, start: usize, current: usize) -> bool {
+        if start == current {
+            return true;
+        }
+        match nodes.iter().position(|node| node.start() == start) {
+            Some(position) => {
+                nodes.truncate(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn structure(&self, lo: usize, hi: usize) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        let mut i = lo;
+        while i < hi {
+            let target = self.target_index(i);
+            match (&self.items[i].stmt, target) {
+                (Stmt::Branch(condition, jumps_if, _), Some(t)) if t > i && t <= hi => {
+                    let end_jump = if t - 1 > i && self.untargeted(t - 1) {
+                        self.target_index(t - 1)
+                            .filter(|_| matches!(self.items[t - 1].stmt, Stmt::Goto(_)))
+                    } else {
+                        None
+                    };
+                    match end_jump {
+                        Some(h)
+                            if h >= lo
+                                && h <= i
+                                && (h..i).all(|j| !self.items[j].stmt.is_control())
+                                && Structurer::take_from(&mut nodes, h, i) =>
+                        {
+                            nodes.push(Node::While {
+                                start: h,
+                                pre: (h..i).collect(),
+                                test: i,
+                                condition: (condition.clone(), !jumps_if),
+                                body: self.structure(i + 1, t - 1),
+                            });
+                            i = t;
+                        }
+                        Some(f) if f >= t && f <= hi => {
+                            nodes.push(Node::If {
+                                start: i,
+                                condition: (condition.clone(), !jumps_if),
+                                then: self.structure(i + 1, t - 1),
+                                otherwise: self.structure(t, f),
+                            });
+                            i = f;
+                        }
+                        _ => {
+                            nodes.push(Node::If {
+                                start: i,
+                                condition: (condition.clone(), !jumps_if),
+                                then: self.structure(i + 1, t),
+                                otherwise: Vec::new(),
+                            });
+                            i = t;
+                        }
+                    }
+                }
+                (Stmt::Branch(condition, jumps_if, _), Some(h))
+                    if h >= lo && h <= i && Structurer::take_from(&mut nodes, h, i) =>
+                {
+                    nodes.push(Node::DoWhile {
+                        start: h,
+                        body: self.structure(h, i),
+                        test: i,
+                        condition: (condition.clone(), *jumps_if),
+                    });
+                    i += 1;
+                }
+                (Stmt::Goto(_), Some(h))
+                    if h >= lo && h <= i && Structurer::take_from(&mut nodes, h, i) =>
+                {
+                    nodes.push(Node::Loop {
+                        start: h,
+                        body: self.structure(h, i),
+                    });
+                    i += 1;
+                }
+                _ => {
+                    nodes.push(Node::Item(i));
+                    i += 1;
+                }
+            }
+        }
+        nodes
+    }
+}
+
+/// Collect the targets of the gotos left over after structuring, jumps to
+/// the head of the innermost loop are shown as continue instead
+fn collect_labels(
+    nodes: &[Node],
+    items: &[Item],
+    head: Option<usize>,
+    labels: &mut BTreeSet<usize>,
+) {
+    for node in nodes.iter() {
+        match node {
+            Node::Item(i) => labels.extend(
+                items[*i]
+                    .stmt
+                    .jump_target()
+                    .filter(|target| Some(*target) != head),
+            ),
+            Node::If {
+                then, otherwise, ..
+            } => {
+                collect_labels(then, items, head, labels);
+                collect_labels(otherwise, items, head, labels);
+            }
+            Node::While { start, body, .. } | Node::Loop { start, body } => {
+                collect_labels(body, items, Some(items[*start].address), labels)
+            }
+            Node::DoWhile { body, .. } => collect_labels(body, items, None, labels),
+        }
+    }
+}
+
+/// Names the relative cells of a function and writes its pseudo-code
+struct Writer<'l> {
+    items: &'l [Item],
+    labels: BTreeSet<usize>,
+    /// address jumped to by continue
+    head: Option<usize>,
+    frame: i64,
+    arity: usize,
+    output: String,
+}
+
+fn simplify(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Binary(op, a, b) => {
+            let (a, b) = (simplify(a), simplify(b));
+            match (op, &a, &b) {
+                (BinaryOp::Add, Expr::Const(x), Expr::Const(y)) if x.checked_add(*y).is_some() => {
+                    Expr::Const(x + y)
+                }
+                (BinaryOp::Mul, Expr::Const(x), Expr::Const(y)) if x.checked_mul(*y).is_some() => {
+                    Expr::Const(x * y)
+                }
+                (BinaryOp::Add, value, Expr::Const(0)) | (BinaryOp::Add, Expr::Const(0), value) => {
+                    value.clone()
+                }
+                (BinaryOp::Mul, value, Expr::Const(1)) | (BinaryOp::Mul, Expr::Const(1), value) => {
+                    value.clone()
+                }
+                _ => Expr::Binary(*op, Box::new(a), Box::new(b)),
+            }
+        }
+        Expr::Load(address) => Expr::Load(Box::new(simplify(address))),
+        _ => expr.clone(),
+    }
+}
+
+impl<'l> Writer<'l> {
+    fn slot(&self, offset: i64) -> String {
+        let slot = match offset.checked_add(self.frame) {
+            Some(slot) => slot,
+            None => return format!("rel[{}]", offset),
+        };
+        if self.frame > 0 && slot >= self.frame {
+            format!("out{}", slot - self.frame)
+        } else if slot >= 1 && slot as usize <= self.arity {
+            format!("a{}", slot)
+        } else if slot >= 1 {
+            format!("v{}", slot)
+        } else if slot == 0 && self.frame > 0 {
+            String::from("return_address")
+        } else {
+            format!("rel[{}]", offset)
+        }
+    }
+
+    fn expr(&self, expr: &Expr) -> String {
+        self.simplified(&simplify(expr))
+    }
+
+    fn simplified(&self, expr: &Expr) -> String {
+        let operand = |expr: &Expr| match expr {
+            Expr::Binary(..) => format!("({})", self.simplified(expr)),
+            _ => self.simplified(expr),
+        };
+        match expr {
+            Expr::Const(value) => value.to_string(),
+            Expr::Global(address) => format!("[{}]", address),
+            Expr::Slot(offset) => self.slot(*offset),
+            Expr::Load(address) => format!("mem[{}]", self.simplified(address)),
+            Expr::Binary(BinaryOp::Mul, a, b) if **b == Expr::Const(-1) => {
+                format!("-{}", operand(a))
+            }
+            Expr::Binary(BinaryOp::Mul, a, b) if **a == Expr::Const(-1) => {
+                format!("-{}", operand(b))
+            }
+            Expr::Binary(BinaryOp::Add, a, b) => match **b {
+                Expr::Const(value) if value < 0 && value != i64::MIN => {
+                    format!("{} - {}", operand(a), -value)
+                }
+                _ => format!("{} + {}", operand(a), operand(b)),
+            },
+            Expr::Binary(op, a, b) => {
+                let symbol = match op {
+                    BinaryOp::Mul => "*",
+                    BinaryOp::Less => "<",
+                    _ => "==",
+                };
+                format!("{} {} {}", operand(a), symbol, operand(b))
+            }
+        }
+    }
+
+    /// Condition that is true if the expression is non zero (holds) or zero
+    fn condition(&self, condition: &(Expr, bool)) -> String {
+        let (expr, holds) = condition;
+        let expr = simplify(expr);
+        if *holds {
+            return self.simplified(&expr);
+        }
+        match &expr {
+            Expr::Binary(BinaryOp::Less, a, b) => self
+                .simplified(&Expr::Binary(BinaryOp::Less, b.clone(), a.clone()))
+                .replacen(" < ", " <= ", 1),
+            Expr::Binary(BinaryOp::Equal, ..) => self.simplified(&expr).replacen(" == ", " != ", 1),
+            Expr::Binary(..) => format!("!({})", self.simplified(&expr)),
+            _ => format!("!{}", self.simplified(&expr)),
+        }
+    }
+
+    fn line(&mut self, depth: usize, text: &str) {
+        for _ in 0..depth {
+            self.output += "    ";
+        }
+        self.output += text;
+        self.output += "\n";
+    }
+
+    fn label(&mut self, i: usize) {
+        let item = &self.items[i];
+        if !item.synthetic && self.labels.contains(&item.address) {
+            let label = format!("L{}:", item.address);
+            self.line(0, &label);
+        }
+    }
+
+    fn stmt(&self, stmt: &Stmt) -> Option<String> {
+        Some(match stmt {
+            Stmt::Assign(place, value) => format!("{} = {}", self.expr(place), self.expr(value)),
+            Stmt::Input(place) => format!("{} = input()", self.expr(place)),
+            Stmt::Output(value) => format!("output({})", self.expr(value)),
+            Stmt::AdjustBase(value) => format!("rb += {}", self.expr(value)),
+            Stmt::Call(target, args) => {
+                let args: Vec<String> = args
+                    .iter()
+                    .map(|arg| match arg {
+                        Some(arg) => self.expr(arg),
+                        None => String::from("_"),
+                    })
+                    .collect();
+                match target {
+                    Expr::Const(address) => format!("f{}({})", address, args.join(", ")),
+                    _ => format!("call {}({})", self.expr(target), args.join(", ")),
+                }
+            }
+            Stmt::Return => String::from("return"),
+            Stmt::Halt => String::from("halt"),
+            Stmt::Goto(target) if Some(*target) == self.head => String::from("continue"),
+            Stmt::Goto(target) => format!("goto L{}", target),
+            Stmt::Branch(condition, jumps_if, target) if Some(*target) == self.head => format!(
+                "if {} continue",
+                self.condition(&(condition.clone(), *jumps_if))
+            ),
+            Stmt::Branch(condition, jumps_if, target) => format!(
+                "if {} goto L{}",
+                self.condition(&(condition.clone(), *jumps_if)),
+                target
+            ),
+            Stmt::IndirectGoto(None, target) => format!("goto *{}", self.expr(target)),
+            Stmt::IndirectGoto(Some(condition), target) => format!(
+                "if {} goto *{}",
+                self.condition(condition),
+                self.expr(target)
+            ),
+            Stmt::Invalid(value) => format!("invalid instruction {}", value),
+            Stmt::Nop => return None,
+        })
+    }
+
+    fn item(&mut self, depth: usize, i: usize) {
+        self.label(i);
+        if let Some(text) = self.stmt(&self.items[i].stmt) {
+            self.line(depth, &text);
+        }
+    }
+
+    fn loop_body(&mut self, depth: usize, head: Option<usize>, body: &[Node]) {
+        let outer = std::mem::replace(&mut self.head, head);
+        self.nodes(depth, body);
+        self.head = outer;
+    }
+
+    fn nodes(&mut self, depth: usize, nodes: &[Node]) {
+        for node in nodes.iter() {
+            match node {
+                Node::Item(i) => self.item(depth, *i),
+                Node::If {
+                    start,
+                    condition,
+                    then,
+                    otherwise,
+                } => {
+                    self.label(*start);
+                    let text = format!("if {} {{", self.condition(condition));
+                    self.line(depth, &text);
+                    self.nodes(depth + 1, then);
+                    if !otherwise.is_empty() {
+                        self.line(depth, "} else {");
+                        self.nodes(depth + 1, otherwise);
+                    }
+                    self.line(depth, "}");
+                }
+                Node::While {
+                    start,
+                    pre,
+                    test,
+                    condition,
+                    body,
+                } => {
+                    if pre.is_empty() {
+                        self.label(*test);
+                        let text = format!("while {} {{", self.condition(condition));
+                        self.line(depth, &text);
+                    } else {
+                        self.line(depth, "loop {");
+                        for i in pre.iter() {
+                            self.item(depth + 1, *i);
+                        }
+                        self.label(*test);
+                        let negated = (condition.0.clone(), !condition.1);
+                        let text = format!("if {} break", self.condition(&negated));
+                        self.line(depth + 1, &text);
+                    }
+                    let head = self.items[*start].address;
+                    self.loop_body(depth + 1, Some(head), body);
+                    self.line(depth, "}");
+                }
+                Node::DoWhile {
+                    body,
+                    test,
+                    condition,
+                    ..
+                } => {
+                    self.line(depth, "do {");
+                    self.loop_body(depth + 1, None, body);
+                    self.label(*test);
+                    let text = format!("}} while {}", self.condition(condition));
+                    self.line(depth, &text);
+                }
+                Node::Loop { start, body } => {
+                    self.line(depth, "loop {");
+                    let head = self.items[*start].address;
+                    self.loop_body(depth + 1, Some(head), body);
+                    self.line(depth, "}");
+                }
+            }
+        }
+    }
+}
+
+/// Constants passed as argument to functions calling that argument, with
+/// the number of arguments they are called with
+fn callbacks(functions: &[Lowered]) -> Vec<(usize, usize)> {
+    let mut called_params: HashMap<i64, BTreeMap<i64, usize>> = HashMap::new();
+    for function in functions.iter() {
+        for item in function.items.iter() {
+            if let Stmt::Call(Expr::Slot(offset), args) = &item.stmt {
+                let param = offset.checked_add(function.frame).unwrap_or(0);
+                if param >= 1 {
+                    called_params
+                        .entry(function.entry as i64)
+                        .or_default()
+                        .insert(param, args.len());
+                }
+            }
+        }
+    }
+    let mut entries = Vec::new();
+    for function in functions.iter() {
+        for item in function.items.iter() {
+            if let Stmt::Call(Expr::Const(target), args) = &item.stmt {
+                for (param, arity) in called_params.get(target).into_iter().flatten() {
+                    if let Some(Some(arg)) = args.get(*param as usize - 1) {
+                        if let Expr::Const(entry) = simplify(arg) {
+                            if entry >= 0 {
+                                entries.push((entry as usize, *arity));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// Decompile the program to pseudo-code, one function after another.
+/// Execution starts in `main`, the other functions are named after their
+/// address, their parameters are a1, a2, ... and their other cells of the
+/// frame v1, v2, ... The cells behind the frame (the arguments of calls)
+/// are out1, out2, ... where out1 usually holds the result of the last call.
+pub fn decompile(memory: &[i64]) -> String {
+    let mut program = Program {
+        memory,
+        instructions: BTreeMap::new(),
+        calls: BTreeSet::new(),
+    };
+    let mut functions = BTreeMap::new();
+    let mut entries = vec![0];
+    let mut lowered: Vec<Lowered>;
+    let mut arities: HashMap<i64, usize> = HashMap::new();
+    loop {
+        while let Some(entry) = entries.pop() {
+            if functions.contains_key(&entry) {
+                continue;
+            }
+            let (function, callees) = program.explore(entry);
+            functions.insert(entry, function);
+            entries.extend(callees);
+        }
+        lowered = functions
+            .values()
+            .map(|function| lower_function(&program, function))
+            .collect();
+        for (entry, arity) in callbacks(&lowered) {
+            let known = arities.entry(entry as i64).or_insert(0);
+            *known = (*known).max(arity);
+            if !functions.contains_key(&entry) {
+                entries.push(entry);
+            }
+        }
+        if entries.is_empty() {
+            break;
+        }
+    }
+
+    for function in lowered.iter() {
+        for item in function.items.iter() {
+            if let Stmt::Call(Expr::Const(target), args) = &item.stmt {
+                let arity = arities.entry(*target).or_insert(0);
+                *arity = (*arity).max(args.len());
+            }
+        }
+    }
+
+    let mut output = String::new();
+    for function in lowered.iter() {
+        let structurer = Structurer::new(&function.items);
+        let nodes = structurer.structure(0, function.items.len());
+        let mut labels = BTreeSet::new();
+        collect_labels(&nodes, &function.items, None, &mut labels);
+        let arity = *arities.get(&(function.entry as i64)).unwrap_or(&0);
+        let mut writer = Writer {
+            items: &function.items,
+            labels,
+            head: None,
+            frame: function.frame,
+            arity,
+            output: String::new(),
+        };
+        writer.nodes(1, &nodes);
+
+        let params: Vec<String> = (1..=arity).map(|i| format!("a{}", i)).collect();
+        if !output.is_empty() {
+            output += "\n";
+        }
+        if function.entry == 0 {
+            output += "fn main() {\n";
+        } else {
+            output += &format!("fn f{}({}) {{\n", function.entry, params.join(", "));
+        }
+        output += &writer.output;
+        output += "}\n";
+    }
+    output
+}
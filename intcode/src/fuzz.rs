@@ -0,0 +1,661 @@
+use crate::conformance::{Feature, Interpreter, Outcome};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Small xorshift generator, so a fuzz run is reproducible from its seed
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // state must never be zero
+        Rng {
+            state: seed ^ 0x9E37_79B9_7F4A_7C15 | 1,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Random number in 0..n
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Random number in low..=high
+    pub fn between(&mut self, low: i64, high: i64) -> i64 {
+        low + (self.next_u64() % (high - low + 1) as u64) as i64
+    }
+
+    /// True with the given probability in percent
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.next_u64() % 100 < percent
+    }
+}
+
+/// Parameter of a generated instruction
+#[derive(Debug, Clone, PartialEq)]
+pub enum Param {
+    /// position mode access of a variable
+    Variable(usize),
+    /// immediate mode value
+    Value(i64),
+    /// relative mode offset
+    Relative(i64),
+    /// address of an instruction, either in immediate mode (1) or read from a
+    /// constant in position mode (0). Pointing past the last instruction
+    /// refers to the final halt.
+    Label(usize, u8),
+}
+
+impl Param {
+    fn mode(&self) -> i64 {
+        match self {
+            Param::Variable(_) => 0,
+            Param::Value(_) => 1,
+            Param::Relative(_) => 2,
+            Param::Label(_, mode) => *mode as i64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+    pub opcode: i64,
+    pub params: Vec<Param>,
+}
+
+/// A generated program: instructions followed by a halt, the variables and
+/// the constants holding jump targets. Code only ever writes to variables,
+/// unless relative mode is in play.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub instructions: Vec<Instruction>,
+    pub variables: Vec<i64>,
+}
+
+impl Program {
+    /// Lay out the program in memory
+    pub fn assemble(&self) -> Vec<i64> {
+        let mut addresses = Vec::new();
+        let mut address = 0;
+        for instruction in self.instructions.iter() {
+            addresses.push(address);
+            address += 1 + instruction.params.len();
+        }
+        let halt = address;
+        let variables_start = halt + 1;
+        let constants_start = variables_start + self.variables.len();
+        let label_address = |index: usize| -> i64 {
+            if index < addresses.len() {
+                addresses[index] as i64
+            } else {
+                halt as i64
+            }
+        };
+
+        let mut intcodes = Vec::new();
+        let mut constants = Vec::new();
+        for instruction in self.instructions.iter() {
+            let mut code = instruction.opcode;
+            for (i, param) in instruction.params.iter().enumerate() {
+                code += param.mode() * 10_i64.pow(i as u32 + 2);
+            }
+            intcodes.push(code);
+            for param in instruction.params.iter() {
+                intcodes.push(match param {
+                    Param::Variable(index) => (variables_start + index) as i64,
+                    Param::Value(value) => *value,
+                    Param::Relative(offset) => *offset,
+                    Param::Label(index, 1) => label_address(*index),
+                    Param::Label(index, _) => {
+                        constants.push(label_address(*index));
+                        (constants_start + constants.len() - 1) as i64
+                    }
+                });
+            }
+        }
+        intcodes.push(99);
+        intcodes.extend(self.variables.iter());
+        intcodes.extend(constants.iter());
+        intcodes
+    }
+}
+
+/// Which kind of instructions and numbers the generator may use
+struct Profile {
+    io: bool,
+    modes: bool,
+    conditionals: bool,
+    relative: bool,
+    negative: bool,
+    large: bool,
+}
+
+impl Profile {
+    fn random(rng: &mut Rng) -> Profile {
+        let modes = rng.chance(75);
+        Profile {
+            io: rng.chance(75),
+            modes,
+            conditionals: modes && rng.chance(60),
+            relative: modes && rng.chance(25),
+            negative: rng.chance(50),
+            large: rng.chance(15),
+        }
+    }
+
+    fn value(&self, rng: &mut Rng) -> i64 {
+        if self.large && rng.chance(10) {
+            return rng.between(1 << 32, 1 << 40);
+        }
+        if self.negative {
+            rng.between(-20, 20)
+        } else {
+            rng.between(0, 20)
+        }
+    }
+}
+
+fn generate_program(rng: &mut Rng, max_instructions: usize) -> (Program, Vec<i64>) {
+    let profile = Profile::random(rng);
+    let n_instructions = 1 + rng.below(max_instructions);
+    let n_variables = 1 + rng.below(6);
+    let mut opcodes = vec![1, 2];
+    if profile.io {
+        opcodes.extend_from_slice(&[3, 4]);
+    }
+    if profile.conditionals {
+        opcodes.extend_from_slice(&[5, 6, 7, 8]);
+    }
+    if profile.relative {
+        opcodes.push(9);
+    }
+
+    let read = |rng: &mut Rng| -> Param {
+        if profile.relative && rng.chance(20) {
+            Param::Relative(rng.between(0, 30))
+        } else if profile.modes && rng.chance(40) {
+            Param::Value(profile.value(rng))
+        } else {
+            Param::Variable(rng.below(n_variables))
+        }
+    };
+    let write = |rng: &mut Rng| -> Param {
+        if profile.relative && rng.chance(20) {
+            Param::Relative(rng.between(0, 30))
+        } else {
+            Param::Variable(rng.below(n_variables))
+        }
+    };
+
+    let mut instructions = Vec::new();
+    for _ in 0..n_instructions {
+        let opcode = opcodes[rng.below(opcodes.len())];
+        let params = match opcode {
+            1 | 2 | 7 | 8 => vec![read(rng), read(rng), write(rng)],
+            3 => vec![write(rng)],
+            4 => vec![read(rng)],
+            5 | 6 => {
+                let mode = if rng.chance(50) { 0 } else { 1 };
+                vec![read(rng), Param::Label(rng.below(n_instructions + 1), mode)]
+            }
+            _ => vec![Param::Value(rng.between(-3, 3))],
+        };
+        instructions.push(Instruction { opcode, params });
+    }
+    let variables = (0..n_variables).map(|_| profile.value(rng)).collect();
+    let inputs = (0..rng.below(5)).map(|_| profile.value(rng)).collect();
+    (
+        Program {
+            instructions,
+            variables,
+        },
+        inputs,
+    )
+}
+
+/// What the reference run observed while executing a program
+#[derive(Default)]
+struct Trace {
+    features: Vec<Feature>,
+    inputs_consumed: usize,
+    seen_output: bool,
+}
+
+impl Trace {
+    fn add(&mut self, feature: Feature) {
+        if !self.features.contains(&feature) {
+            self.features.push(feature);
+        }
+    }
+
+    fn check_number(&mut self, number: i128) {
+        if number < 0 {
+            self.add(Feature::NegativeNumbers);
+        }
+        if number > i32::MAX as i128 || number < i32::MIN as i128 {
+            self.add(Feature::LargeNumbers);
+        }
+    }
+
+    fn instruction(&mut self, opcode: i64, modes: &[i64]) {
+        match opcode {
+            3 | 4 => self.add(Feature::Io),
+            5..=8 => self.add(Feature::Conditionals),
+            9 => self.add(Feature::RelativeBase),
+            _ => (),
+        }
+        if modes.iter().any(|&mode| mode != 0) {
+            self.add(Feature::ParameterModes);
+        }
+        if modes.contains(&2) {
+            self.add(Feature::RelativeBase);
+        }
+    }
+
+    fn input(&mut self, value: i64) {
+        self.inputs_consumed += 1;
+        if self.seen_output || self.inputs_consumed > 2 {
+            self.add(Feature::StreamingIo);
+        }
+        self.check_number(value as i128);
+    }
+}
+
+/// Memory of the reference, as much as the IntcodeComputer has
+const MEMORY_SIZE: usize = 125_000;
+
+/// What a step of the reference ended with
+enum Step {
+    Running,
+    Halted,
+    /// invalid instruction or address, arithmetic overflow or missing input
+    Failed,
+}
+
+/// Plain interpreter of the complete instruction set, kept apart from all
+/// interpreters under test so the IntcodeComputer is checked as well
+struct Reference<'a> {
+    memory: Vec<i64>,
+    program_length: usize,
+    ip: usize,
+    relative_base: i64,
+    inputs: std::slice::Iter<'a, i64>,
+    outputs: Vec<i64>,
+    trace: Trace,
+}
+
+impl<'a> Reference<'a> {
+    fn new(program: &[i64], inputs: &'a [i64]) -> Reference<'a> {
+        let mut trace = Trace::default();
+        for &number in program.iter().chain(inputs.iter()) {
+            trace.check_number(number as i128);
+        }
+        Reference {
+            memory: program.to_vec(),
+            program_length: program.len(),
+            ip: 0,
+            relative_base: 0,
+            inputs: inputs.iter(),
+            outputs: Vec::new(),
+            trace,
+        }
+    }
+
+    fn access(&mut self, address: usize) -> Option<&mut i64> {
+        if address >= MEMORY_SIZE {
+            return None;
+        }
+        if address >= self.program_length {
+            self.trace.add(Feature::MemoryBeyondProgram);
+        }
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        Some(&mut self.memory[address])
+    }
+
+    /// Address parameter n of the current instruction refers to
+    fn address(&mut self, n: usize, mode: i64) -> Option<usize> {
+        let at = self.ip + 1 + n;
+        let parameter = *self.memory.get(at)?;
+        let address = match mode {
+            0 => parameter,
+            1 => return Some(at),
+            2 => self.relative_base.checked_add(parameter)?,
+            _ => return None,
+        };
+        if address < 0 {
+            return None;
+        }
+        Some(address as usize)
+    }
+
+    fn read(&mut self, n: usize, mode: i64) -> Option<i64> {
+        let address = self.address(n, mode)?;
+        let value = *self.access(address)?;
+        self.trace.check_number(value as i128);
+        Some(value)
+    }
+
+    fn write(&mut self, n: usize, mode: i64, value: i64) -> Option<()> {
+        if mode == 1 {
+            return None;
+        }
+        let address = self.address(n, mode)?;
+        *self.access(address)? = value;
+        self.trace.check_number(value as i128);
+        Some(())
+    }
+
+    /// Execute one instruction, None if it fails
+    fn execute(&mut self) -> Option<Step> {
+        let instruction = *self.memory.get(self.ip)?;
+        if !(0..100_000).contains(&instruction) {
+            return None;
+        }
+        let opcode = instruction % 100;
+        let modes = [
+            instruction / 100 % 10,
+            instruction / 1000 % 10,
+            instruction / 10000 % 10,
+        ];
+        let n_params = match opcode {
+            1 | 2 | 7 | 8 => 3,
+            5 | 6 => 2,
+            3 | 4 | 9 => 1,
+            99 => 0,
+            _ => return None,
+        };
+        self.trace.instruction(opcode, &modes[..n_params]);
+        let mut next = self.ip + 1 + n_params;
+        match opcode {
+            1 | 2 => {
+                let (a, b) = (self.read(0, modes[0])?, self.read(1, modes[1])?);
+                let (wide, result) = if opcode == 1 {
+                    (a as i128 + b as i128, a.checked_add(b))
+                } else {
+                    (a as i128 * b as i128, a.checked_mul(b))
+                };
+                self.trace.check_number(wide);
+                self.write(2, modes[2], result?)?;
+            }
+            3 => {
+                let value = *self.inputs.next()?;
+                self.trace.input(value);
+                self.write(0, modes[0], value)?;
+            }
+            4 => {
+                let value = self.read(0, modes[0])?;
+                self.outputs.push(value);
+                self.trace.seen_output = true;
+            }
+            5 | 6 => {
+                let (condition, target) = (self.read(0, modes[0])?, self.read(1, modes[1])?);
+                if (condition != 0) == (opcode == 5) {
+                    if target < 0 {
+                        return None;
+                    }
+                    next = target as usize;
+                }
+            }
+            7 | 8 => {
+                let (a, b) = (self.read(0, modes[0])?, self.read(1, modes[1])?);
+                let holds = if opcode == 7 { a < b } else { a == b };
+                self.write(2, modes[2], holds as i64)?;
+            }
+            9 => {
+                let offset = self.read(0, modes[0])?;
+                self.relative_base = self.relative_base.checked_add(offset)?;
+            }
+            _ => return Some(Step::Halted),
+        }
+        self.ip = next;
+        Some(Step::Running)
+    }
+
+    fn step(&mut self) -> Step {
+        self.execute().unwrap_or(Step::Failed)
+    }
+}
+
+/// Run the program on the reference. Returns None if the program fails or
+/// does not halt within budget instructions.
+fn reference_run(
+    program: &[i64],
+    inputs: &[i64],
+    budget: usize,
+) -> Option<(Outcome, Vec<Feature>)> {
+    let mut reference = Reference::new(program, inputs);
+    for _ in 0..budget {
+        match reference.step() {
+            Step::Running => (),
+            Step::Halted => {
+                let outcome = Outcome {
+                    outputs: reference.outputs,
+                    memory: reference.memory[..program.len()].to_vec(),
+                };
+                return Some((outcome, reference.trace.features));
+            }
+            Step::Failed => return None,
+        }
+    }
+    None
+}
+
+/// Run an interpreter for as many instructions as the reference may use. A
+/// panic or a run that does not halt by then is reported as error.
+fn execute_bounded(
+    interpreter: &(dyn Interpreter + Sync),
+    program: &[i64],
+    inputs: &[i64],
+    budget: usize,
+) -> Result<Outcome, String> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        interpreter.execute_within(program, inputs, budget)
+    }));
+    match result {
+        Ok(Some(outcome)) => Ok(outcome),
+        Ok(None) => Err(format!(
+            "interpreter did not halt within {} instructions",
+            budget
+        )),
+        Err(_) => Err(String::from("interpreter panicked")),
+    }
+}
+
+/// A program on which an interpreter does not agree with the reference
+pub struct Divergence {
+    pub interpreter: &'static str,
+    pub program: Program,
+    pub inputs: Vec<i64>,
+    pub expected: Outcome,
+    pub actual: Result<Outcome, String>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let program: Vec<String> = self
+            .program
+            .assemble()
+            .iter()
+            .map(|code| code.to_string())
+            .collect();
+        writeln!(f, "{} diverges on", self.interpreter)?;
+        writeln!(f, "  program: {}", program.join(","))?;
+        writeln!(f, "  inputs: {:?}", self.inputs)?;
+        writeln!(f, "  expected: {:?}", self.expected)?;
+        write!(f, "  actual: {:?}", self.actual)
+    }
+}
+
+pub struct FuzzConfig {
+    pub seed: u64,
+    /// number of programs that have to halt on the reference
+    pub programs: usize,
+    pub max_instructions: usize,
+    /// maximal number of instructions executed by the reference and the
+    /// interpreters
+    pub budget: usize,
+}
+
+pub struct FuzzReport {
+    pub generated: usize,
+    /// how many programs every interpreter was compared on
+    pub compared: Vec<(&'static str, usize)>,
+    /// minimised reproducers of all divergences found
+    pub divergences: Vec<Divergence>,
+}
+
+/// Check a single program against one interpreter
+fn diverges(
+    interpreter: &'static (dyn Interpreter + Sync),
+    program: &Program,
+    inputs: &[i64],
+    config: &FuzzConfig,
+) -> Option<Divergence> {
+    let intcodes = program.assemble();
+    let (expected, features) = reference_run(&intcodes, inputs, config.budget)?;
+    if !features
+        .iter()
+        .all(|&feature| interpreter.supports(feature))
+    {
+        return None;
+    }
+    let actual = execute_bounded(interpreter, &intcodes, inputs, config.budget);
+    if let Ok(outcome) = &actual {
+        if outcome.outputs == expected.outputs
+            && outcome.memory.len() >= intcodes.len()
+            && outcome.memory[..intcodes.len()] == expected.memory[..]
+        {
+            return None;
+        }
+    }
+    Some(Divergence {
+        interpreter: interpreter.name(),
+        program: program.clone(),
+        inputs: inputs.to_vec(),
+        expected,
+        actual,
+    })
+}
+
+/// Smaller variants of a program and its inputs, tried while minimising
+fn shrink_candidates(program: &Program, inputs: &[i64]) -> Vec<(Program, Vec<i64>)> {
+    let mut candidates = Vec::new();
+    for i in (0..inputs.len()).rev() {
+        let mut fewer_inputs = inputs.to_vec();
+        fewer_inputs.remove(i);
+        candidates.push((program.clone(), fewer_inputs));
+    }
+    for i in (0..program.instructions.len()).rev() {
+        let mut shorter = program.clone();
+        shorter.instructions.remove(i);
+        // labels behind the removed instruction move up by one
+        for instruction in shorter.instructions.iter_mut() {
+            for param in instruction.params.iter_mut() {
+                if let Param::Label(index, _) = param {
+                    if *index > i {
+                        *index -= 1;
+                    }
+                }
+            }
+        }
+        candidates.push((shorter, inputs.to_vec()));
+    }
+    let simpler = |value: i64| -> Vec<i64> {
+        let mut values = vec![0, 1, value / 2];
+        values.retain(|&simple| simple.abs() < value.abs());
+        values
+    };
+    for i in 0..program.variables.len() {
+        for value in simpler(program.variables[i]) {
+            let mut simplified = program.clone();
+            simplified.variables[i] = value;
+            candidates.push((simplified, inputs.to_vec()));
+        }
+    }
+    for i in 0..program.instructions.len() {
+        for j in 0..program.instructions[i].params.len() {
+            if let Param::Value(value) = program.instructions[i].params[j] {
+                for simple in simpler(value) {
+                    let mut simplified = program.clone();
+                    simplified.instructions[i].params[j] = Param::Value(simple);
+                    candidates.push((simplified, inputs.to_vec()));
+                }
+            }
+        }
+    }
+    for i in 0..inputs.len() {
+        for value in simpler(inputs[i]) {
+            let mut simplified = inputs.to_vec();
+            simplified[i] = value;
+            candidates.push((program.clone(), simplified));
+        }
+    }
+    candidates
+}
+
+/// Shrink a divergence until no smaller program shows it anymore
+pub fn minimise(
+    interpreter: &'static (dyn Interpreter + Sync),
+    divergence: Divergence,
+    config: &FuzzConfig,
+) -> Divergence {
+    let mut smallest = divergence;
+    'shrinking: loop {
+        for (program, inputs) in shrink_candidates(&smallest.program, &smallest.inputs) {
+            if let Some(smaller) = diverges(interpreter, &program, &inputs, config) {
+                smallest = smaller;
+                continue 'shrinking;
+            }
+        }
+        return smallest;
+    }
+}
+
+/// Generate random programs and compare every interpreter that supports the
+/// features a program uses with the reference
+pub fn fuzz(config: &FuzzConfig, interpreters: &[&'static (dyn Interpreter + Sync)]) -> FuzzReport {
+    let mut rng = Rng::new(config.seed);
+    let mut report = FuzzReport {
+        generated: 0,
+        compared: interpreters
+            .iter()
+            .map(|interpreter| (interpreter.name(), 0))
+            .collect(),
+        divergences: Vec::new(),
+    };
+    let mut halting_programs = 0;
+    while halting_programs < config.programs {
+        let (program, inputs) = generate_program(&mut rng, config.max_instructions);
+        report.generated += 1;
+        let intcodes = program.assemble();
+        let features = match reference_run(&intcodes, &inputs, config.budget) {
+            Some((_, features)) => features,
+            None => continue,
+        };
+        halting_programs += 1;
+        for (i, &interpreter) in interpreters.iter().enumerate() {
+            if !features
+                .iter()
+                .all(|&feature| interpreter.supports(feature))
+            {
+                continue;
+            }
+            report.compared[i].1 += 1;
+            if let Some(divergence) = diverges(interpreter, &program, &inputs, config) {
+                report
+                    .divergences
+                    .push(minimise(interpreter, divergence, config));
+            }
+        }
+    }
+    report
+}
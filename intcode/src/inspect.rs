@@ -0,0 +1,15 @@
+// Access to the registers of an IntcodeComputer for the debugger and the
+// decompiler, next to the memory access of day 13.
+
+use crate::computer::IntcodeComputer;
+
+impl IntcodeComputer {
+    /// Address of the instruction executed next
+    pub fn instruction_pointer(&self) -> usize {
+        self.ip
+    }
+
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+}
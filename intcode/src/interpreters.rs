@@ -0,0 +1,154 @@
+use crate::computer::IntcodeComputer;
+use crate::conformance::{Feature, Interpreter, Outcome};
+use crate::{day2, day5, day7};
+
+/// The day 2 interpreter: opcodes 1, 2 and 99 on unsigned numbers
+pub struct Day2;
+
+impl Interpreter for Day2 {
+    fn name(&self) -> &'static str {
+        "day2 run_intcodes"
+    }
+
+    fn supports(&self, _feature: Feature) -> bool {
+        false
+    }
+
+    fn execute_within(
+        &self,
+        program: &[i64],
+        _inputs: &[i64],
+        max_steps: usize,
+    ) -> Option<Outcome> {
+        let mut intcodes: Vec<usize> = program.iter().map(|&value| value as usize).collect();
+        if !day2::run_intcodes_within(&mut intcodes, max_steps) {
+            return None;
+        }
+        Some(Outcome {
+            outputs: Vec::new(),
+            memory: intcodes.iter().map(|&value| value as i64).collect(),
+        })
+    }
+}
+
+/// The day 5 interpreter: all inputs passed upfront, position and immediate mode
+pub struct Day5;
+
+impl Interpreter for Day5 {
+    fn name(&self) -> &'static str {
+        "day5 calculate_output"
+    }
+
+    fn supports(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::Io
+            | Feature::ParameterModes
+            | Feature::Conditionals
+            | Feature::NegativeNumbers
+            | Feature::StreamingIo => true,
+            Feature::RelativeBase | Feature::LargeNumbers | Feature::MemoryBeyondProgram => false,
+        }
+    }
+
+    fn execute_within(&self, program: &[i64], inputs: &[i64], max_steps: usize) -> Option<Outcome> {
+        let mut intcodes: Vec<i32> = program.iter().map(|&value| value as i32).collect();
+        let inputs: Vec<i32> = inputs.iter().map(|&value| value as i32).collect();
+        let outputs = day5::calculate_output_within(&mut intcodes, &inputs, max_steps)?;
+        Some(Outcome {
+            outputs: outputs.iter().map(|&value| value as i64).collect(),
+            memory: intcodes.iter().map(|&value| value as i64).collect(),
+        })
+    }
+}
+
+/// The day 7 amplifier interpreter: pauses at every output and is restarted
+/// from the returned instruction pointer. Takes at most two inputs, which are
+/// read from the start again after every restart.
+pub struct Day7;
+
+impl Interpreter for Day7 {
+    fn name(&self) -> &'static str {
+        "day7 calculate_output"
+    }
+
+    fn supports(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::Io
+            | Feature::ParameterModes
+            | Feature::Conditionals
+            | Feature::NegativeNumbers => true,
+            Feature::StreamingIo
+            | Feature::RelativeBase
+            | Feature::LargeNumbers
+            | Feature::MemoryBeyondProgram => false,
+        }
+    }
+
+    fn execute_within(&self, program: &[i64], inputs: &[i64], max_steps: usize) -> Option<Outcome> {
+        let mut intcodes: Vec<i32> = program.iter().map(|&value| value as i32).collect();
+        let mut amplifier_inputs = [0; 2];
+        for (slot, &value) in amplifier_inputs.iter_mut().zip(inputs.iter()) {
+            *slot = value as usize;
+        }
+        let mut outputs = Vec::new();
+        let mut ip = 0;
+        let mut steps = max_steps;
+        loop {
+            let (next_ip, output) =
+                day7::calculate_output_within(&mut intcodes, amplifier_inputs, ip, &mut steps)?;
+            if next_ip == -1 {
+                break;
+            }
+            outputs.push(output as i64);
+            ip = next_ip as usize;
+        }
+        Some(Outcome {
+            outputs,
+            memory: intcodes.iter().map(|&value| value as i64).collect(),
+        })
+    }
+}
+
+/// The IntcodeComputer used from day 9 onwards
+pub struct Computer;
+
+impl Interpreter for Computer {
+    fn name(&self) -> &'static str {
+        "IntcodeComputer"
+    }
+
+    fn supports(&self, _feature: Feature) -> bool {
+        true
+    }
+
+    fn execute_within(&self, program: &[i64], inputs: &[i64], max_steps: usize) -> Option<Outcome> {
+        let mut computer = IntcodeComputer::new(program.to_vec());
+        let mut inputs = inputs.iter();
+        let mut outputs = Vec::new();
+        let mut steps = 0;
+        while steps < max_steps {
+            let running = computer.step();
+            if computer.terminated {
+                return Some(Outcome {
+                    outputs,
+                    memory: (0..program.len())
+                        .map(|address| computer.read_memory(address))
+                        .collect(),
+                });
+            }
+            if running {
+                steps += 1;
+            } else if computer.requesting_input {
+                // paused before the input instruction
+                let input = inputs
+                    .next()
+                    .expect("Program requested more inputs than were passed.");
+                computer.set_memory_input(*input);
+            } else {
+                steps += 1;
+                outputs.push(computer.memory_output);
+            }
+        }
+        None
+    }
+}
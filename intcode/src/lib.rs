@@ -0,0 +1,70 @@
+// Tooling shared by all intcode interpreters of the different days.
+// The interpreters themselves stay in their day, they are pulled in here
+// by path. Days 2, 5 and 7 each have their own interpreter. Days 13 to 25
+// keep identical copies of computer.rs, only the one of day 25 is included,
+// so every module from those days runs on it and not on its own copy.
+
+#[path = "../../day2/src/intcode.rs"]
+pub mod day2;
+// the symbolic execution of day 2 refers to its interpreter by this name
+use day2 as intcode;
+#[path = "../../day2/src/symbolic.rs"]
+pub mod symbolic;
+#[path = "../../day5/src/intcode.rs"]
+pub mod day5;
+#[path = "../../day7/src/intcode.rs"]
+pub mod day7;
+#[path = "../../day13/src/memory.rs"]
+pub mod memory;
+#[path = "../../day13/src/arcade.rs"]
+pub mod arcade;
+#[path = "../../day13/src/scanner.rs"]
+pub mod scanner;
+#[path = "../../day19/src/batch.rs"]
+pub mod batch;
+#[path = "../../day21/src/springscript.rs"]
+pub mod springscript;
+#[path = "../../day21/src/hull.rs"]
+pub mod hull;
+#[path = "../../day21/src/springdroid.rs"]
+pub mod springdroid;
+#[path = "../../day21/src/corpus.rs"]
+pub mod corpus;
+#[path = "../../day21/src/logic.rs"]
+pub mod logic;
+#[path = "../../day21/src/expression.rs"]
+pub mod expression;
+#[path = "../../day21/src/synthesis.rs"]
+pub mod synthesis;
+#[path = "../../day23/src/network.rs"]
+pub mod network;
+#[path = "../../day23/src/capture.rs"]
+pub mod capture;
+#[path = "../../day23/src/codec.rs"]
+pub mod codec;
+#[path = "../../day23/src/schedule.rs"]
+pub mod schedule;
+#[path = "../../day23/src/threaded.rs"]
+pub mod threaded;
+#[path = "../../day25/src/computer.rs"]
+pub mod computer;
+#[path = "../../day25/src/snapshot.rs"]
+pub mod snapshot;
+#[path = "../../day25/src/ascii_computer.rs"]
+pub mod ascii_computer;
+#[path = "../../day25/src/adventure.rs"]
+pub mod adventure;
+#[path = "../../day25/src/explorer.rs"]
+pub mod explorer;
+#[path = "../../day25/src/checkpoint.rs"]
+pub mod checkpoint;
+#[path = "../../day25/src/repl.rs"]
+pub mod repl;
+
+pub mod compiler;
+pub mod conformance;
+pub mod debugger;
+pub mod decompiler;
+pub mod fuzz;
+pub mod inspect;
+pub mod interpreters;
@@ -0,0 +1,86 @@
+use intcode::adventure::{parse, Alert, Direction, Message, Room};
+
+const EJECTED: &str = "
+
+
+== Pressure-Sensitive Floor ==
+Analyzing...
+
+Doors here lead:
+- south
+
+A loud, robotic voice says \"Alert! Droids on this ship are heavier than the detected value!\" and you are ejected back to the checkpoint.
+
+
+
+== Security Checkpoint ==
+In the next room, a pressure-sensitive floor will verify your identity.
+
+Doors here lead:
+- north
+- east
+
+Items here:
+- jam
+- spool of cat6
+
+Command?
+";
+
+fn room(name: &str, description: &str, doors: Vec<Direction>, items: Vec<&str>) -> Message {
+    Message::Room(Room {
+        name: String::from(name),
+        description: String::from(description),
+        doors,
+        items: items.into_iter().map(String::from).collect(),
+    })
+}
+
+#[test]
+fn ejection_describes_both_rooms() {
+    assert_eq!(
+        parse(EJECTED),
+        vec![
+            room(
+                "Pressure-Sensitive Floor",
+                "Analyzing...",
+                vec![Direction::South],
+                vec![]
+            ),
+            Message::Ejected(Alert::DroidTooLight),
+            room(
+                "Security Checkpoint",
+                "In the next room, a pressure-sensitive floor will verify your identity.",
+                vec![Direction::North, Direction::East],
+                vec!["jam", "spool of cat6"]
+            ),
+            Message::Prompt,
+        ]
+    );
+}
+
+#[test]
+fn item_messages() {
+    let text = "\nYou take the prime number.\n\nCommand?\n\nYou drop the mug.\n\nCommand?\n\
+                \nYou don't have that item.\n\nCommand?\n";
+    assert_eq!(
+        parse(text),
+        vec![
+            Message::Taken(String::from("prime number")),
+            Message::Prompt,
+            Message::Dropped(String::from("mug")),
+            Message::Prompt,
+            Message::Other(String::from("You don't have that item.")),
+            Message::Prompt,
+        ]
+    );
+    assert_eq!(
+        parse(
+            "\"Oh, hello! You should be able to get in by typing 537002052 on the keypad at the \
+             main airlock.\"\n"
+        ),
+        vec![Message::Passcode(537002052)]
+    );
+    assert_eq!("west".parse(), Ok(Direction::West));
+    assert_eq!(Direction::North.to_string(), "north");
+}
@@ -0,0 +1,47 @@
+use intcode::ascii_computer::{AsciiComputer, Output};
+
+/// Prints "Hi", a large value and "ok", then echoes one input and halts
+const PROGRAM: [i64; 19] = [
+    104, 72, 104, 105, 104, 10, 104, 1000, 104, 111, 104, 107, 3, 17, 4, 17, 99, 0, 0,
+];
+
+#[test]
+fn lines_separate_text_and_values() {
+    let mut computer = AsciiComputer::new(PROGRAM.to_vec());
+    let outputs: Vec<Output> = computer.lines().collect();
+    assert_eq!(
+        outputs,
+        vec![
+            Output::Line(String::from("Hi")),
+            Output::Value(1000),
+            Output::Line(String::from("ok"))
+        ]
+    );
+    assert_eq!(computer.read_line(), None);
+    assert!(!computer.terminated);
+    assert_eq!(computer.enter_command("A"), Ok(String::from("A")));
+    assert!(computer.terminated);
+}
+
+#[test]
+fn run_returns_the_text_up_to_the_prompt() {
+    let mut computer = AsciiComputer::new(PROGRAM.to_vec());
+    assert_eq!(computer.run(), "Hi\nok");
+    assert_eq!(computer.take_values(), vec![1000]);
+    assert_eq!(computer.run(), "");
+    assert!(computer.take_values().is_empty());
+}
+
+#[test]
+fn non_ascii_commands_are_rejected() {
+    let mut computer = AsciiComputer::new(PROGRAM.to_vec());
+    computer.run();
+    assert_eq!(
+        computer.enter_command("Ä"),
+        Err(String::from("Non ascii character 'Ä' in command."))
+    );
+    // nothing was fed, the program still waits for its input
+    assert!(!computer.terminated);
+    assert_eq!(computer.enter_command("B"), Ok(String::from("B")));
+    assert!(computer.terminated);
+}
@@ -0,0 +1,40 @@
+use intcode::batch::run_batch;
+use intcode::compiler::compile;
+
+#[test]
+fn batch_keeps_the_order_of_the_inputs() {
+    // the amount of work differs per input, so the runs finish out of order
+    let program = compile(
+        "
+        fn main() {
+            let n = input();
+            let sum = 0;
+            while n > 0 {
+                sum = sum + n;
+                n = n - 1;
+            }
+            output(sum);
+            output(input());
+        }",
+    )
+    .unwrap();
+    let inputs: Vec<Vec<i64>> = (0..200).map(|i| vec![(i * 37) % 500, i]).collect();
+    let outputs = run_batch(&program, &inputs);
+    assert_eq!(outputs.len(), inputs.len());
+    for (input, output) in inputs.iter().zip(outputs.iter()) {
+        let n = input[0];
+        assert_eq!(output, &vec![n * (n + 1) / 2, input[1]]);
+        assert_eq!(output, &run_batch(&program, std::slice::from_ref(input))[0]);
+    }
+}
+
+#[test]
+fn runs_stop_when_inputs_run_out() {
+    let doubler = [3, 9, 1002, 9, 2, 9, 4, 9, 1105, 1, 0];
+    assert_eq!(run_batch(&doubler, &[vec![1, 2, 3]]), vec![vec![2, 4, 6]]);
+    assert_eq!(
+        run_batch(&doubler, &[vec![], vec![5], vec![7, 8]]),
+        vec![vec![], vec![10], vec![14, 16]]
+    );
+    assert!(run_batch(&doubler, &[]).is_empty());
+}
@@ -0,0 +1,96 @@
+use intcode::capture::{self, Format, Kind, Record};
+use intcode::network::{Network, NetworkConfig, Packet};
+
+mod common;
+
+#[test]
+fn record_formats() {
+    let send = Record {
+        round: 3,
+        kind: Kind::Send,
+        src: Some(4),
+        dst: Some(255),
+        packet: Some(Packet { x: -1, y: 20 }),
+    };
+    assert_eq!(send.to_line(), "3 SEND 4 255 -1 20");
+    assert_eq!(
+        send.to_json(),
+        "{\"round\":3,\"kind\":\"SEND\",\"src\":4,\"dst\":255,\"x\":-1,\"y\":20}"
+    );
+    let idle = Record {
+        round: 7,
+        kind: Kind::Idle,
+        src: None,
+        dst: None,
+        packet: None,
+    };
+    assert_eq!(idle.to_line(), "7 IDLE - - - -");
+    assert_eq!(
+        idle.to_json(),
+        "{\"round\":7,\"kind\":\"IDLE\",\"src\":null,\"dst\":null,\"x\":null,\"y\":null}"
+    );
+    for record in [send, idle].iter() {
+        assert_eq!(record.to_line().parse::<Record>().unwrap(), *record);
+        assert_eq!(record.to_json().parse::<Record>().unwrap(), *record);
+    }
+    assert_eq!(
+        "1 JUMP - - - -".parse::<Record>().unwrap_err(),
+        "Unknown event JUMP."
+    );
+    assert_eq!(
+        "{\"round\":1,\"kind\":\"IDLE\"}"
+            .parse::<Record>()
+            .unwrap_err(),
+        "Missing field src."
+    );
+    assert_eq!(
+        "-3 IDLE - - - -".parse::<Record>().unwrap_err(),
+        "Invalid round -3."
+    );
+    assert_eq!(
+        "- IDLE - - - -".parse::<Record>().unwrap_err(),
+        "Missing round."
+    );
+    assert_eq!(Format::for_path("trace.jsonl"), Format::JsonLines);
+    assert_eq!(Format::for_path("trace.txt"), Format::Lines);
+}
+
+#[test]
+fn capture_and_analyse() {
+    let config = NetworkConfig {
+        capture: true,
+        ..NetworkConfig::default()
+    };
+    let report = Network::new(common::intcodes(23), config).run();
+    let records = &report.capture;
+    let count = |kind: Kind| records.iter().filter(|record| record.kind == kind).count();
+    assert_eq!(count(Kind::NatWakeup), report.wakeups.len());
+    assert!(count(Kind::Idle) >= report.wakeups.len());
+    assert!(records
+        .windows(2)
+        .all(|pair| pair[0].round <= pair[1].round));
+
+    for name in ["capture.txt", "capture.jsonl"].iter() {
+        let path = std::env::temp_dir().join(format!("{}_{}", std::process::id(), name));
+        capture::write(&path, records, Format::for_path(&path)).unwrap();
+        assert_eq!(&capture::read(&path).unwrap(), records);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    let analysis = capture::analyse(records, 255);
+    assert_eq!(analysis.rounds, report.rounds);
+    let sent: usize = analysis.nodes.values().map(|stats| stats.sent).sum();
+    let received: usize = analysis.nodes.values().map(|stats| stats.received).sum();
+    assert_eq!(sent, count(Kind::Send));
+    assert_eq!(received, count(Kind::Receive));
+    let first = analysis.nat_history.first().unwrap();
+    assert_eq!((first.kind, first.packet.unwrap().y), (Kind::Send, 20372));
+    let last = analysis.nat_history.last().unwrap();
+    assert_eq!(
+        (last.kind, last.dst, last.packet.unwrap().y),
+        (Kind::NatWakeup, Some(0), 13334)
+    );
+    let text = analysis.to_string();
+    assert!(text.starts_with(&format!("{} rounds, idle", report.rounds)));
+    assert!(text.contains("woke up 0 with (98563, 13334)"));
+}
@@ -0,0 +1,136 @@
+use intcode::codec::{CodecError, Destination, Packet, PacketCodec};
+use intcode::computer::IntcodeComputer;
+use intcode::network::{self, Network, NetworkConfig, NoopNat, Termination};
+use intcode::threaded::ThreadedNetwork;
+
+fn boot(intcodes: &[i64], nodes: usize) -> PacketCodec {
+    PacketCodec::boot(IntcodeComputer::new(intcodes.to_vec()), 0, nodes, 255)
+}
+
+#[test]
+fn partial_packets_are_kept() {
+    // sends the destination, reads an input, then sends x and y
+    let mut codec = boot(&[3, 100, 104, 5, 3, 101, 104, 7, 104, 8, 3, 101, 99], 10);
+    assert_eq!(codec.next_packet(), None);
+    codec.receive_nothing();
+    let packet = Packet {
+        dst: Destination::Computer(5),
+        x: 7,
+        y: 8,
+    };
+    assert_eq!(codec.next_packet(), Some(Ok(packet)));
+    assert_eq!(packet.payload(), network::Packet { x: 7, y: 8 });
+    assert_eq!(codec.next_packet(), None);
+    assert!(!codec.halted());
+    codec.receive_nothing();
+    assert!(codec.halted());
+}
+
+#[test]
+fn packets_sent_while_receiving() {
+    // reads x, sends a packet and reads y
+    let mut codec = boot(&[3, 100, 3, 101, 104, 1, 104, 2, 104, 3, 3, 102, 99], 10);
+    codec.receive(network::Packet { x: 10, y: 20 });
+    assert!(!codec.halted());
+    assert_eq!(
+        codec.next_packet(),
+        Some(Ok(Packet {
+            dst: Destination::Computer(1),
+            x: 2,
+            y: 3
+        }))
+    );
+    assert_eq!(codec.next_packet(), None);
+    assert!(codec.halted());
+}
+
+/// Sends (1, 2) to 99 and (3, 4) to the NAT, then reads input forever
+const MISADDRESSED: [i64; 19] = [
+    3, 100, 104, 99, 104, 1, 104, 2, 104, 255, 104, 3, 104, 4, 3, 101, 1105, 1, 14,
+];
+
+#[test]
+fn invalid_destinations() {
+    let mut codec = boot(&MISADDRESSED, 50);
+    let error = CodecError::InvalidDestination {
+        dst: 99,
+        x: 1,
+        y: 2,
+    };
+    assert_eq!(codec.next_packet(), Some(Err(error)));
+    assert_eq!(
+        error.to_string(),
+        "Trying to send to unknown address: 99 (1 2)"
+    );
+    assert_eq!(
+        codec.next_packet(),
+        Some(Ok(Packet {
+            dst: Destination::Nat,
+            x: 3,
+            y: 4
+        }))
+    );
+    assert_eq!(codec.next_packet(), None);
+}
+
+#[test]
+fn networks_drop_invalid_packets() {
+    let config = || NetworkConfig {
+        nodes: 2,
+        nat: Box::new(NoopNat),
+        termination: vec![Termination::Idle],
+        ..NetworkConfig::default()
+    };
+    let error = CodecError::InvalidDestination {
+        dst: 99,
+        x: 1,
+        y: 2,
+    };
+    let reports = [
+        Network::new(MISADDRESSED.to_vec(), config()).run(),
+        ThreadedNetwork::new(MISADDRESSED.to_vec(), config())
+            .unwrap()
+            .run(),
+    ];
+    for report in reports.iter() {
+        assert_eq!(report.termination, Termination::Idle);
+        assert_eq!(
+            report.first_nat_packet,
+            Some(network::Packet { x: 3, y: 4 })
+        );
+        let mut errors = report.errors.clone();
+        errors.sort_by_key(|(src, _)| *src);
+        assert_eq!(errors, vec![(0, error), (1, error)]);
+    }
+}
+
+#[test]
+fn truncated_packets() {
+    // sends the destination and x, then halts
+    let truncated = [3, 100, 104, 1, 104, 6, 99];
+    let mut codec = boot(&truncated, 2);
+    let error = CodecError::TruncatedPacket { dst: 1, x: Some(6) };
+    assert!(!codec.halted());
+    assert_eq!(codec.next_packet(), Some(Err(error)));
+    assert_eq!(error.to_string(), "Halted while sending to 1 (6 ...)");
+    assert_eq!(codec.next_packet(), None);
+    assert!(codec.halted());
+
+    let config = || NetworkConfig {
+        nodes: 2,
+        nat: Box::new(NoopNat),
+        ..NetworkConfig::default()
+    };
+    let reports = [
+        Network::new(truncated.to_vec(), config()).run(),
+        ThreadedNetwork::new(truncated.to_vec(), config())
+            .unwrap()
+            .run(),
+    ];
+    for report in reports.iter() {
+        assert_eq!(report.termination, Termination::Idle);
+        let mut errors = report.errors.clone();
+        errors.sort_by_key(|(src, _)| *src);
+        assert_eq!(errors, vec![(0, error), (1, error)]);
+    }
+}
@@ -0,0 +1,43 @@
+// every test crate uses only some of the helpers
+#![allow(dead_code)]
+
+use intcode::network::{IdleWakeupNat, Nat, Packet};
+
+/// The puzzle input of a day
+pub fn intcodes(day: u32) -> Vec<i64> {
+    let input = std::fs::read_to_string(format!("../day{}/input", day)).unwrap();
+    input
+        .trim()
+        .split(',')
+        .map(|code| code.parse().unwrap())
+        .collect()
+}
+
+/// Wakes the network a limited number of times
+pub struct LimitedNat {
+    nat: IdleWakeupNat,
+    left: usize,
+}
+
+impl LimitedNat {
+    pub fn new(address: usize, left: usize) -> LimitedNat {
+        LimitedNat {
+            nat: IdleWakeupNat::new(address),
+            left,
+        }
+    }
+}
+
+impl Nat for LimitedNat {
+    fn receive(&mut self, packet: Packet) {
+        self.nat.receive(packet);
+    }
+
+    fn wake_up(&mut self) -> Option<(usize, Packet)> {
+        if self.left == 0 {
+            return None;
+        }
+        self.left -= 1;
+        self.nat.wake_up()
+    }
+}
@@ -0,0 +1,201 @@
+use intcode::compiler::compile;
+use intcode::conformance::Interpreter;
+use intcode::interpreters::Computer;
+
+/// Compile the source and run it on the IntcodeComputer
+fn run(source: &str, inputs: &[i64]) -> Vec<i64> {
+    let program = compile(source).unwrap_or_else(|error| panic!("{}", error));
+    Computer.execute(&program, inputs).outputs
+}
+
+fn compile_error(source: &str) -> String {
+    compile(source).unwrap_err().to_string()
+}
+
+#[test]
+fn arithmetic_follows_precedence() {
+    let source = "
+        fn main() {
+            output(1 + 2 * 3);
+            output((1 + 2) * 3);
+            output(10 - 4 - 3);
+            output(-2 * -3 - -1);
+            output(100000 * 100000);
+        }";
+    assert_eq!(run(source, &[]), vec![7, 9, 3, 7, 10_000_000_000]);
+}
+
+#[test]
+fn comparisons_and_logic() {
+    let source = "
+        fn main() {
+            let a = input();
+            let b = input();
+            output(a < b);
+            output(a > b);
+            output(a <= b);
+            output(a >= b);
+            output(a == b);
+            output(a != b);
+            output(a && b);
+            output(a || b);
+            output(!a);
+        }";
+    assert_eq!(run(source, &[3, 5]), vec![1, 0, 1, 0, 0, 1, 1, 1, 0]);
+    assert_eq!(run(source, &[5, 5]), vec![0, 0, 1, 1, 1, 0, 1, 1, 0]);
+    assert_eq!(run(source, &[0, -2]), vec![0, 1, 0, 1, 0, 1, 0, 1, 1]);
+    assert_eq!(run(source, &[0, 0]), vec![0, 0, 1, 1, 1, 0, 0, 0, 1]);
+}
+
+#[test]
+fn if_else_chains() {
+    let source = "
+        fn main() {
+            let x = input();
+            if x < 0 {
+                output(-1);
+            } else if x == 0 {
+                output(0);
+            } else {
+                output(1);
+            }
+            if x { output(42); }
+        }";
+    assert_eq!(run(source, &[-7]), vec![-1, 42]);
+    assert_eq!(run(source, &[0]), vec![0]);
+    assert_eq!(run(source, &[9]), vec![1, 42]);
+}
+
+#[test]
+fn while_sums_inputs_until_zero() {
+    let source = "
+        fn main() {
+            let sum = 0;
+            let count = 0;
+            let x = input();
+            while x != 0 {
+                sum = sum + x;
+                count = count + 1;
+                x = input();
+            }
+            output(sum);
+            output(count);
+        }";
+    assert_eq!(run(source, &[4, 8, -3, 0]), vec![9, 3]);
+    assert_eq!(run(source, &[0]), vec![0, 0]);
+}
+
+#[test]
+fn blocks_scope_their_variables() {
+    let source = "
+        fn main() {
+            let x = 1;
+            if 1 {
+                let x = x + 10;
+                let y = 5;
+                output(x + y);
+            }
+            let y = 2;
+            output(x + y);
+        }";
+    assert_eq!(run(source, &[]), vec![16, 3]);
+}
+
+#[test]
+fn functions_take_arguments_and_return() {
+    let source = "
+        fn add(a, b) { return a + b; }
+        fn square(x) { return x * x; }
+        fn nothing() { return; }
+        fn main() {
+            output(add(square(3), add(1, 2)));
+            output(nothing());
+            output(square(add(2, 3)) - add(10, 0));
+        }";
+    assert_eq!(run(source, &[]), vec![12, 0, 15]);
+}
+
+#[test]
+fn locals_survive_calls() {
+    let source = "
+        fn clobber(a) {
+            let b = a * 2;
+            let c = b * 2;
+            return c;
+        }
+        fn main() {
+            let x = 7;
+            let y = 11;
+            let z = x + clobber(y) * clobber(1);
+            output(x);
+            output(y);
+            output(z);
+        }";
+    assert_eq!(run(source, &[]), vec![7, 11, 7 + 44 * 4]);
+}
+
+#[test]
+fn recursion_uses_the_stack() {
+    let source = "
+        fn factorial(n) {
+            if n <= 1 { return 1; }
+            return n * factorial(n - 1);
+        }
+        fn fibonacci(n) {
+            if n < 2 { return n; }
+            return fibonacci(n - 1) + fibonacci(n - 2);
+        }
+        fn main() {
+            let n = input();
+            output(factorial(n));
+            output(fibonacci(n));
+        }";
+    assert_eq!(run(source, &[10]), vec![3_628_800, 55]);
+    assert_eq!(run(source, &[20]), vec![2_432_902_008_176_640_000, 6765]);
+}
+
+#[test]
+fn output_streams_between_inputs() {
+    let source = "
+        // doubles every input until a negative one
+        fn main() {
+            let x = input();
+            while x >= 0 {
+                output(2 * x);
+                x = input();
+            }
+        }";
+    assert_eq!(run(source, &[1, 2, 3, -1]), vec![2, 4, 6]);
+}
+
+#[test]
+fn errors_name_the_line() {
+    assert_eq!(
+        compile_error("fn main() {\n    output(x);\n}"),
+        "line 2: Unknown variable x."
+    );
+    assert_eq!(
+        compile_error("fn main() {\n  let x = 1\n}"),
+        "line 3: Expected ';' but found '}'."
+    );
+    assert_eq!(
+        compile_error("fn f(a) { return a; }\nfn main() {\n  f(1, 2);\n}"),
+        "line 3: Function f takes 1 arguments but got 2."
+    );
+    assert_eq!(
+        compile_error("fn main() {\n  g();\n}"),
+        "line 2: Unknown function g."
+    );
+    assert_eq!(
+        compile_error("fn main() {\n  let a = 1;\n  let a = 2;\n}"),
+        "line 3: Variable a is already declared."
+    );
+    assert_eq!(
+        compile_error("fn main() {\n  output(5 / 2);\n}"),
+        "line 2: Unexpected character '/'."
+    );
+    assert_eq!(
+        compile_error("fn helper() {}"),
+        "line 1: Function main is missing."
+    );
+}
@@ -0,0 +1,53 @@
+use intcode::conformance::{run_suite, Interpreter, Report};
+use intcode::interpreters::{Computer, Day2, Day5, Day7};
+
+fn assert_conforms(interpreter: &dyn Interpreter) -> Report {
+    let report = run_suite(interpreter);
+    for failure in report.failures.iter() {
+        println!("{}: {}", failure.case, failure.reason);
+    }
+    assert!(
+        report.failures.is_empty(),
+        "{} failed {} of {} cases",
+        report.interpreter,
+        report.failures.len(),
+        report.failures.len() + report.passed.len()
+    );
+    report
+}
+
+#[test]
+fn intcode_computer_runs_every_case() {
+    let report = assert_conforms(&Computer);
+    assert!(report.skipped.is_empty());
+}
+
+#[test]
+fn day2_agrees_with_suite() {
+    let report = assert_conforms(&Day2);
+    // published examples plus add and multiply in position mode
+    assert_eq!(report.passed.len(), 5 + 2 + 1);
+}
+
+#[test]
+fn day5_agrees_with_suite() {
+    let report = assert_conforms(&Day5);
+    assert!(report
+        .passed
+        .iter()
+        .any(|case| case == "day5 compare 8 (9)"));
+    assert!(report.skipped.iter().any(|case| case == "day9 quine"));
+}
+
+#[test]
+fn day7_agrees_with_suite() {
+    let report = assert_conforms(&Day7);
+    assert!(report
+        .passed
+        .iter()
+        .any(|case| case == "day7 amplifier chained"));
+    assert!(report
+        .skipped
+        .iter()
+        .any(|case| case == "input after output"));
+}
@@ -0,0 +1,143 @@
+use intcode::computer::IntcodeComputer;
+use intcode::debugger::{DebugClient, DebugServer, Reply, StopEvent};
+use std::net::TcpListener;
+use std::thread;
+
+/// Reads numbers forever and outputs them doubled
+const DOUBLER: [i64; 15] = [3, 13, 1002, 13, 2, 14, 4, 14, 1105, 1, 0, 99, 0, 0, 0];
+
+fn ok(result: &str) -> Result<String, String> {
+    Ok(String::from(result))
+}
+
+fn reply(events: Vec<StopEvent>, result: &str) -> Reply {
+    Reply {
+        events,
+        result: ok(result),
+    }
+}
+
+#[test]
+fn debug_session_over_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let mut server = DebugServer::new(IntcodeComputer::new(DOUBLER.to_vec()));
+        server.serve_tcp(&listener).unwrap();
+    });
+
+    let mut client = DebugClient::connect_tcp(address).unwrap();
+    let mut command = |line: &str| client.command(line).unwrap();
+    assert_eq!(
+        command("continue"),
+        reply(vec![StopEvent::InputNeeded(0)], "")
+    );
+    assert_eq!(command("input 5 7"), reply(vec![], ""));
+    assert_eq!(
+        command("continue"),
+        reply(
+            vec![
+                StopEvent::Output(10),
+                StopEvent::Output(14),
+                StopEvent::InputNeeded(0)
+            ],
+            ""
+        )
+    );
+
+    assert_eq!(command("break 6"), reply(vec![], ""));
+    assert_eq!(command("break 11"), reply(vec![], ""));
+    assert_eq!(command("breakpoints"), reply(vec![], "6 11"));
+    assert_eq!(command("input 1"), reply(vec![], ""));
+    assert_eq!(
+        command("continue"),
+        reply(vec![StopEvent::Breakpoint(6)], "")
+    );
+    assert_eq!(command("read 13 2"), reply(vec![], "1 2"));
+    assert_eq!(
+        command("step"),
+        reply(vec![StopEvent::Output(2), StopEvent::Step(8)], "")
+    );
+    assert_eq!(command("registers"), reply(vec![], "ip 8 rb 0"));
+
+    // redirect the jump to the halt
+    assert_eq!(command("write 10 11"), reply(vec![], ""));
+    assert_eq!(command("read 8 3"), reply(vec![], "1105 1 11"));
+    assert_eq!(
+        command("step 5"),
+        reply(vec![StopEvent::Breakpoint(11)], "")
+    );
+    assert_eq!(command("clear 11"), reply(vec![], ""));
+    assert_eq!(
+        command("clear 11").result,
+        Err(String::from("No breakpoint at 11."))
+    );
+    assert_eq!(command("continue"), reply(vec![StopEvent::Halted], ""));
+    assert_eq!(command("continue"), reply(vec![StopEvent::Halted], ""));
+
+    assert_eq!(
+        command("read 125000").result,
+        Err(String::from("Address 125000 is out of memory."))
+    );
+    assert_eq!(
+        command("write 3 x").result,
+        Err(String::from("Invalid number x."))
+    );
+    assert_eq!(
+        command("jump 3").result,
+        Err(String::from("Unknown command jump."))
+    );
+    assert_eq!(command("quit"), reply(vec![], ""));
+    server.join().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn crash_is_reported_over_unix_socket() {
+    use std::os::unix::net::UnixListener;
+
+    let path = std::env::temp_dir().join(format!("intcode-debugger-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+    let server = thread::spawn(move || {
+        // adds and then runs into an unknown opcode
+        let mut server = DebugServer::new(IntcodeComputer::new(vec![1101, 2, 3, 7, 104, 5, 42, 0]));
+        server.serve_unix(&listener).unwrap();
+    });
+
+    let mut client = DebugClient::connect_unix(&path).unwrap();
+    let reply = client.command("continue").unwrap();
+    assert_eq!(reply.result, ok(""));
+    assert_eq!(reply.events[0], StopEvent::Output(5));
+    match &reply.events[1] {
+        StopEvent::Crashed(message) => assert!(message.contains("Unknown opcode 42")),
+        event => panic!("Expected a crash, got {:?}", event),
+    }
+    assert_eq!(client.command("read 7").unwrap().result, ok("5"));
+    assert!(client
+        .command("step")
+        .unwrap()
+        .result
+        .unwrap_err()
+        .starts_with("Computer crashed"));
+    assert_eq!(client.command("quit").unwrap().result, ok(""));
+    server.join().unwrap();
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn continue_stops_on_endless_programs() {
+    // jumps to itself forever
+    let endless = vec![1105, 1, 0];
+    let mut server = DebugServer::new(IntcodeComputer::new(endless.clone()));
+    let mut events = Vec::new();
+    assert_eq!(server.handle("continue", &mut events), ok(""));
+    assert_eq!(events, vec![StopEvent::Step(0)]);
+
+    let mut server = DebugServer::new(IntcodeComputer::new(endless));
+    server.set_continue_limit(100);
+    let mut events = Vec::new();
+    assert_eq!(server.handle("continue", &mut events), ok(""));
+    assert_eq!(server.handle("continue 3", &mut events), ok(""));
+    assert_eq!(events, vec![StopEvent::Step(0), StopEvent::Step(0)]);
+}
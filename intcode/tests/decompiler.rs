@@ -0,0 +1,139 @@
+use intcode::compiler::compile;
+use intcode::conformance::Interpreter;
+use intcode::decompiler::decompile;
+use intcode::interpreters::Computer;
+
+mod common;
+
+#[test]
+fn compiled_control_flow_is_structured() {
+    let source = "
+        fn fibonacci(n) {
+            if n < 2 { return n; }
+            return fibonacci(n - 1) + fibonacci(n - 2);
+        }
+        fn main() {
+            let sum = 0;
+            let x = input();
+            while x != 0 {
+                if x < 0 { sum = sum - x; } else { sum = sum + x; }
+                x = input();
+            }
+            output(fibonacci(sum));
+        }";
+    let program = compile(source).unwrap();
+    let code = decompile(&program);
+    assert!(!code.contains("goto"), "{}", code);
+    assert!(code.contains("fn main() {"), "{}", code);
+    assert!(
+        code.contains("(a1) {\n    v2 = a1 < 2\n    if v2 {"),
+        "{}",
+        code
+    );
+    assert!(code.contains("    loop {\n"), "{}", code);
+    assert!(code.contains("        if !v3 break\n"), "{}", code);
+    assert!(code.contains("        } else {\n"), "{}", code);
+    assert!(code.contains("input()"), "{}", code);
+    assert!(code.contains("return\n"), "{}", code);
+    // the definition, both recursive calls and the call from main
+    let name = code
+        .lines()
+        .find(|line| line.ends_with("(a1) {"))
+        .map(|line| &line[3..line.len() - 6])
+        .unwrap();
+    assert_eq!(code.matches(&format!("{}(", name)).count(), 4, "{}", code);
+}
+
+#[test]
+fn frames_calls_and_patched_loads() {
+    let mut program = vec![
+        109, 100, // rb += 100
+        3, 50, // [50] = input()
+        21101, 0, 15, 0, // return address
+        21001, 50, 0, 1, // argument
+        1105, 1, 18, // call
+        204, 1,  // output the result
+        99, //
+        109, 3, // frame of the function
+        1201, -2, 40, 25, // patch the address of the next read
+        21002, 0, 1, -2, // result = mem[a1 + 40]
+        109, -3, 2105, 1, 0,
+    ];
+    program.resize(45, 0);
+    program[42] = 17;
+    assert_eq!(Computer.execute(&program, &[2]).outputs, vec![17]);
+    assert_eq!(
+        decompile(&program),
+        "fn main() {
+    rb += 100
+    [50] = input()
+    f18([50])
+    output(v1)
+    halt
+}
+
+fn f18(a1) {
+    a1 = mem[a1 + 40]
+    return
+}
+"
+    );
+}
+
+#[test]
+fn unstructured_jumps_fall_back_to_goto() {
+    // jumps from outside into the middle of a loop
+    let program = vec![
+        3, 30, 1005, 30, 9, 104, 1, 104, 2, 1001, 30, -1, 30, 1005, 30, 5, 99,
+    ];
+    assert_eq!(Computer.execute(&program, &[2]).outputs, vec![1, 2]);
+    assert_eq!(
+        decompile(&program),
+        "fn main() {
+    [30] = input()
+    if ![30] {
+L5:
+        output(1)
+        output(2)
+    }
+    [30] = [30] - 1
+    if [30] goto L5
+    halt
+}
+"
+    );
+}
+
+#[test]
+fn callbacks_of_the_day25_program() {
+    let program = common::intcodes(25);
+    let code = decompile(&program);
+    assert!(
+        code.starts_with("fn main() {\n    rb += 4789\n"),
+        "{}",
+        code
+    );
+    // prints a string by calling f1256 for every character
+    assert!(code.contains("fn f1234(a1) {\n    f1174(a1, 1256)\n    return\n}"));
+    assert!(code.contains("fn f1256(a1, a2, a3) {"));
+    assert!(code.contains("call a2(mem[a1 + v3], v3, v4)"));
+}
+
+#[test]
+fn overflowing_constants() {
+    let (max, min) = (i64::MAX, i64::MIN);
+    let programs = [
+        // rb[0] = MAX + 1 and rb[0] = MAX * 2 in front of a constant jump
+        vec![21101, max, 1, 0, 21102, max, 2, 0, 1105, 1, 11, 99],
+        // a patch of the address MIN
+        vec![1101, 1, 1, min, 4, 0, 99],
+        // an argument far out of the frame
+        vec![21101, 0, 7, max / 2, 1105, 1, 9, 99, 0, 99],
+        // [8] = [7] + MIN can not be shown as a subtraction
+        vec![1001, 7, min, 8, 4, 8, 99, 0, 0],
+    ];
+    for program in programs.iter() {
+        let code = decompile(program);
+        assert!(code.starts_with("fn main() {"), "{}", code);
+    }
+}
@@ -0,0 +1,150 @@
+use intcode::adventure::{Direction, Room};
+use intcode::ascii_computer::AsciiComputer;
+use intcode::checkpoint::solve;
+use intcode::explorer::{explore, ShipMap};
+
+mod common;
+
+fn day25() -> AsciiComputer {
+    AsciiComputer::new(common::intcodes(25))
+}
+
+#[test]
+fn explores_the_whole_ship() {
+    let mut computer = day25();
+    let intro = computer.run();
+    let exploration = explore(&mut computer, &intro);
+
+    assert_eq!(exploration.start, "Hull Breach");
+    assert_eq!(exploration.map.rooms.len(), 20);
+    let mut inventory = exploration.inventory.clone();
+    inventory.sort();
+    assert_eq!(
+        inventory,
+        vec![
+            "food ration",
+            "fuel cell",
+            "jam",
+            "loom",
+            "manifold",
+            "mug",
+            "prime number",
+            "spool of cat6"
+        ]
+    );
+    let mut dangerous = exploration.dangerous.clone();
+    dangerous.sort();
+    assert_eq!(
+        dangerous,
+        vec![
+            "escape pod",
+            "giant electromagnet",
+            "infinite loop",
+            "molten lava",
+            "photons"
+        ]
+    );
+    assert_eq!(
+        exploration.checkpoint,
+        Some((String::from("Security Checkpoint"), Direction::North))
+    );
+    assert_eq!(
+        exploration.map.path("Hull Breach", "Security Checkpoint"),
+        Some(vec![
+            Direction::North,
+            Direction::West,
+            Direction::North,
+            Direction::West
+        ])
+    );
+
+    // the droid is back at the start and carries everything
+    let inventory = computer.enter_command("inv").unwrap();
+    assert!(inventory.contains("- spool of cat6"));
+    assert!(computer
+        .enter_command("north")
+        .unwrap()
+        .contains("== Hallway =="));
+}
+
+#[test]
+fn weighs_item_sets_until_the_floor_lets_the_droid_pass() {
+    let mut computer = day25();
+    let intro = computer.run();
+    let exploration = explore(&mut computer, &intro);
+    let (checkpoint, door) = exploration.checkpoint.unwrap();
+    for direction in exploration
+        .map
+        .path(&exploration.start, &checkpoint)
+        .unwrap()
+    {
+        computer.enter_command(&direction.to_string()).unwrap();
+    }
+
+    let solution = solve(&mut computer, &exploration.inventory, door).unwrap();
+    assert_eq!(solution.passcode, 537002052);
+    let mut items = solution.items.clone();
+    items.sort();
+    assert_eq!(
+        items,
+        vec!["food ration", "fuel cell", "mug", "prime number"]
+    );
+    // far less than all 256 sets of the 8 items
+    assert!(solution.attempts < 64, "{} attempts", solution.attempts);
+    assert!(computer.terminated);
+}
+
+#[test]
+fn too_many_items_to_weigh() {
+    let mut computer = day25();
+    computer.run();
+    let items: Vec<String> = (0..32).map(|n| format!("item {}", n)).collect();
+    assert!(solve(&mut computer, &items, Direction::North).is_none());
+    assert!(!computer.terminated);
+}
+
+#[test]
+fn map_as_graphviz_dot() {
+    let room = |name: &str, doors: Vec<Direction>, items: Vec<&str>| Room {
+        name: String::from(name),
+        description: String::new(),
+        doors,
+        items: items.into_iter().map(String::from).collect(),
+    };
+    let mut map = ShipMap::default();
+    map.add_room(&room(
+        "Stables",
+        vec![Direction::West],
+        vec!["giant \"electro\" magnet"],
+    ));
+    map.add_room(&room(
+        "Security Checkpoint",
+        vec![Direction::North, Direction::East],
+        vec![],
+    ));
+    map.add_room(&room(
+        "Pressure-Sensitive Floor",
+        vec![Direction::South],
+        vec![],
+    ));
+    map.connect("Stables", Direction::West, "Security Checkpoint");
+    map.connect(
+        "Security Checkpoint",
+        Direction::North,
+        "Pressure-Sensitive Floor",
+    );
+    assert_eq!(
+        map.to_dot(),
+        r#"digraph ship {
+    node [shape=box];
+    "Pressure-Sensitive Floor" [label="Pressure-Sensitive Floor", style=filled, fillcolor=tomato];
+    "Security Checkpoint" [label="Security Checkpoint", style=filled, fillcolor=gold];
+    "Stables" [label="Stables\n- giant \"electro\" magnet"];
+    "Pressure-Sensitive Floor" -> "Security Checkpoint" [label="south"];
+    "Security Checkpoint" -> "Pressure-Sensitive Floor" [label="north"];
+    "Security Checkpoint" -> "Stables" [label="east"];
+    "Stables" -> "Security Checkpoint" [label="west"];
+}
+"#
+    );
+}
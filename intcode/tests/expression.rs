@@ -0,0 +1,161 @@
+use intcode::expression::{CompileError, Expr};
+use intcode::springscript::{Mode, Reg, ScriptError, SpringScript};
+
+fn sensors(reading: u16) -> Vec<bool> {
+    (0..9).map(|i| reading & 1 << i != 0).collect()
+}
+
+/// The script jumps exactly for the readings the expression is true for
+fn assert_equivalent(expr: &Expr, script: &SpringScript) {
+    for reading in 0..1 << 9 {
+        assert_eq!(
+            script.jumps(&sensors(reading)),
+            Ok(expr.evaluate(reading)),
+            "{} with reading {:09b}:\n{}",
+            expr,
+            reading,
+            script
+        );
+    }
+}
+
+/// Random expressions over the sensors up to `sensors`
+fn random_exprs(sensors: usize, count: usize) -> Vec<Expr> {
+    let mut seed: u64 = 21;
+    let mut random = move |range: u64| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) % range
+    };
+    fn build(depth: usize, sensors: usize, random: &mut dyn FnMut(u64) -> u64) -> Expr {
+        match if depth == 0 { 0 } else { random(5) } {
+            0 | 1 => Expr::Sensor(Reg::SENSORS[random(sensors as u64) as usize]),
+            2 => !build(depth - 1, sensors, random),
+            3 => Expr::And((0..2).map(|_| build(depth - 1, sensors, random)).collect()),
+            _ => Expr::Or((0..2).map(|_| build(depth - 1, sensors, random)).collect()),
+        }
+    }
+    (0..count).map(|_| build(4, sensors, &mut random)).collect()
+}
+
+#[test]
+fn parse_and_print() {
+    let expr: Expr = "(!C & D & (E | H)) | !A".parse().unwrap();
+    assert_eq!(expr.to_string(), "!C & D & (E | H) | !A");
+    assert_eq!(expr.sensors(), 8);
+    assert_eq!(expr.to_string().parse::<Expr>().unwrap(), expr);
+    assert_eq!(
+        "!A || !(B && C) | true"
+            .parse::<Expr>()
+            .unwrap()
+            .to_string(),
+        "!A | !(B & C) | true"
+    );
+
+    assert_eq!(
+        "A & ".parse::<Expr>().unwrap_err(),
+        "column 5: Expression ends too early."
+    );
+    assert_eq!(
+        "((A | B)".parse::<Expr>().unwrap_err(),
+        "column 9: Expected ')'."
+    );
+    assert_eq!(
+        "A | J".parse::<Expr>().unwrap_err(),
+        "column 5: J is not a sensor."
+    );
+    assert_eq!(
+        "A B".parse::<Expr>().unwrap_err(),
+        "column 3: Unexpected input after the expression."
+    );
+    assert_eq!(
+        "A + B".parse::<Expr>().unwrap_err(),
+        "column 3: Unexpected '+'."
+    );
+    assert_eq!(
+        "A & | B".parse::<Expr>().unwrap_err(),
+        "column 5: Expected a sensor, a constant, '!' or '('."
+    );
+}
+
+#[test]
+fn simplify_expressions() {
+    let simplified = |text: &str| text.parse::<Expr>().unwrap().simplify().to_string();
+    assert_eq!(simplified("!!A"), "A");
+    assert_eq!(simplified("A & !A"), "false");
+    assert_eq!(simplified("B | true & !B"), "true");
+    assert_eq!(simplified("A | A & B"), "A");
+    assert_eq!(simplified("(A | B) & (A | !B)"), "A");
+    assert_eq!(simplified("!(!A & !B) & C & (C | D)"), "(A | B) & C");
+
+    for expr in random_exprs(9, 300).iter() {
+        let simplified = expr.simplify();
+        assert!(simplified.size() <= expr.size());
+        for reading in 0..1 << 9 {
+            assert_eq!(
+                simplified.evaluate(reading),
+                expr.evaluate(reading),
+                "{} to {}",
+                expr,
+                simplified
+            );
+        }
+    }
+}
+
+#[test]
+fn compiled_scripts_are_equivalent() {
+    let texts = [
+        "(!C & D & (E | H)) | !A",
+        "!A | !B & !E | !C & D & (E | H)",
+        "!(A & B & C) & D & (E | H)",
+        "true",
+        "false",
+        "!(A | B) | C & !(D | !E) | F & G & !H | I",
+    ];
+    for text in texts.iter() {
+        let expr: Expr = text.parse().unwrap();
+        assert_equivalent(&expr, &expr.compile(Mode::Run).unwrap());
+    }
+    let part1: Expr = "!A | !C & D".parse().unwrap();
+    assert_eq!(part1.compile(Mode::Walk).unwrap().instructions().len(), 4);
+
+    let mut compiled = 0;
+    for expr in random_exprs(9, 300).iter() {
+        match expr.compile(Mode::Run) {
+            Ok(script) => {
+                assert_equivalent(expr, &script);
+                compiled += 1;
+            }
+            Err(error) => assert!(matches!(error, CompileError::TooLong(length) if length > 15)),
+        }
+    }
+    assert!(compiled > 250);
+}
+
+#[test]
+fn compile_errors() {
+    let parity: Expr = "A & !B | !A & B".parse().unwrap();
+    let parity = Expr::Or(vec![
+        Expr::And(vec![
+            parity.clone(),
+            !"C & !D | !C & D".parse::<Expr>().unwrap(),
+        ]),
+        Expr::And(vec![!parity, "C & !D | !C & D".parse().unwrap()]),
+    ]);
+    let error = parity.compile(Mode::Walk).unwrap_err();
+    assert!(
+        matches!(error, CompileError::TooLong(length) if length > 15),
+        "{:?}",
+        error
+    );
+    assert!(error.to_string().ends_with("instructions, only 15 fit."));
+
+    let run_only: Expr = "!A | E".parse().unwrap();
+    assert_eq!(
+        run_only.compile(Mode::Walk).unwrap_err(),
+        CompileError::Script(ScriptError::SensorNotAvailable(Reg::E, Mode::Walk))
+    );
+    assert!(run_only.compile(Mode::Run).is_ok());
+}
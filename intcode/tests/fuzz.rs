@@ -0,0 +1,73 @@
+use intcode::conformance::{Feature, Interpreter, Outcome};
+use intcode::fuzz::{fuzz, FuzzConfig};
+use intcode::interpreters::{Computer, Day2, Day5, Day7};
+
+fn config(seed: u64, programs: usize) -> FuzzConfig {
+    FuzzConfig {
+        seed,
+        programs,
+        max_instructions: 8,
+        budget: 200,
+    }
+}
+
+#[test]
+fn interpreters_agree_on_random_programs() {
+    let report = fuzz(&config(2019, 2000), &[&Day2, &Day5, &Day7, &Computer]);
+    for divergence in report.divergences.iter() {
+        println!("{}", divergence);
+    }
+    assert!(report.divergences.is_empty());
+    for (interpreter, compared) in report.compared.iter() {
+        println!("{} compared on {} programs", interpreter, compared);
+        assert!(*compared >= 100, "{} was rarely compared", interpreter);
+    }
+}
+
+/// Behaves like the IntcodeComputer, but loses the last output
+struct DropsLastOutput;
+
+impl Interpreter for DropsLastOutput {
+    fn name(&self) -> &'static str {
+        "drops last output"
+    }
+
+    fn supports(&self, feature: Feature) -> bool {
+        Computer.supports(feature)
+    }
+
+    fn execute_within(&self, program: &[i64], inputs: &[i64], max_steps: usize) -> Option<Outcome> {
+        let mut outcome = Computer.execute_within(program, inputs, max_steps)?;
+        outcome.outputs.pop();
+        Some(outcome)
+    }
+}
+
+#[test]
+fn divergences_are_minimised() {
+    let report = fuzz(&config(7, 200), &[&DropsLastOutput]);
+    assert!(!report.divergences.is_empty());
+    for divergence in report.divergences.iter() {
+        // a single output instruction is all it takes
+        assert_eq!(divergence.program.instructions.len(), 1, "{}", divergence);
+        assert_eq!(divergence.program.instructions[0].opcode, 4);
+        assert!(divergence.inputs.is_empty());
+    }
+}
+
+#[test]
+fn runs_are_bounded_by_steps() {
+    let endless = [1105, 1, 0];
+    let interpreters: [&dyn Interpreter; 3] = [&Day5, &Day7, &Computer];
+    for interpreter in interpreters.iter() {
+        assert_eq!(interpreter.execute_within(&endless, &[], 1000), None);
+        // an addition and the halt
+        let program = [1101, 2, 3, 5, 99, 0];
+        assert_eq!(interpreter.execute_within(&program, &[], 1), None);
+        let outcome = interpreter.execute_within(&program, &[], 2).unwrap();
+        assert_eq!(outcome.memory[5], 5, "{}", interpreter.name());
+    }
+    let program = [1, 0, 0, 0, 99];
+    assert_eq!(Day2.execute_within(&program, &[], 1), None);
+    assert_eq!(Day2.execute_within(&program, &[], 2).unwrap().memory[0], 2);
+}
@@ -0,0 +1,130 @@
+use intcode::ascii_computer::{AsciiComputer, Output};
+use intcode::hull::{HullPattern, Outcome, Simulator};
+use intcode::springscript::{Mode, ScriptError, SpringScript};
+
+mod common;
+
+const WALK: &str = "NOT A J\nNOT C T\nAND D T\nOR T J\nWALK";
+const RUN: &str = "NOT C J\nAND D J\nOR E T\nOR H T\nAND T J\nNOT J T\nNOT T T\n\
+                   OR B T\nOR E T\nNOT T T\nOR T J\nNOT A T\nOR T J\nRUN";
+
+/// Run the script on the real droid. Returns the hull it fell on and where,
+/// or None if it made it across.
+fn crash(intcodes: &[i64], script: &SpringScript) -> Option<(HullPattern, usize)> {
+    let mut computer = AsciiComputer::new(intcodes.to_vec());
+    computer.run();
+    for instruction in script.instructions() {
+        computer.enter_command(&instruction.to_string()).unwrap();
+    }
+    computer.input_ascii(&script.mode().to_string()).unwrap();
+    let mut hulls = Vec::new();
+    for output in computer.lines() {
+        match output {
+            Output::Value(_) => return None,
+            Output::Line(line) if line.contains('#') => hulls.push(line),
+            Output::Line(_) => (),
+        }
+    }
+    let fall = hulls.last().unwrap().find('@').unwrap();
+    Some((hulls[0].parse().unwrap(), fall))
+}
+
+#[test]
+fn simulator_matches_the_droid() {
+    let intcodes = common::intcodes(21);
+    let scripts = [
+        "WALK",
+        "NOT A J\nWALK",
+        "NOT D J\nWALK",
+        "NOT C J\nAND D J\nWALK",
+        "NOT B J\nNOT A T\nOR T J\nWALK",
+        "NOT A J\nNOT C T\nAND D T\nOR T J\nRUN",
+        "NOT C J\nAND D J\nNOT A T\nOR T J\nRUN",
+        "NOT B J\nNOT C T\nOR T J\nAND D J\nAND H J\nNOT A T\nOR T J\nRUN",
+        WALK,
+        RUN,
+    ];
+    let mut patterns = Vec::new();
+    let mut survivors = Vec::new();
+    for text in scripts.iter() {
+        let script = SpringScript::parse(text).unwrap();
+        let simulator = Simulator::new(script.mode());
+        match crash(&intcodes, &script) {
+            Some((hull, fall)) => {
+                assert_eq!(
+                    simulator.run(&script, &hull),
+                    Ok(Outcome::Fell(fall)),
+                    "{} on {}",
+                    text,
+                    hull
+                );
+                patterns.push((script.mode(), hull));
+            }
+            None => survivors.push(script),
+        }
+    }
+    assert_eq!(patterns.len(), 7);
+    assert_eq!(survivors.len(), 3);
+
+    // scripts the droid made it across with get over every hull seen
+    for script in survivors.iter() {
+        for (mode, hull) in patterns.iter().filter(|(mode, _)| *mode == script.mode()) {
+            assert_eq!(
+                Simulator::new(*mode).run(script, hull),
+                Ok(Outcome::Survived),
+                "{}",
+                hull
+            );
+        }
+    }
+}
+
+#[test]
+fn droid_jumps_and_falls() {
+    let hull: HullPattern = "#####.#..########".parse().unwrap();
+    assert_eq!(hull.to_string(), "#####.#..########");
+    assert!(hull.is_ground(100));
+    assert_eq!(
+        "#.a".parse::<HullPattern>().unwrap_err(),
+        "Unknown hull tile a."
+    );
+
+    let simulator = Simulator::new(Mode::Walk);
+    assert_eq!(simulator.sensors(&hull, 2), vec![true, true, false, true]);
+    let never = SpringScript::parse("WALK").unwrap();
+    assert_eq!(simulator.run(&never, &hull), Ok(Outcome::Fell(5)));
+    let hole_ahead = SpringScript::parse("NOT A J\nWALK").unwrap();
+    assert_eq!(simulator.run(&hole_ahead, &hull), Ok(Outcome::Fell(8)));
+
+    // T and J start as false on every tile
+    let toggle = SpringScript::parse("NOT J J\nWALK").unwrap();
+    assert_eq!(toggle.jumps(&[true; 4]), Ok(true));
+    let stepping_stones: HullPattern = "#...#...#...".parse().unwrap();
+    assert_eq!(
+        simulator.run(&toggle, &stepping_stones),
+        Ok(Outcome::Survived)
+    );
+    let long_jump = Simulator {
+        sensors: 4,
+        jump: 5,
+    };
+    assert_eq!(
+        long_jump.run(&toggle, &stepping_stones),
+        Ok(Outcome::Fell(5))
+    );
+
+    // a RUN script reads more sensors than the simulator has
+    let run = SpringScript::parse("NOT J J\nRUN").unwrap();
+    assert_eq!(
+        run.jumps(&[true; 4]),
+        Err(ScriptError::MissingSensors(Mode::Run, 4))
+    );
+    assert_eq!(
+        simulator.run(&run, &stepping_stones),
+        Err(ScriptError::MissingSensors(Mode::Run, 4))
+    );
+    assert_eq!(
+        ScriptError::MissingSensors(Mode::Run, 4).to_string(),
+        "RUN mode reads 9 sensors, but only 4 were given."
+    );
+}
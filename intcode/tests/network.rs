@@ -0,0 +1,89 @@
+use intcode::network::{IdleWakeupNat, Network, NetworkConfig, NoopNat, Termination};
+
+mod common;
+use common::LimitedNat;
+
+#[test]
+fn puzzle_network() {
+    let report = Network::new(common::intcodes(23), NetworkConfig::default()).run();
+    assert_eq!(report.termination, Termination::RepeatedWakeupY);
+    assert_eq!(report.first_nat_packet.unwrap().y, 20372);
+    assert_eq!(report.repeated_y, Some(13334));
+    let (address, last) = *report.wakeups.last().unwrap();
+    assert_eq!((address, last.y), (0, 13334));
+}
+
+#[test]
+fn termination_conditions() {
+    let config = NetworkConfig {
+        nat: Box::new(NoopNat),
+        termination: vec![Termination::FirstNatPacket],
+        ..NetworkConfig::default()
+    };
+    let report = Network::new(common::intcodes(23), config).run();
+    assert_eq!(report.termination, Termination::FirstNatPacket);
+    assert_eq!(report.first_nat_packet.unwrap().y, 20372);
+
+    let config = NetworkConfig {
+        nat: Box::new(NoopNat),
+        termination: vec![Termination::Idle, Termination::Rounds(1000)],
+        ..NetworkConfig::default()
+    };
+    let report = Network::new(common::intcodes(23), config).run();
+    assert_eq!(report.termination, Termination::Idle);
+    assert!(report.wakeups.is_empty());
+    assert!(report.rounds < 1000);
+
+    let config = NetworkConfig {
+        termination: vec![Termination::Rounds(3)],
+        ..NetworkConfig::default()
+    };
+    let report = Network::new(common::intcodes(23), config).run();
+    assert_eq!(report.termination, Termination::Rounds(3));
+    assert_eq!(report.rounds, 3);
+
+    let config = NetworkConfig {
+        termination: vec![Termination::Wakeups(2)],
+        ..NetworkConfig::default()
+    };
+    let report = Network::new(common::intcodes(23), config).run();
+    assert_eq!(report.termination, Termination::Wakeups(2));
+    assert_eq!(report.wakeups.len(), 2);
+    assert_eq!(report.idle, 2);
+}
+
+#[test]
+fn custom_nat() {
+    let config = NetworkConfig {
+        nat: Box::new(LimitedNat::new(7, 2)),
+        termination: vec![Termination::Idle],
+        ..NetworkConfig::default()
+    };
+    let report = Network::new(common::intcodes(23), config).run();
+    assert_eq!(report.termination, Termination::Idle);
+    assert_eq!(report.wakeups.len(), 2);
+    assert!(report.wakeups.iter().all(|(address, _)| *address == 7));
+}
+
+#[test]
+fn stops_once_the_nat_gives_up() {
+    let config = NetworkConfig {
+        nat: Box::new(NoopNat),
+        ..NetworkConfig::default()
+    };
+    let report = Network::new(common::intcodes(23), config).run();
+    assert_eq!(report.termination, Termination::Idle);
+    assert!(report.wakeups.is_empty());
+}
+
+#[test]
+fn wakeups_outside_the_network() {
+    let config = NetworkConfig {
+        nat: Box::new(IdleWakeupNat::new(50)),
+        ..NetworkConfig::default()
+    };
+    let report = Network::new(common::intcodes(23), config).run();
+    assert_eq!(report.termination, Termination::Idle);
+    assert!(report.wakeups.is_empty());
+    assert_eq!(report.invalid_wakeup.map(|(address, _)| address), Some(50));
+}
@@ -0,0 +1,96 @@
+use intcode::ascii_computer::AsciiComputer;
+use intcode::repl::Repl;
+
+mod common;
+
+fn day25() -> Repl {
+    let mut repl = Repl::new(AsciiComputer::new(common::intcodes(25)));
+    assert!(repl.start().contains("== Hull Breach =="));
+    repl
+}
+
+#[test]
+fn save_load_and_undo() {
+    let mut repl = day25();
+    assert!(repl.handle("east").contains("== Hot Chocolate Fountain =="));
+    assert_eq!(
+        repl.handle("take food ration"),
+        "\nYou take the food ration.\n\nCommand?\n"
+    );
+    assert_eq!(repl.handle(":inv"), "- food ration\n");
+    assert_eq!(repl.handle(":save fountain"), "Saved fountain.\n");
+
+    assert!(repl.handle("west").contains("== Hull Breach =="));
+    assert_eq!(
+        repl.handle(":map"),
+        "Hot Chocolate Fountain: east -> ?, south -> ?, west -> Hull Breach [food ration]\n\
+         Hull Breach: north -> ?, east -> Hot Chocolate Fountain\n"
+    );
+    assert_eq!(repl.handle(":undo"), "Back in Hot Chocolate Fountain.\n");
+    assert!(repl
+        .handle("drop food ration")
+        .contains("You drop the food ration."));
+    assert_eq!(repl.handle(":inv"), "You aren't carrying any items.\n");
+
+    assert_eq!(repl.handle(":load fountain"), "Loaded fountain.\n");
+    assert_eq!(repl.handle(":inv"), "- food ration\n");
+    assert_eq!(repl.transcript(), "east\ntake food ration\n");
+    // the load can be undone as well
+    repl.handle(":undo");
+    assert_eq!(repl.handle(":inv"), "You aren't carrying any items.\n");
+
+    assert_eq!(repl.handle(":load nothing"), "No save named nothing.\n");
+    assert_eq!(
+        repl.handle(":teleport"),
+        "Unknown meta command :teleport, try :help.\n"
+    );
+}
+
+#[test]
+fn transcripts_replay_the_game() {
+    let mut repl = day25();
+    for command in ["north", "north", "west", "take mug", "east"].iter() {
+        repl.handle(command);
+    }
+    let transcript = repl.transcript();
+    let path = std::env::temp_dir().join(format!("day25-transcript-{}", std::process::id()));
+    let path = path.to_str().unwrap();
+    assert_eq!(
+        repl.handle(&format!(":transcript {}", path)),
+        format!("Wrote 5 commands to {}.\n", path)
+    );
+
+    let mut replayed = day25();
+    let text = replayed.handle(&format!(":replay {}", path));
+    assert!(text.starts_with("> north\n"));
+    assert!(text.contains("> take mug\n\nYou take the mug."));
+    assert_eq!(replayed.handle(":inv"), "- mug\n");
+    assert_eq!(replayed.transcript(), transcript);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn non_ascii_commands_are_rejected() {
+    let mut repl = day25();
+    assert_eq!(
+        repl.handle("nörth"),
+        "Non ascii character 'ö' in command.\n"
+    );
+    assert_eq!(repl.transcript(), "");
+    assert_eq!(repl.handle(":undo"), "Nothing to undo.\n");
+}
+
+#[test]
+fn transcripts_replaying_themselves() {
+    let path = std::env::temp_dir().join(format!("day25-recursive-{}", std::process::id()));
+    let path = path.to_str().unwrap();
+    std::fs::write(path, format!("east\n:replay {}\ntake food ration\n", path)).unwrap();
+    let mut repl = day25();
+    let text = repl.handle(&format!(":replay {}", path));
+    assert!(text.contains(&format!(
+        "> :replay {}\nSkipped replay within a replay.\n",
+        path
+    )));
+    assert_eq!(repl.transcript(), "east\ntake food ration\n");
+    std::fs::remove_file(path).unwrap();
+}
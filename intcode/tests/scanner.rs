@@ -0,0 +1,83 @@
+use intcode::arcade::Arcade;
+use intcode::computer::IntcodeComputer;
+use intcode::scanner::{locate, Filter, MemoryScanner};
+
+mod common;
+
+#[test]
+fn filters_narrow_the_candidates() {
+    let mut computer = IntcodeComputer::new(vec![5, 5, 5, 7, 9]);
+    let mut scanner = MemoryScanner::in_range(&computer, 0..5);
+    assert_eq!(scanner.filter(&computer, Filter::Equals(5)), 3);
+    computer.write_memory(0, 6);
+    computer.write_memory(1, 4);
+    assert_eq!(scanner.filter(&computer, Filter::Unchanged), 1);
+    assert_eq!(scanner.candidates(), vec![2]);
+    assert!(scanner.converged());
+
+    let mut scanner = MemoryScanner::in_range(&computer, 0..5);
+    computer.write_memory(3, 8);
+    computer.write_memory(4, 8);
+    assert_eq!(scanner.filter(&computer, Filter::Increased), 1);
+    assert_eq!(scanner.candidates(), vec![3]);
+    scanner.patch(&mut computer, 42).unwrap();
+    assert_eq!(computer.read_memory(3), 42);
+    assert_eq!(scanner.filter(&computer, Filter::Decreased), 0);
+    assert_eq!(
+        scanner.patch(&mut computer, 1),
+        Err(String::from("No candidate left to patch."))
+    );
+}
+
+#[test]
+fn changed_narrows_the_ball_position() {
+    let mut intcodes = common::intcodes(13);
+    intcodes[0] = 2;
+    let mut arcade = Arcade::new(intcodes);
+    arcade.run();
+    let mut scanner = MemoryScanner::new(&arcade.computer);
+    scanner.filter(&arcade.computer, Filter::Equals(arcade.ball_pos.0));
+    // the ball moves diagonally, so its x changes every frame
+    for _ in 0..2 {
+        arcade.follow_ball();
+        arcade.run();
+        scanner.filter(&arcade.computer, Filter::Changed);
+        scanner.filter(&arcade.computer, Filter::Equals(arcade.ball_pos.0));
+    }
+    assert_eq!(scanner.candidates(), vec![388]);
+}
+
+#[test]
+fn locate_game_values() {
+    let intcodes = common::intcodes(13);
+    let located = locate(
+        &intcodes,
+        &[
+            |arcade: &Arcade| arcade.ball_pos.0,
+            |arcade: &Arcade| arcade.horizontal_pos.0,
+            |arcade: &Arcade| arcade.score,
+            // never stored anywhere
+            |_: &Arcade| 123_456_789,
+        ],
+    );
+    let addresses: Vec<Option<Vec<usize>>> = located
+        .iter()
+        .map(|scanner| scanner.as_ref().map(|scanner| scanner.candidates()))
+        .collect();
+    assert_eq!(
+        addresses,
+        vec![Some(vec![388]), Some(vec![392]), Some(vec![386]), None]
+    );
+
+    let mut intcodes = intcodes;
+    intcodes[0] = 2;
+    let mut arcade = Arcade::new(intcodes);
+    arcade.run();
+    let score = located[2].as_ref().unwrap();
+    score.patch(&mut arcade.computer, 1_000_000).unwrap();
+    while !arcade.computer.terminated {
+        arcade.follow_ball();
+        arcade.run();
+    }
+    assert!(arcade.score > 1_000_000);
+}
@@ -0,0 +1,103 @@
+use intcode::network::{Network, NetworkConfig, NetworkReport, Termination};
+use intcode::schedule::{Faults, Rng, Schedule};
+
+mod common;
+
+fn run(schedule: Schedule, faults: Faults) -> NetworkReport {
+    let config = NetworkConfig {
+        schedule,
+        faults,
+        termination: vec![Termination::RepeatedWakeupY, Termination::Rounds(20000)],
+        ..NetworkConfig::default()
+    };
+    Network::new(common::intcodes(23), config).run()
+}
+
+const SCHEDULES: [Schedule; 5] = [
+    Schedule::RoundRobin,
+    Schedule::UntilBlocked,
+    Schedule::Random(1),
+    Schedule::Random(7),
+    Schedule::Random(2019),
+];
+
+#[test]
+fn parse_schedules() {
+    assert_eq!("round-robin".parse(), Ok(Schedule::RoundRobin));
+    assert_eq!("until-blocked".parse(), Ok(Schedule::UntilBlocked));
+    assert_eq!("random:42".parse(), Ok(Schedule::Random(42)));
+    assert_eq!(
+        "random:x".parse::<Schedule>(),
+        Err(String::from("Invalid seed x."))
+    );
+    assert_eq!(
+        "fifo".parse::<Schedule>(),
+        Err(String::from("Unknown schedule fifo."))
+    );
+}
+
+#[test]
+fn all_schedules_converge() {
+    for &schedule in SCHEDULES.iter() {
+        let report = run(schedule, Faults::default());
+        assert_eq!(report.termination, Termination::RepeatedWakeupY);
+        assert_eq!(report.first_nat_packet.unwrap().y, 20372);
+        assert_eq!(report.repeated_y, Some(13334), "{:?}", schedule);
+    }
+}
+
+#[test]
+fn duplicated_packets_converge() {
+    for seed in 1..4 {
+        let faults = Faults {
+            seed,
+            duplicate: 10,
+            ..Faults::default()
+        };
+        for &schedule in SCHEDULES.iter() {
+            let report = run(schedule, faults);
+            assert_eq!(report.repeated_y, Some(13334), "{:?} {}", schedule, seed);
+        }
+    }
+}
+
+#[test]
+fn reordered_and_delayed_packets_diverge() {
+    let reorder = Faults {
+        seed: 1,
+        reorder: 30,
+        ..Faults::default()
+    };
+    let delay = Faults {
+        seed: 1,
+        delay: 30,
+        max_delay: 5,
+        ..Faults::default()
+    };
+    // whether a run still reaches the answer of the puzzle depends on the
+    // seeds, but every run terminates with the NAT repeating itself
+    for &faults in [reorder, delay].iter() {
+        for &schedule in SCHEDULES.iter() {
+            let report = run(schedule, faults);
+            assert_eq!(
+                report.termination,
+                Termination::RepeatedWakeupY,
+                "{:?} {:?}",
+                schedule,
+                faults
+            );
+            // the first packet to the NAT does not depend on the order
+            assert_eq!(report.first_nat_packet.unwrap().y, 20372);
+            // runs are reproducible from the seeds
+            assert_eq!(report, run(schedule, faults));
+        }
+    }
+}
+
+#[test]
+fn close_seeds_differ() {
+    let first: Vec<u64> = (0..8).map(|seed| Rng::new(seed).next_u64()).collect();
+    for (i, a) in first.iter().enumerate() {
+        assert!(first[i + 1..].iter().all(|b| a != b));
+    }
+}
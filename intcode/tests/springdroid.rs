@@ -0,0 +1,97 @@
+use intcode::corpus::Corpus;
+use intcode::hull::{HullPattern, Outcome, Simulator};
+use intcode::springdroid::{DroidError, SpringDroid};
+use intcode::springscript::{Mode, SpringScript};
+use std::fs;
+
+mod common;
+
+const WALK: &str = "NOT A J\nNOT C T\nAND D T\nOR T J\nWALK";
+const RUN: &str = "NOT C J\nAND D J\nOR E T\nOR H T\nAND T J\nNOT J T\nNOT T T\n\
+                   OR B T\nOR E T\nNOT T T\nOR T J\nNOT A T\nOR T J\nRUN";
+
+fn execute(script: &str) -> Result<i64, DroidError> {
+    let mut springdroid = SpringDroid::new(common::intcodes(21));
+    springdroid.execute(&SpringScript::parse(script).unwrap())
+}
+
+#[test]
+fn droid_reports_damage_or_hull() {
+    assert_eq!(execute(WALK), Ok(19355790));
+    assert_eq!(execute(RUN), Ok(1140920822));
+    assert_eq!(
+        execute("NOT A J\nWALK"),
+        Err(DroidError::Fell("#####.#..########".parse().unwrap()))
+    );
+    assert_eq!(
+        execute("NOT A J\nNOT C T\nAND D T\nOR T J\nRUN"),
+        Err(DroidError::Fell("#####.#.##..#####".parse().unwrap()))
+    );
+}
+
+#[test]
+fn unexpected_droid_output() {
+    // reads a character, then prints "Hi" and halts
+    let mut springdroid = SpringDroid::new(vec![3, 9, 104, 72, 104, 105, 104, 10, 99, 0]);
+    assert_eq!(
+        springdroid.execute(&SpringScript::parse("WALK").unwrap()),
+        Err(DroidError::UnexpectedOutput(String::from("Hi\n")))
+    );
+}
+
+#[test]
+fn hull_from_crash_frames() {
+    let text = "Walking...\n\nDidn't make it across:\n\n\
+                .................\n.................\n@................\n#####.###########\n\n\
+                .................\n.................\n.....@...........\n#####.###########\n\n\
+                .................\n.................\n.................\n#####@###########\n";
+    let hull = HullPattern::from_crash(text).unwrap();
+    assert_eq!(hull.to_string(), "#####.###########");
+    assert_eq!(HullPattern::from_crash("Walking...\n"), None);
+}
+
+#[test]
+fn corpus_round_trip() {
+    let path = std::env::temp_dir().join(format!("hull_patterns_{}", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let mut corpus = Corpus::load(&path).unwrap();
+    assert!(corpus.is_empty());
+    assert!(corpus.add(Mode::Walk, "#####.#..########".parse().unwrap()));
+    assert!(corpus.add(Mode::Run, "#####.#..########".parse().unwrap()));
+    assert!(!corpus.add(Mode::Walk, "#####.#..########".parse().unwrap()));
+    corpus.save().unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "WALK #####.#..########\nRUN #####.#..########\n"
+    );
+
+    let corpus = Corpus::load(&path).unwrap();
+    assert_eq!(corpus.len(), 2);
+    assert_eq!(corpus.patterns(Mode::Run).count(), 1);
+    fs::write(&path, "WALK #####\nJUMP ####\n").unwrap();
+    let error = Corpus::load(&path).err().unwrap();
+    assert!(
+        error.to_string().ends_with(":2: Unknown mode JUMP."),
+        "{}",
+        error
+    );
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn solutions_survive_the_corpus() {
+    let corpus = Corpus::load("../day21/hull_patterns").unwrap();
+    assert!(corpus.len() >= 6);
+    for text in [WALK, RUN].iter() {
+        let script = SpringScript::parse(text).unwrap();
+        let simulator = Simulator::new(script.mode());
+        for hull in corpus.patterns(script.mode()) {
+            assert_eq!(
+                simulator.run(&script, hull),
+                Ok(Outcome::Survived),
+                "{}",
+                hull
+            );
+        }
+    }
+}
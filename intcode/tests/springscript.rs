@@ -0,0 +1,62 @@
+use intcode::springscript::{Mode, Op, Reg, ScriptError, SpringInstr, SpringScript, MAX_INSTRUCTIONS};
+
+#[test]
+fn builder_validates_instructions() {
+    let mut script = SpringScript::new(Mode::Walk);
+    assert_eq!(script.and(Reg::A, Reg::B).err(), Some(ScriptError::InvalidDestination(Reg::B)));
+    assert_eq!(
+        script.or(Reg::E, Reg::J).err(),
+        Some(ScriptError::SensorNotAvailable(Reg::E, Mode::Walk))
+    );
+    for _ in 0..MAX_INSTRUCTIONS {
+        script.not(Reg::T, Reg::J).unwrap();
+    }
+    assert_eq!(script.not(Reg::A, Reg::J).err(), Some(ScriptError::TooManyInstructions));
+    assert_eq!(script.instructions().len(), MAX_INSTRUCTIONS);
+
+    let mut script = SpringScript::new(Mode::Run);
+    script.or(Reg::I, Reg::T).unwrap().and(Reg::T, Reg::J).unwrap();
+    assert_eq!(
+        script.instructions()[0],
+        SpringInstr { op: Op::Or, src: Reg::I, dst: Reg::T }
+    );
+}
+
+#[test]
+fn parse_and_print_round_trip() {
+    let script = SpringScript::parse(
+        "
+        // jump over a hole in front
+        NOT A J
+        NOT C T   // hole three tiles ahead
+        AND D T
+        OR T J
+
+        WALK
+        ",
+    )
+    .unwrap();
+    assert_eq!(script.mode(), Mode::Walk);
+    let text = script.to_string();
+    assert_eq!(text, "NOT A J\nNOT C T\nAND D T\nOR T J\nWALK\n");
+    assert_eq!(SpringScript::parse(&text).unwrap(), script);
+}
+
+#[test]
+fn parse_errors_name_the_line() {
+    let error = SpringScript::parse("NOT A J\nOR E J\nWALK").unwrap_err();
+    assert_eq!(
+        error,
+        ScriptError::AtLine(2, Box::new(ScriptError::SensorNotAvailable(Reg::E, Mode::Walk)))
+    );
+    assert_eq!(error.to_string(), "line 2: Sensor E can not be read in WALK mode.");
+    assert_eq!(SpringScript::parse("NOT A J\nOR E J\nRUN").unwrap().instructions().len(), 2);
+
+    let error = SpringScript::parse("NOT A J\n\nXOR A J\nWALK").unwrap_err();
+    assert_eq!(error.to_string(), "line 3: Unknown instruction XOR.");
+    let error = SpringScript::parse("AND A C\nRUN").unwrap_err();
+    assert_eq!(error.to_string(), "line 1: Register C can not be written, only T and J.");
+    let error = SpringScript::parse("RUN\nNOT A J").unwrap_err();
+    assert_eq!(error.to_string(), "line 2: Unexpected 'NOT A J' after the mode.");
+    assert_eq!(SpringScript::parse("NOT A J").unwrap_err(), ScriptError::MissingMode);
+}
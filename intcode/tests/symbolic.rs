@@ -0,0 +1,78 @@
+use intcode::day2::run_intcodes;
+use intcode::symbolic::{execute_symbolic, solve_noun_verb, Method, Usage};
+
+mod common;
+
+fn day2() -> Vec<usize> {
+    common::intcodes(2)
+        .iter()
+        .map(|&code| code as usize)
+        .collect()
+}
+
+/// Value at address 0 after running the intcodes with noun and verb
+fn output(intcodes: &[usize], noun: usize, verb: usize) -> usize {
+    let mut memory = intcodes.to_vec();
+    memory[1] = noun;
+    memory[2] = verb;
+    run_intcodes(&mut memory);
+    memory[0]
+}
+
+#[test]
+fn puzzle_output_is_linear() {
+    let intcodes = day2();
+    let solution = solve_noun_verb(&intcodes, 19690720).unwrap();
+    assert_eq!(solution.method, Method::Linear);
+    assert_eq!(100 * solution.noun + solution.verb, 4259);
+    assert_eq!(output(&intcodes, solution.noun, solution.verb), 19690720);
+    let polynomial = solution.output.unwrap().polynomial().unwrap();
+    assert!(polynomial.is_linear());
+    assert_eq!(
+        polynomial.evaluate(&[(1, 12), (2, 2)]) as usize,
+        output(&intcodes, 12, 2)
+    );
+}
+
+#[test]
+fn products_are_evaluated_for_every_pair() {
+    // overwrites its own write address, then stores noun * verb at 0
+    let intcodes = [1, 0, 0, 3, 2, 1, 2, 0, 99];
+    let solution = solve_noun_verb(&intcodes, 9702).unwrap();
+    assert_eq!(solution.method, Method::Polynomial);
+    assert_eq!((solution.noun, solution.verb), (98, 99));
+    let polynomial = solution.output.unwrap().polynomial().unwrap();
+    assert!(!polynomial.is_linear());
+    assert_eq!(polynomial.to_string(), "noun*verb");
+}
+
+#[test]
+fn loads_from_noun_and_verb_fall_back_to_enumeration() {
+    // address 0 ends up as the sum of the cells noun and verb point to
+    let mut intcodes = vec![1, 0, 0, 0, 99];
+    intcodes.extend(5..100);
+    let execution = execute_symbolic(&intcodes, &[1, 2]);
+    assert!(execution.memory.unwrap()[0].polynomial().is_none());
+    assert!(execution
+        .constraints
+        .iter()
+        .all(|constraint| constraint.usage == Usage::ReadAddress));
+    assert_eq!(execution.constraints.len(), 2);
+
+    let solution = solve_noun_verb(&intcodes, 150).unwrap();
+    assert_eq!(solution.method, Method::Enumeration);
+    // noun 2 points to the verb itself
+    assert_eq!((solution.noun, solution.verb), (2, 75));
+    assert_eq!(output(&intcodes, 2, 75), 150);
+}
+
+#[test]
+fn unreachable_targets() {
+    // below the output of noun and verb 0
+    assert!(solve_noun_verb(&day2(), 1).is_none());
+    // a prime above 99 is no product of two numbers below 100
+    assert!(solve_noun_verb(&[1, 0, 0, 3, 2, 1, 2, 0, 99], 9973).is_none());
+    let mut intcodes = vec![1, 0, 0, 0, 99];
+    intcodes.extend(5..100);
+    assert!(solve_noun_verb(&intcodes, 199).is_none());
+}
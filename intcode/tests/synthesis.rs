@@ -0,0 +1,148 @@
+use intcode::corpus::Corpus;
+use intcode::hull::{HullPattern, Outcome, Simulator};
+use intcode::logic::{self, Cube, TruthTable};
+use intcode::springscript::{Mode, Op, Reg, SpringInstr};
+use intcode::synthesis::{derive_table, synthesise};
+
+mod common;
+
+/// Value of J after the instructions, without the limit of a script
+fn jumps(instructions: &[SpringInstr], reading: u16) -> bool {
+    let (mut t, mut j) = (false, false);
+    for instruction in instructions.iter() {
+        let src = match instruction.src {
+            Reg::T => t,
+            Reg::J => j,
+            sensor => reading & 1 << sensor as usize != 0,
+        };
+        let dst = if instruction.dst == Reg::T {
+            &mut t
+        } else {
+            &mut j
+        };
+        *dst = match instruction.op {
+            Op::And => *dst && src,
+            Op::Or => *dst || src,
+            Op::Not => !src,
+        };
+    }
+    j
+}
+
+/// Tables with random rows, some left open
+fn random_tables(sensors: usize, count: usize) -> Vec<TruthTable> {
+    let mut seed: u64 = 2019;
+    let mut random = move || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        seed >> 33
+    };
+    (0..count)
+        .map(|_| {
+            let mut table = TruthTable::new(sensors);
+            for reading in 0..1 << sensors {
+                match random() % 3 {
+                    0 => (),
+                    value => {
+                        table.rows.insert(reading, value == 1);
+                    }
+                }
+            }
+            table
+        })
+        .collect()
+}
+
+#[test]
+fn compiled_tables_agree_with_every_row() {
+    for table in random_tables(4, 200).iter() {
+        let cubes = logic::minimise(table);
+        for negate in [false, true].iter() {
+            let complement = table.complement();
+            let (function, cubes) = if *negate {
+                (&complement, logic::minimise(&complement))
+            } else {
+                (table, cubes.clone())
+            };
+            let instructions = logic::compile(&cubes, *negate);
+            for (&reading, &value) in function.rows.iter() {
+                assert!(cubes.iter().any(|cube| cube.contains(reading)) == value);
+                assert_eq!(
+                    jumps(&instructions, reading),
+                    value != *negate,
+                    "{:?}",
+                    cubes
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn minimised_functions() {
+    // jump if a hole is ahead and there is ground to land on: !A | !C & D
+    let mut table = TruthTable::new(4);
+    for reading in 0..16 {
+        let ground = |i: usize| reading & 1 << i != 0;
+        table
+            .rows
+            .insert(reading, !ground(0) || !ground(2) && ground(3));
+    }
+    let cubes = logic::minimise(&table);
+    let names: Vec<String> = cubes.iter().map(|cube| cube.to_string()).collect();
+    assert_eq!(names, vec!["!A", "!C & D"]);
+    assert_eq!(logic::compile(&cubes, false).len(), 4);
+    assert_eq!(
+        logic::script_for(&table, Mode::Walk)
+            .unwrap()
+            .instructions()
+            .len(),
+        4
+    );
+
+    let anything = TruthTable::new(9);
+    assert_eq!(logic::minimise(&anything), Vec::<Cube>::new());
+    let mut always = TruthTable::new(9);
+    always.rows.insert(0, true);
+    always.rows.insert(511, true);
+    let cubes = logic::minimise(&always);
+    assert_eq!(cubes, vec![Cube { mask: 0, value: 0 }]);
+    assert_eq!(logic::compile(&cubes, false).len(), 1);
+}
+
+#[test]
+fn derived_table_gets_over_the_corpus() {
+    let corpus = Corpus::load("../day21/hull_patterns").unwrap();
+    for &mode in [Mode::Walk, Mode::Run].iter() {
+        let simulator = Simulator::new(mode);
+        let hulls: Vec<&HullPattern> = corpus.patterns(mode).collect();
+        let table = derive_table(&hulls, &simulator).unwrap();
+        let script = logic::script_for(&table, mode).unwrap();
+        for hull in hulls.iter() {
+            assert_eq!(
+                simulator.run(&script, hull),
+                Ok(Outcome::Survived),
+                "{}",
+                hull
+            );
+        }
+    }
+    // no droid gets over two holes of four tiles in a row
+    let hull: HullPattern = "#####....#....####".parse().unwrap();
+    assert_eq!(derive_table(&[&hull], &Simulator::new(Mode::Run)), None);
+}
+
+#[test]
+fn synthesised_scripts_get_the_droid_across() {
+    let intcodes = common::intcodes(21);
+    let path = std::env::temp_dir().join(format!("synthesis_patterns_{}", std::process::id()));
+    let mut corpus = Corpus::load(&path).unwrap();
+
+    let walk = synthesise(&intcodes, Mode::Walk, &mut corpus).unwrap();
+    assert_eq!(walk.damage, 19355790);
+    let run = synthesise(&intcodes, Mode::Run, &mut corpus).unwrap();
+    assert_eq!(run.damage, 1140920822);
+    assert!(run.rounds > 1);
+    assert_eq!(corpus.len(), walk.rounds + run.rounds - 2);
+}
@@ -0,0 +1,151 @@
+use intcode::capture::Kind;
+use intcode::network::{IdleWakeupNat, NetworkConfig, NoopNat, Termination};
+use intcode::schedule::{Faults, Schedule};
+use intcode::threaded::ThreadedNetwork;
+
+mod common;
+use common::LimitedNat;
+
+#[test]
+fn same_answers_as_single_thread() {
+    let config = NetworkConfig {
+        nat: Box::new(NoopNat),
+        termination: vec![Termination::FirstNatPacket],
+        ..NetworkConfig::default()
+    };
+    let report = ThreadedNetwork::new(common::intcodes(23), config)
+        .unwrap()
+        .run();
+    assert_eq!(report.termination, Termination::FirstNatPacket);
+    assert_eq!(report.first_nat_packet.unwrap().y, 20372);
+
+    for _ in 0..5 {
+        let report = ThreadedNetwork::new(common::intcodes(23), NetworkConfig::default())
+            .unwrap()
+            .run();
+        assert_eq!(report.termination, Termination::RepeatedWakeupY);
+        assert_eq!(report.repeated_y, Some(13334));
+        assert_eq!(report.idle, report.wakeups.len());
+        assert_eq!(report.rounds, 0);
+    }
+}
+
+#[test]
+fn quiescence() {
+    let config = NetworkConfig {
+        nat: Box::new(NoopNat),
+        termination: vec![Termination::Idle],
+        capture: true,
+        ..NetworkConfig::default()
+    };
+    let report = ThreadedNetwork::new(common::intcodes(23), config)
+        .unwrap()
+        .run();
+    assert_eq!(report.termination, Termination::Idle);
+    assert_eq!(report.idle, 1);
+    // once idle every packet sent to a computer was taken
+    let records = &report.capture;
+    let sent = records
+        .iter()
+        .filter(|record| record.kind == Kind::Send && record.dst != Some(255))
+        .count();
+    let received = records
+        .iter()
+        .filter(|record| record.kind == Kind::Receive)
+        .count();
+    assert_eq!(sent, received);
+    assert_eq!(records.last().unwrap().kind, Kind::Idle);
+
+    let config = NetworkConfig {
+        termination: vec![Termination::Wakeups(3)],
+        ..NetworkConfig::default()
+    };
+    let report = ThreadedNetwork::new(common::intcodes(23), config)
+        .unwrap()
+        .run();
+    assert_eq!(report.termination, Termination::Wakeups(3));
+    assert_eq!(report.wakeups.len(), 3);
+}
+
+#[test]
+fn rejects_what_only_runs_on_one_thread() {
+    let configs = vec![
+        NetworkConfig {
+            schedule: Schedule::Random(1),
+            ..NetworkConfig::default()
+        },
+        NetworkConfig {
+            faults: Faults {
+                seed: 1,
+                reorder: 2,
+                ..Faults::default()
+            },
+            ..NetworkConfig::default()
+        },
+        NetworkConfig {
+            termination: vec![Termination::Idle, Termination::Rounds(3)],
+            ..NetworkConfig::default()
+        },
+    ];
+    for config in configs {
+        assert!(ThreadedNetwork::new(common::intcodes(23), config).is_err());
+    }
+}
+
+#[test]
+fn stops_once_the_nat_gives_up() {
+    let config = NetworkConfig {
+        nat: Box::new(LimitedNat::new(7, 2)),
+        termination: vec![Termination::RepeatedWakeupY],
+        ..NetworkConfig::default()
+    };
+    let report = ThreadedNetwork::new(common::intcodes(23), config)
+        .unwrap()
+        .run();
+    assert_eq!(report.termination, Termination::Idle);
+    assert_eq!(report.idle, 3);
+    assert!(report.wakeups.iter().all(|(address, _)| *address == 7));
+}
+
+#[test]
+fn wakeups_outside_the_network() {
+    let config = NetworkConfig {
+        nat: Box::new(IdleWakeupNat::new(50)),
+        ..NetworkConfig::default()
+    };
+    let report = ThreadedNetwork::new(common::intcodes(23), config)
+        .unwrap()
+        .run();
+    assert_eq!(report.termination, Termination::Idle);
+    assert!(report.wakeups.is_empty());
+    assert_eq!(report.invalid_wakeup.map(|(address, _)| address), Some(50));
+}
+
+#[test]
+fn packets_to_halted_nodes() {
+    // node 0 halts right away, every other node sends (5, 6) to it and then
+    // keeps reading its input
+    let program = [
+        3, 100, 1006, 100, 16, 104, 0, 104, 5, 104, 6, 3, 101, 1105, 1, 11, 99,
+    ];
+    for _ in 0..500 {
+        let config = NetworkConfig {
+            nodes: 2,
+            nat: Box::new(NoopNat),
+            termination: vec![Termination::Idle],
+            capture: true,
+            ..NetworkConfig::default()
+        };
+        let report = ThreadedNetwork::new(program.to_vec(), config)
+            .unwrap()
+            .run();
+        assert_eq!(report.termination, Termination::Idle);
+        // the packet was sent, but never taken
+        let sent = report
+            .capture
+            .iter()
+            .filter(|record| record.kind == Kind::Send && record.dst == Some(0))
+            .count();
+        assert_eq!(sent, 1);
+    }
+}